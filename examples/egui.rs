@@ -63,13 +63,5 @@ impl eframe::App for MyApp {
 }
 
 fn load_icon(path: &std::path::Path) -> tray_icon_win::Icon {
-    let (icon_rgba, icon_width, icon_height) = {
-        let image = image::open(path)
-            .expect("Failed to open icon path")
-            .into_rgba8();
-        let (width, height) = image.dimensions();
-        let rgba = image.into_raw();
-        (rgba, width, height)
-    };
-    tray_icon_win::Icon::from_rgba(icon_rgba, icon_width, icon_height).expect("Failed to open icon")
+    tray_icon_win::Icon::from_path(path, None).expect("Failed to open icon")
 }
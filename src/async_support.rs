@@ -0,0 +1,129 @@
+//! Async adapters for the events of this crate.
+//!
+//! Tray icon and menu events can be handled together, e.g. with `select!`:
+//!
+//! ```no_run
+//! # async fn run() -> tray_icon_win::Result<()> {
+//! use futures::{select, StreamExt};
+//! use tray_icon_win::{async_support::menu_event_stream, TrayIconEvent};
+//!
+//! let mut tray_events = TrayIconEvent::stream()?.fuse();
+//! let mut menu_events = menu_event_stream()?.fuse();
+//! loop {
+//!     select! {
+//!         event = tray_events.select_next_some() => println!("tray: {event:?}"),
+//!         event = menu_events.select_next_some() => println!("menu: {event:?}"),
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    task::{Context, Poll},
+};
+
+use futures_channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures_core::Stream;
+
+use crate::{
+    menu::{MenuEvent, MenuEventHandler},
+    TrayIconEvent,
+};
+
+/// The sender of the existing [`TrayIconEventStream`], if any.
+static TRAY_EVENT_STREAM: Mutex<Option<UnboundedSender<TrayIconEvent>>> = Mutex::new(None);
+/// Whether a [`MenuEventStream`] exists.
+static MENU_EVENT_STREAM: AtomicBool = AtomicBool::new(false);
+
+/// A stream of [`TrayIconEvent`]s, see [`TrayIconEvent::stream`].
+///
+/// Dropping it sends events to the handler or channel again.
+#[derive(Debug)]
+pub struct TrayIconEventStream {
+    receiver: UnboundedReceiver<TrayIconEvent>,
+}
+
+impl TrayIconEventStream {
+    pub(crate) fn new() -> crate::Result<Self> {
+        let mut stream = TRAY_EVENT_STREAM.lock().unwrap_or_else(|e| e.into_inner());
+        if stream.is_some() {
+            return Err(crate::Error::EventStreamActive);
+        }
+
+        let (sender, receiver) = unbounded();
+        *stream = Some(sender);
+        Ok(Self { receiver })
+    }
+}
+
+impl Stream for TrayIconEventStream {
+    type Item = TrayIconEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for TrayIconEventStream {
+    fn drop(&mut self) {
+        TRAY_EVENT_STREAM
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+    }
+}
+
+/// Sends `event` to the existing stream, or returns it if there is none.
+pub(crate) fn send_tray_event(event: TrayIconEvent) -> Option<TrayIconEvent> {
+    match &*TRAY_EVENT_STREAM.lock().unwrap_or_else(|e| e.into_inner()) {
+        Some(sender) => sender.unbounded_send(event).err().map(|e| e.into_inner()),
+        None => Some(event),
+    }
+}
+
+/// Returns a stream of new [`MenuEvent`]s.
+///
+/// This installs a [`MenuEvent::set_event_handler`] forwarding to the stream, events are
+/// buffered from this call on, so none are lost before the stream is first polled. Dropping
+/// the stream restores the previous handler. Only one stream may exist at a time, requesting
+/// another one returns [`Error::EventStreamActive`](crate::Error::EventStreamActive).
+pub fn menu_event_stream() -> crate::Result<MenuEventStream> {
+    if MENU_EVENT_STREAM.swap(true, Ordering::AcqRel) {
+        return Err(crate::Error::EventStreamActive);
+    }
+
+    let (sender, receiver) = unbounded();
+    let previous = MenuEvent::set_event_handler(Some(move |event| {
+        let _ = sender.unbounded_send(event);
+    }));
+    Ok(MenuEventStream { receiver, previous })
+}
+
+/// A stream of [`MenuEvent`]s, see [`menu_event_stream`].
+///
+/// Receiving the next event with `StreamExt::next` is cancel-safe, events aren't lost when
+/// the future is dropped before completing.
+pub struct MenuEventStream {
+    receiver: UnboundedReceiver<MenuEvent>,
+    previous: Option<Option<MenuEventHandler>>,
+}
+
+impl Stream for MenuEventStream {
+    type Item = MenuEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for MenuEventStream {
+    fn drop(&mut self) {
+        MenuEvent::set_event_handler(self.previous.take().flatten());
+        MENU_EVENT_STREAM.store(false, Ordering::Release);
+    }
+}
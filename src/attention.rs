@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use crate::Icon;
+
+/// How the tray icon draws attention, see [`TrayIcon::set_attention`](crate::TrayIcon::set_attention).
+#[derive(Debug, Clone)]
+pub struct AttentionConfig {
+    /// The icon alternated with the tray icon, `None` blanks the icon instead.
+    pub icon: Option<Icon>,
+    /// How long each of the two icons is shown.
+    pub interval: Duration,
+    /// Stops drawing attention after this long, runs until the user interacts with the icon
+    /// when `None`.
+    pub max_duration: Option<Duration>,
+}
+
+impl Default for AttentionConfig {
+    /// Blanks the icon every half second until the user interacts with it.
+    fn default() -> Self {
+        Self {
+            icon: None,
+            interval: Duration::from_millis(500),
+            max_duration: None,
+        }
+    }
+}
@@ -1,12 +1,49 @@
 use std::fmt;
 use std::io;
 
+use crate::BadIcon;
+
 #[non_exhaustive]
 /// Errors returned by tray-icon.
 #[derive(Debug)]
 pub enum Error {
     OsError(io::Error),
     NotMainThread,
+    /// An icon couldn't be created, e.g. by [`TrayIconBuilder::with_icon_from_path`](crate::TrayIconBuilder::with_icon_from_path).
+    BadIcon(BadIcon),
+    /// A notification title is longer than 63 UTF-16 code units.
+    NotificationTitleTooLong {
+        len: usize,
+    },
+    /// A notification body is longer than 255 UTF-16 code units.
+    NotificationBodyTooLong {
+        len: usize,
+    },
+    /// A notification was rejected because the notification queue is full, see
+    /// [`TrayIcon::set_notification_queue_limit`](crate::TrayIcon::set_notification_queue_limit).
+    NotificationQueueFull,
+    /// A tooltip is longer than `max` UTF-16 code units.
+    TooltipTooLong {
+        len: usize,
+        max: usize,
+    },
+    /// An event stream was requested while another one exists, see `TrayIconEvent::stream`.
+    EventStreamActive,
+    /// The event channel capacity was set after the channel was used, see
+    /// [`TrayIconEvent::set_channel_capacity`](crate::TrayIconEvent::set_channel_capacity).
+    EventChannelInUse,
+    /// A menu was requested to be shown while the tray icon has none.
+    NoMenu,
+    /// The shell rejected the GUID of
+    /// [`TrayIconBuilder::with_guid`](crate::TrayIconBuilder::with_guid) when adding the tray
+    /// icon, usually because it is registered for another executable path. Adding the icon
+    /// without a GUID still works.
+    GuidRejected,
+    /// The tray icon was removed with [`TrayIcon::remove`](crate::TrayIcon::remove).
+    Removed,
+    /// A live tray icon already has the id, see
+    /// [`TrayIconBuilder::allow_duplicate_ids`](crate::TrayIconBuilder::allow_duplicate_ids).
+    DuplicateId(crate::TrayIconId),
 }
 
 impl core::fmt::Display for Error {
@@ -14,11 +51,60 @@ impl core::fmt::Display for Error {
         match self {
             Error::OsError(err) => write!(f, "OS error: {}", err),
             Error::NotMainThread => write!(f, "Not on the main thread"),
+            Error::BadIcon(err) => write!(f, "Bad icon: {}", err),
+            Error::NotificationTitleTooLong { len } => write!(
+                f,
+                "Notification title is {} UTF-16 code units long, at most {} are allowed",
+                len,
+                crate::notification::MAX_TITLE_LEN
+            ),
+            Error::NotificationBodyTooLong { len } => write!(
+                f,
+                "Notification body is {} UTF-16 code units long, at most {} are allowed",
+                len,
+                crate::notification::MAX_BODY_LEN
+            ),
+            Error::NotificationQueueFull => write!(f, "Notification queue is full"),
+            Error::TooltipTooLong { len, max } => write!(
+                f,
+                "Tooltip is {} UTF-16 code units long, at most {} are allowed",
+                len, max
+            ),
+            Error::EventStreamActive => write!(f, "An event stream already exists"),
+            Error::EventChannelInUse => write!(f, "The event channel is already in use"),
+            Error::NoMenu => write!(f, "The tray icon has no menu"),
+            Error::GuidRejected => write!(f, "The shell rejected the tray icon GUID"),
+            Error::Removed => write!(f, "The tray icon was removed"),
+            Error::DuplicateId(id) => write!(f, "A tray icon with id {:?} exists", id.0),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::OsError(err) => Some(err),
+            Error::BadIcon(err) => Some(err),
+            Error::NotMainThread
+            | Error::NotificationTitleTooLong { .. }
+            | Error::NotificationBodyTooLong { .. }
+            | Error::NotificationQueueFull
+            | Error::TooltipTooLong { .. }
+            | Error::EventStreamActive
+            | Error::EventChannelInUse
+            | Error::NoMenu
+            | Error::GuidRejected
+            | Error::Removed
+            | Error::DuplicateId(_) => None,
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl From<BadIcon> for Error {
+    fn from(err: BadIcon) -> Self {
+        Error::BadIcon(err)
+    }
+}
 
 /// Convenient type alias of Result type for tray-icon.
 pub type Result<T> = core::result::Result<T, Error>;
@@ -5,7 +5,16 @@
 // taken from https://github.com/rust-windowing/winit/blob/92fdf5ba85f920262a61cee4590f4a11ad5738d1/src/icon.rs
 
 use crate::platform_impl::PlatformIcon;
-use std::{error::Error, fmt, io, mem};
+use std::{
+    error::Error,
+    fmt,
+    hash::{Hash, Hasher},
+    io, mem,
+    path::PathBuf,
+};
+
+pub(crate) mod compose;
+pub(crate) mod ico;
 
 #[repr(C)]
 #[derive(Debug)]
@@ -24,6 +33,9 @@ pub enum BadIcon {
     /// Produced when the length of the `rgba` argument isn't divisible by 4, thus `rgba` can't be
     /// safely interpreted as 32bpp RGBA pixels.
     ByteCountNotDivisibleBy4 { byte_count: usize },
+    /// Produced when the length of the `rgb` argument passed to [`Icon::from_rgb`] isn't
+    /// divisible by 3, thus `rgb` can't be safely interpreted as 24bpp RGB pixels.
+    ByteCountNotDivisibleBy3 { byte_count: usize },
     /// Produced when the number of pixels (`rgba.len() / 4`) isn't equal to `width * height`.
     /// At least one of your arguments is incorrect.
     DimensionsVsPixelCount {
@@ -34,6 +46,26 @@ pub enum BadIcon {
     },
     /// Produced when underlying OS functionality failed to create the icon
     OsError(io::Error),
+    /// Produced when the file passed to [`Icon::from_path`] doesn't exist.
+    FileNotFound(PathBuf),
+    /// Produced when the file passed to [`Icon::from_path`] isn't a supported image format.
+    /// Contains the file extension, if any.
+    UnsupportedFormat(Option<String>),
+    /// Produced when the image data couldn't be decoded, e.g. a corrupt or truncated file.
+    DecodeError(String),
+    /// Produced when no icon resource with the given name exists in the current module.
+    ResourceNotFound(String),
+    /// Produced when the `stride` passed to [`Icon::from_rgba_with_stride`] is smaller than
+    /// `width * 4`, so rows would overlap.
+    StrideTooSmall { stride: usize, min_stride: usize },
+    /// Produced when the `rgba` buffer passed to [`Icon::from_rgba_with_stride`] is shorter
+    /// than `stride * height` bytes.
+    BufferTooSmall { byte_count: usize, expected: usize },
+    /// Produced when a handle passed to [`Icon::from_hbitmap`] isn't a valid bitmap.
+    InvalidHandle,
+    /// Produced when the data passed to [`Icon::from_ico_bytes`] isn't a valid `.ico` file.
+    /// Contains a description of what is wrong with it.
+    MalformedIco(&'static str),
 }
 
 impl fmt::Display for BadIcon {
@@ -43,6 +75,10 @@ impl fmt::Display for BadIcon {
                 "The length of the `rgba` argument ({:?}) isn't divisible by 4, making it impossible to interpret as 32bpp RGBA pixels.",
                 byte_count,
             ),
+            BadIcon::ByteCountNotDivisibleBy3 { byte_count } => write!(f,
+                "The length of the `rgb` argument ({:?}) isn't divisible by 3, making it impossible to interpret as 24bpp RGB pixels.",
+                byte_count,
+            ),
             BadIcon::DimensionsVsPixelCount {
                 width,
                 height,
@@ -53,17 +89,43 @@ impl fmt::Display for BadIcon {
                 width, height, pixel_count, width_x_height,
             ),
             BadIcon::OsError(e) => write!(f, "OS error when instantiating the icon: {:?}", e),
+            BadIcon::FileNotFound(path) => {
+                write!(f, "The icon file {:?} doesn't exist.", path)
+            }
+            BadIcon::UnsupportedFormat(Some(ext)) => {
+                write!(f, "Unsupported icon format {:?}, expected `ico` or `png`.", ext)
+            }
+            BadIcon::UnsupportedFormat(None) => {
+                write!(f, "Unsupported icon format, expected `ico` or `png`.")
+            }
+            BadIcon::DecodeError(e) => write!(f, "Failed to decode the icon image: {}", e),
+            BadIcon::ResourceNotFound(name) => {
+                write!(f, "No icon resource named {:?} found in this module.", name)
+            }
+            BadIcon::StrideTooSmall { stride, min_stride } => write!(f,
+                "The specified stride ({:?}) is smaller than the length of a row of pixels ({:?}).",
+                stride, min_stride,
+            ),
+            BadIcon::BufferTooSmall { byte_count, expected } => write!(f,
+                "The length of the `rgba` argument ({:?}) is smaller than `stride * height` ({:?}).",
+                byte_count, expected,
+            ),
+            BadIcon::InvalidHandle => write!(f, "The handle isn't a valid bitmap."),
+            BadIcon::MalformedIco(reason) => write!(f, "Malformed ICO data: {}", reason),
         }
     }
 }
 
 impl Error for BadIcon {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
-        Some(self)
+        match self {
+            BadIcon::OsError(e) => Some(e),
+            _ => None,
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct RgbaIcon {
     pub(crate) rgba: Vec<u8>,
     pub(crate) width: u32,
@@ -74,6 +136,28 @@ pub(crate) struct RgbaIcon {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct NoIcon;
 
+/// Converts 8-bit PNG output pixels, as produced by `normalize_to_color8`, to RGBA.
+pub(crate) fn png_to_rgba(buf: Vec<u8>, color_type: png::ColorType) -> Result<Vec<u8>, BadIcon> {
+    Ok(match color_type {
+        png::ColorType::Rgba => buf,
+        png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], u8::MAX])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, u8::MAX]).collect(),
+        // `normalize_to_color8` expands palettes, so this shouldn't happen
+        png::ColorType::Indexed => {
+            return Err(BadIcon::DecodeError(
+                "unexpected indexed color output".to_string(),
+            ))
+        }
+    })
+}
+
 #[allow(dead_code)] // These are not used on every platform
 mod constructors {
     use super::*;
@@ -86,11 +170,12 @@ mod constructors {
                 });
             }
             let pixel_count = rgba.len() / PIXEL_SIZE;
-            if pixel_count != (width * height) as usize {
+            let width_x_height = width as usize * height as usize;
+            if pixel_count != width_x_height {
                 Err(BadIcon::DimensionsVsPixelCount {
                     width,
                     height,
-                    width_x_height: (width * height) as usize,
+                    width_x_height,
                     pixel_count,
                 })
             } else {
@@ -103,6 +188,114 @@ mod constructors {
         }
     }
 
+    impl RgbaIcon {
+        /// Expands 24bpp RGB pixels into opaque RGBA pixels.
+        pub fn from_rgb(rgb: &[u8], width: u32, height: u32) -> Result<Self, BadIcon> {
+            if rgb.len() % 3 != 0 {
+                return Err(BadIcon::ByteCountNotDivisibleBy3 {
+                    byte_count: rgb.len(),
+                });
+            }
+            let pixel_count = rgb.len() / 3;
+            let width_x_height = width as usize * height as usize;
+            if pixel_count != width_x_height {
+                return Err(BadIcon::DimensionsVsPixelCount {
+                    width,
+                    height,
+                    width_x_height,
+                    pixel_count,
+                });
+            }
+
+            let rgba = rgb
+                .chunks_exact(3)
+                .flat_map(|p| [p[0], p[1], p[2], u8::MAX])
+                .collect();
+            RgbaIcon::from_rgba(rgba, width, height)
+        }
+
+        /// Packs rows of `stride` bytes into a tightly packed 32bpp RGBA buffer.
+        pub fn from_rgba_with_stride(
+            rgba: &[u8],
+            width: u32,
+            height: u32,
+            stride: usize,
+        ) -> Result<Self, BadIcon> {
+            let row_len = (width as usize).saturating_mul(PIXEL_SIZE);
+            if stride < row_len {
+                return Err(BadIcon::StrideTooSmall {
+                    stride,
+                    min_stride: row_len,
+                });
+            }
+            // an overflowing size can't be backed by any buffer
+            let expected = stride.saturating_mul(height as usize);
+            if rgba.len() < expected {
+                return Err(BadIcon::BufferTooSmall {
+                    byte_count: rgba.len(),
+                    expected,
+                });
+            }
+
+            let mut packed = Vec::with_capacity(row_len * height as usize);
+            for y in 0..height as usize {
+                let start = y * stride;
+                packed.extend_from_slice(&rgba[start..start + row_len]);
+            }
+
+            RgbaIcon::from_rgba(packed, width, height)
+        }
+
+        /// Decodes a PNG image into 32bpp RGBA pixels.
+        pub fn from_png<R: io::Read>(reader: R) -> Result<Self, BadIcon> {
+            let decode_error = |e: png::DecodingError| BadIcon::DecodeError(e.to_string());
+
+            let mut decoder = png::Decoder::new(reader);
+            decoder.set_transformations(png::Transformations::normalize_to_color8());
+            let mut reader = decoder.read_info().map_err(decode_error)?;
+            let mut buf = vec![0; reader.output_buffer_size()];
+            let info = reader.next_frame(&mut buf).map_err(decode_error)?;
+            buf.truncate(info.buffer_size());
+
+            let rgba = png_to_rgba(buf, info.color_type)?;
+            RgbaIcon::from_rgba(rgba, info.width, info.height)
+        }
+    }
+
+    #[cfg(feature = "svg")]
+    impl RgbaIcon {
+        /// Rasterizes an SVG document into a `size` x `size` RGBA image, keeping its aspect ratio.
+        pub fn from_svg(svg: &str, size: u32) -> Result<Self, BadIcon> {
+            use resvg::{tiny_skia, usvg};
+
+            let tree = usvg::Tree::from_str(svg, &usvg::Options::default())
+                .map_err(|e| BadIcon::DecodeError(e.to_string()))?;
+            let mut pixmap = tiny_skia::Pixmap::new(size, size).ok_or_else(|| {
+                BadIcon::DecodeError(format!("invalid rasterization size {}", size))
+            })?;
+
+            let tree_size = tree.size();
+            let scale = (size as f32 / tree_size.width()).min(size as f32 / tree_size.height());
+            let transform = tiny_skia::Transform::from_scale(scale, scale).post_translate(
+                (size as f32 - tree_size.width() * scale) / 2.,
+                (size as f32 - tree_size.height() * scale) / 2.,
+            );
+            resvg::render(&tree, transform, &mut pixmap.as_mut());
+
+            // tiny-skia stores premultiplied pixels
+            let rgba = pixmap
+                .pixels()
+                .iter()
+                .flat_map(|pixel| {
+                    let color = pixel.demultiply();
+                    [color.red(), color.green(), color.blue(), color.alpha()]
+                })
+                .collect();
+
+            RgbaIcon::from_rgba(rgba, size, size)
+        }
+    }
+
     impl NoIcon {
         pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
             // Create the rgba icon anyway to validate the input
@@ -112,6 +305,57 @@ mod constructors {
     }
 }
 
+/// Options controlling how an [`Icon`] is created from pixel data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IconOptions {
+    /// Keeps the pixel data at its original size instead of rescaling it to the tray icon
+    /// size, for icons that are already scaled for the current DPI.
+    pub no_resize: bool,
+}
+
+/// The corner of an icon an overlay is drawn in, see [`Icon::with_overlay`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverlayCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// How [`Icon::tint`] combines the tint color with the pixels of an icon.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TintMode {
+    /// Multiplies each color channel by the tint color, so white becomes the tint color
+    /// and darker pixels stay darker.
+    #[default]
+    Multiply,
+    /// Replaces the color of each pixel with the tint color, keeping only the shape of
+    /// the icon.
+    Replace,
+}
+
+/// A built-in shell icon, see [`Icon::from_system`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StockIcon {
+    Application,
+    Document,
+    Folder,
+    Error,
+    Warning,
+    Info,
+    Help,
+    Shield,
+    Lock,
+    Key,
+    Users,
+    World,
+    Find,
+    Settings,
+    Delete,
+    Recycler,
+}
+
 /// An icon used for the window titlebar, taskbar, etc.
 #[derive(Clone)]
 pub struct Icon {
@@ -124,21 +368,264 @@ impl fmt::Debug for Icon {
     }
 }
 
+/// Icons are equal when they have the same dimensions and pixels, regardless of how they were
+/// created. Icons whose pixels can't be read back, e.g. monochrome ones, are only equal to
+/// icons sharing the same handle.
+///
+/// The pixels are read back and hashed the first time an icon is compared or hashed.
+impl PartialEq for Icon {
+    fn eq(&self, other: &Self) -> bool {
+        if self.as_raw_handle() == other.as_raw_handle() {
+            return true;
+        }
+        match (self.inner.content_hash(), other.inner.content_hash()) {
+            // rule out hash collisions
+            (Some(hash), Some(other_hash)) if hash == other_hash => {
+                self.inner.to_rgba().ok() == other.inner.to_rgba().ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Icon {}
+
+impl Hash for Icon {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.inner.content_hash() {
+            Some(hash) => hash.hash(state),
+            None => self.as_raw_handle().hash(state),
+        }
+    }
+}
+
+/// Creates an icon from the pixels of an image, see [`Icon::from_rgba`].
+#[cfg(feature = "image")]
+impl TryFrom<image::RgbaImage> for Icon {
+    type Error = BadIcon;
+
+    fn try_from(image: image::RgbaImage) -> Result<Self, Self::Error> {
+        let (width, height) = image.dimensions();
+        Icon::from_rgba(image.into_raw(), width, height)
+    }
+}
+
+/// Converts the image to RGBA and creates an icon from its pixels, see [`Icon::from_rgba`].
+#[cfg(feature = "image")]
+impl TryFrom<image::DynamicImage> for Icon {
+    type Error = BadIcon;
+
+    fn try_from(image: image::DynamicImage) -> Result<Self, Self::Error> {
+        Icon::try_from(image.into_rgba8())
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<image::ImageError> for BadIcon {
+    fn from(err: image::ImageError) -> Self {
+        match err {
+            image::ImageError::IoError(err) => BadIcon::OsError(err),
+            err => BadIcon::DecodeError(err.to_string()),
+        }
+    }
+}
+
+/// The serialized form of an [`Icon`], with base64 encoded RGBA pixels.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedIcon {
+    rgba: String,
+    width: u32,
+    height: u32,
+}
+
+/// Icons are serialized as their RGBA pixels, those of icons created from RGBA data are kept
+/// as is, other icons are read back with [`Icon::to_rgba`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for Icon {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let icon = match self.inner.source_rgba() {
+            Some(icon) => SerializedIcon {
+                rgba: STANDARD.encode(&icon.rgba),
+                width: icon.width,
+                height: icon.height,
+            },
+            None => {
+                let (rgba, width, height) = self.to_rgba().map_err(serde::ser::Error::custom)?;
+                SerializedIcon {
+                    rgba: STANDARD.encode(rgba),
+                    width,
+                    height,
+                }
+            }
+        };
+        icon.serialize(serializer)
+    }
+}
+
+/// Deserialized icons are created with [`Icon::from_rgba`], invalid pixel data fails with
+/// the message of the corresponding [`BadIcon`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Icon {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+        use serde::de::Error;
+
+        let icon = SerializedIcon::deserialize(deserializer)?;
+        let rgba = STANDARD.decode(icon.rgba).map_err(D::Error::custom)?;
+        Icon::from_rgba(rgba, icon.width, icon.height).map_err(D::Error::custom)
+    }
+}
+
 impl Icon {
     /// Creates an icon from 32bpp RGBA data.
     ///
     /// The length of `rgba` must be divisible by 4, and `width * height` must equal
     /// `rgba.len() / 4`. Otherwise, this will return a `BadIcon` error.
+    ///
+    /// The color channels are expected to use straight (non-premultiplied) alpha,
+    /// use [`Icon::from_rgba_premultiplied`] for premultiplied data.
+    ///
+    /// The pixels are rescaled to the tray icon size for the DPI of the taskbar monitor
+    /// (`SM_CXSMICON`) so the shell doesn't have to scale them, see
+    /// [`Icon::from_rgba_with_options`] to opt out. Tray icons rescale them again when that
+    /// DPI changes.
+    ///
+    /// Icons created from identical pixels share the same underlying `HICON`, which is
+    /// destroyed once the last of them is dropped.
     pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+        Self::from_rgba_with_options(rgba, width, height, IconOptions::default())
+    }
+
+    /// Creates an icon from 32bpp RGBA data using the given [`IconOptions`].
+    ///
+    /// See [`Icon::from_rgba`] for more info.
+    pub fn from_rgba_with_options(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        options: IconOptions,
+    ) -> Result<Self, BadIcon> {
+        Ok(Icon {
+            inner: PlatformIcon::from_rgba(rgba, width, height, options)?,
+        })
+    }
+
+    /// Creates a fully transparent `width` x `height` icon, e.g. as a placeholder while the
+    /// real icon is loading.
+    ///
+    /// Repeated calls with the same size share one underlying `HICON`.
+    pub fn transparent(width: u32, height: u32) -> Result<Self, BadIcon> {
+        Self::solid([0; 4], width, height)
+    }
+
+    /// Creates a `width` x `height` icon filled with `color` as RGBA, which can be handy
+    /// for debugging.
+    ///
+    /// Repeated calls with the same color and size share one underlying `HICON`.
+    pub fn solid(color: [u8; 4], width: u32, height: u32) -> Result<Self, BadIcon> {
+        let rgba = color.repeat(width as usize * height as usize);
+        Self::from_rgba_with_options(rgba, width, height, IconOptions { no_resize: true })
+    }
+
+    /// Creates a fully opaque icon from 24bpp RGB data.
+    ///
+    /// The length of `rgb` must be divisible by 3, and `width * height` must equal
+    /// `rgb.len() / 3`. Otherwise, this will return a `BadIcon` error.
+    ///
+    /// See [`Icon::from_rgba`] for more info.
+    pub fn from_rgb(rgb: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+        Ok(Icon {
+            inner: PlatformIcon::from_rgb(&rgb, width, height)?,
+        })
+    }
+
+    /// Creates an icon from 32bpp RGBA data whose color channels are already
+    /// multiplied by their alpha.
+    ///
+    /// See [`Icon::from_rgba`] for more info.
+    pub fn from_rgba_premultiplied(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, BadIcon> {
+        Ok(Icon {
+            inner: PlatformIcon::from_rgba_premultiplied(rgba, width, height)?,
+        })
+    }
+
+    /// Creates an icon from 32bpp RGBA data whose rows are `stride` bytes apart.
+    ///
+    /// `stride` must be at least `width * 4` and `rgba` must be at least `stride * height`
+    /// bytes long, otherwise this will return [`BadIcon::StrideTooSmall`] or
+    /// [`BadIcon::BufferTooSmall`]. Any padding at the end of each row is ignored.
+    pub fn from_rgba_with_stride(
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Result<Self, BadIcon> {
+        Ok(Icon {
+            inner: PlatformIcon::from_rgba_with_stride(rgba, width, height, stride)?,
+        })
+    }
+
+    /// Creates an icon from in-memory PNG data, e.g. from `include_bytes!`.
+    ///
+    /// The image doesn't have to be square or a particular size, it is rescaled like
+    /// [`Icon::from_rgba`]. Decoding failures return [`BadIcon::DecodeError`].
+    pub fn from_png_bytes(bytes: &[u8]) -> Result<Self, BadIcon> {
+        Ok(Icon {
+            inner: PlatformIcon::from_png_bytes(bytes)?,
+        })
+    }
+
+    /// Creates an icon from in-memory `.ico` data.
+    ///
+    /// The frame closest to `preferred_size` is used, or to the tray icon size for the DPI
+    /// of the taskbar monitor when `None`. Both bitmap and PNG-compressed frames are
+    /// supported. Truncated or malformed data returns [`BadIcon::MalformedIco`].
+    #[cfg(windows)]
+    pub fn from_ico_bytes(bytes: &[u8], preferred_size: Option<u32>) -> Result<Self, BadIcon> {
+        Ok(Icon {
+            inner: PlatformIcon::from_ico_bytes(bytes, preferred_size)?,
+        })
+    }
+
+    /// Creates a `size` x `size` icon by rasterizing an SVG document.
+    ///
+    /// Non-square documents are scaled to fit and centered. Parse failures return
+    /// [`BadIcon::DecodeError`].
+    #[cfg(feature = "svg")]
+    pub fn from_svg_str(svg: &str, size: u32) -> Result<Self, BadIcon> {
         Ok(Icon {
-            inner: PlatformIcon::from_rgba(rgba, width, height)?,
+            inner: PlatformIcon::from_svg_str(svg, size)?,
         })
     }
 
+    /// Creates a `size` x `size` icon by rasterizing the SVG file at `path`.
+    ///
+    /// See [`Icon::from_svg_str`] for more info.
+    #[cfg(feature = "svg")]
+    pub fn from_svg_path<P: AsRef<std::path::Path>>(path: P, size: u32) -> Result<Self, BadIcon> {
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(BadIcon::FileNotFound(path.to_path_buf()));
+        }
+        let svg = std::fs::read_to_string(path).map_err(BadIcon::OsError)?;
+        Self::from_svg_str(&svg, size)
+    }
+
     /// Create an icon from a file path.
     ///
-    /// Specify `size` to load a specific icon size from the file, or `None` to load the default
-    /// icon size from the file.
+    /// `.ico` files are loaded through `LoadImageW`, `.png` files are decoded and converted
+    /// to an icon. Any other extension returns [`BadIcon::UnsupportedFormat`] and a missing
+    /// file returns [`BadIcon::FileNotFound`].
+    ///
+    /// Specify `size` to load a specific icon size from the file, or `None` to use the system
+    /// small icon size (`SM_CXSMICON`), which is the size used by the notification area.
     ///
     /// In cases where the specified size does not exist in the file, Windows may perform scaling
     /// to get an icon of the desired size.
@@ -165,7 +652,10 @@ impl Icon {
     }
 
     /// This is basically the same as from_resource, but takes a resource name
-    /// rather than oridinal id.
+    /// rather than ordinal id.
+    ///
+    /// Returns [`BadIcon::ResourceNotFound`] if the current module has no icon resource
+    /// with that name.
     #[cfg(windows)]
     pub fn from_resource_name(
         resource_name: &str,
@@ -175,10 +665,519 @@ impl Icon {
         Ok(Icon { inner: win_icon })
     }
 
-    /// Create an icon from an HICON
+    /// Creates an icon from one of the icons built into the shell, using `SHGetStockIconInfo`.
+    ///
+    /// The icon is extracted at `size` pixels, or at the tray icon size for the DPI of the
+    /// taskbar monitor when `None`.
     #[cfg(windows)]
-    pub fn from_handle(handle: isize) -> Self {
-        let win_icon = PlatformIcon::from_handle(handle as _);
+    pub fn from_system(icon: StockIcon, size: Option<u32>) -> Result<Self, BadIcon> {
+        let win_icon = PlatformIcon::from_system(icon, size)?;
+        Ok(Icon { inner: win_icon })
+    }
+
+    /// Creates an icon from the small shell icon of a file, e.g. the icon of its file type,
+    /// using `SHGetFileInfoW`.
+    ///
+    /// The file doesn't have to exist, in that case the icon associated with its extension
+    /// is used. Unknown file types use the generic document icon.
+    #[cfg(windows)]
+    pub fn from_associated_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, BadIcon> {
+        let win_icon = PlatformIcon::from_associated_file(path.as_ref())?;
+        Ok(Icon { inner: win_icon })
+    }
+
+    /// Creates an icon from the small shell icon associated with a file extension,
+    /// e.g. `".pdf"` or `"pdf"`.
+    ///
+    /// Unknown extensions use the generic document icon.
+    #[cfg(windows)]
+    pub fn from_extension(extension: &str) -> Result<Self, BadIcon> {
+        let win_icon = PlatformIcon::from_extension(extension)?;
+        Ok(Icon { inner: win_icon })
+    }
+
+    /// Create an icon from an HICON.
+    ///
+    /// If `owned` is `true`, the icon takes ownership of the handle and calls `DestroyIcon`
+    /// when the last clone of the returned [`Icon`] is dropped. Otherwise the caller stays
+    /// responsible for destroying the handle.
+    ///
+    /// The icon is shown as is, tray icons can't rescale it when the DPI changes.
+    ///
+    /// # Safety
+    ///
+    /// `handle` must be a valid HICON, and if `owned` is `false` it must outlive the returned
+    /// [`Icon`] and all of its clones, including any copy held by a [`TrayIcon`](crate::TrayIcon).
+    #[cfg(windows)]
+    pub unsafe fn from_handle(handle: isize, owned: bool) -> Self {
+        let win_icon = PlatformIcon::from_raw_handle(handle as _, owned);
         Icon { inner: win_icon }
     }
+
+    /// Create an icon from an HBITMAP and an optional monochrome mask bitmap.
+    ///
+    /// When `mask` is `None`, an opaque mask is generated, so bitmaps without an alpha
+    /// channel are shown fully opaque while 32-bit bitmaps keep their alpha channel.
+    ///
+    /// The icon holds its own copies of the bitmaps. If `owned` is `true`, `hbitmap` and `mask`
+    /// are deleted once the icon has been created, otherwise the caller stays responsible for them.
+    /// On error they are never deleted. Invalid handles return [`BadIcon::InvalidHandle`].
+    ///
+    /// # Safety
+    ///
+    /// If `owned` is `true`, the bitmaps must not be used after this function succeeds.
+    #[cfg(windows)]
+    pub unsafe fn from_hbitmap(
+        hbitmap: isize,
+        mask: Option<isize>,
+        owned: bool,
+    ) -> Result<Self, BadIcon> {
+        let win_icon = PlatformIcon::from_hbitmap(hbitmap as _, mask.map(|m| m as _), owned)?;
+        Ok(Icon { inner: win_icon })
+    }
+
+    /// Reads back the pixels of this icon as 32bpp straight-alpha RGBA data, along with
+    /// its width and height.
+    ///
+    /// The alpha channel is preserved for 32-bit icons, icons without one get their
+    /// transparency from the icon mask.
+    #[cfg(windows)]
+    pub fn to_rgba(&self) -> Result<(Vec<u8>, u32, u32), BadIcon> {
+        self.inner.to_rgba()
+    }
+
+    /// Returns a grayscale copy of this icon, e.g. to show a "disabled" state.
+    ///
+    /// The alpha channel is preserved and multiplied by `opacity` (`0.0..=1.0`) when provided,
+    /// so the icon can also be dimmed.
+    #[cfg(windows)]
+    pub fn to_grayscale(&self, opacity: Option<f32>) -> Result<Self, BadIcon> {
+        let (mut rgba, width, height) = self.to_rgba()?;
+        compose::grayscale(&mut rgba, opacity.unwrap_or(1.));
+        Self::from_rgba_with_options(rgba, width, height, IconOptions { no_resize: true })
+    }
+
+    /// Returns a copy of this icon tinted with `color` as RGBA, e.g. to recolor a white glyph
+    /// for different states.
+    ///
+    /// `mode` defaults to [`TintMode::Multiply`]. In both modes the alpha channel is kept and
+    /// multiplied by the alpha of `color`.
+    #[cfg(windows)]
+    pub fn tint(&self, color: [u8; 4], mode: Option<TintMode>) -> Result<Self, BadIcon> {
+        let (mut rgba, width, height) = self.to_rgba()?;
+        compose::tint(&mut rgba, color, mode.unwrap_or_default());
+        Self::from_rgba_with_options(rgba, width, height, IconOptions { no_resize: true })
+    }
+
+    /// Returns a copy of this icon rotated clockwise by `degrees` around its center, using
+    /// bilinear sampling.
+    ///
+    /// The icon keeps its size, areas not covered by the rotated image are transparent.
+    #[cfg(windows)]
+    pub fn rotated(&self, degrees: f32) -> Result<Self, BadIcon> {
+        let (rgba, width, height) = self.to_rgba()?;
+        let rgba = compose::rotate(&rgba, width, height, degrees);
+        Self::from_rgba_with_options(rgba, width, height, IconOptions { no_resize: true })
+    }
+
+    /// Returns a mirrored copy of this icon, left to right if `horizontal` is `true`,
+    /// otherwise top to bottom.
+    #[cfg(windows)]
+    pub fn flipped(&self, horizontal: bool) -> Result<Self, BadIcon> {
+        let (rgba, width, height) = self.to_rgba()?;
+        let rgba = compose::flip(&rgba, width, height, horizontal);
+        Self::from_rgba_with_options(rgba, width, height, IconOptions { no_resize: true })
+    }
+
+    /// Returns a copy of this icon with `overlay` composited in `corner`, e.g. a small
+    /// "paused" or "error" glyph.
+    ///
+    /// The overlay is scaled, keeping its aspect ratio, to fit within `scale` (`0.0..=1.0`)
+    /// times the size of this icon, so larger overlays are scaled down.
+    #[cfg(windows)]
+    pub fn with_overlay(
+        &self,
+        overlay: &Icon,
+        corner: OverlayCorner,
+        scale: f32,
+    ) -> Result<Self, BadIcon> {
+        let (mut rgba, width, height) = self.to_rgba()?;
+        let (overlay, overlay_width, overlay_height) = overlay.to_rgba()?;
+        compose::draw_overlay(
+            &mut rgba,
+            width,
+            height,
+            &overlay,
+            overlay_width,
+            overlay_height,
+            corner,
+            scale,
+        );
+        Self::from_rgba_with_options(rgba, width, height, IconOptions { no_resize: true })
+    }
+
+    /// Returns this icon created again for the current tray icon size, or `None` for icons
+    /// that aren't scaled to it, like those created from a handle or with
+    /// [`IconOptions::no_resize`]. Failing to create it again keeps the icon as is.
+    #[cfg(windows)]
+    pub(crate) fn rescaled(&self) -> Option<Self> {
+        match self.inner.rescaled()? {
+            Ok(inner) => Some(Self { inner }),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns a copy of this icon resized to `size` x `size` pixels.
+    #[cfg(windows)]
+    pub(crate) fn resized(&self, size: u32) -> Result<Self, BadIcon> {
+        if (self.width(), self.height()) == (size, size) {
+            return Ok(self.clone());
+        }
+
+        let (mut rgba, width, height) = self.to_rgba()?;
+        compose::premultiply(&mut rgba);
+        let mut rgba = compose::resize(&rgba, width, height, size, size);
+        compose::demultiply(&mut rgba);
+        Self::from_rgba_with_options(rgba, size, size, IconOptions { no_resize: true })
+    }
+
+    /// Returns a copy of this icon with a numeric badge drawn in its bottom-right corner.
+    #[cfg(windows)]
+    pub(crate) fn with_badge(&self, count: u32) -> Result<Self, BadIcon> {
+        let (mut rgba, width, height) = self.to_rgba()?;
+        compose::draw_badge(&mut rgba, width, height, count);
+        Self::from_rgba_with_options(rgba, width, height, IconOptions { no_resize: true })
+    }
+
+    /// Returns the width of this icon in pixels.
+    ///
+    /// This is `0` for icons wrapping a handle whose size couldn't be queried.
+    pub fn width(&self) -> u32 {
+        self.inner.width()
+    }
+
+    /// Returns the height of this icon in pixels.
+    ///
+    /// This is `0` for icons wrapping a handle whose size couldn't be queried.
+    pub fn height(&self) -> u32 {
+        self.inner.height()
+    }
+
+    /// Returns the size of this icon in pixels.
+    pub fn size(&self) -> crate::dpi::PhysicalSize<u32> {
+        crate::dpi::PhysicalSize::new(self.width(), self.height())
+    }
+
+    /// Returns the underlying HICON.
+    ///
+    /// The handle is only valid while this [`Icon`] or one of its clones is alive,
+    /// it must not be destroyed by the caller.
+    #[cfg(windows)]
+    pub fn as_raw_handle(&self) -> isize {
+        self.inner.as_raw_handle() as isize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_handle_is_non_null() {
+        let icon = Icon::from_rgba(vec![255; 16 * 16 * PIXEL_SIZE], 16, 16).unwrap();
+        assert_ne!(icon.as_raw_handle(), 0);
+        assert_eq!(icon.clone().as_raw_handle(), icon.as_raw_handle());
+    }
+
+    #[test]
+    fn reuses_handles_for_identical_pixels() {
+        let rgba: Vec<u8> = (0..16 * 16u32)
+            .flat_map(|i| [i as u8, 0, 0, u8::MAX])
+            .collect();
+        let icon = Icon::from_rgba(rgba.clone(), 16, 16).unwrap();
+        let same = Icon::from_rgba(rgba.clone(), 16, 16).unwrap();
+        assert_eq!(icon.as_raw_handle(), same.as_raw_handle());
+
+        let mut other = rgba.clone();
+        other[1] = 1;
+        let other = Icon::from_rgba(other, 16, 16).unwrap();
+        assert_ne!(icon.as_raw_handle(), other.as_raw_handle());
+
+        // the handle stays valid as long as one icon sharing it is alive
+        drop(icon);
+        assert_eq!(same.to_rgba().unwrap().0.len(), rgba.len());
+    }
+
+    #[test]
+    fn loads_stock_icons() {
+        let icon = Icon::from_system(StockIcon::Warning, Some(32)).unwrap();
+        assert_eq!(icon.size(), crate::dpi::PhysicalSize::new(32, 32));
+
+        let size = crate::platform_impl::tray_icon_size();
+        let icon = Icon::from_system(StockIcon::Info, None).unwrap();
+        assert_eq!(icon.size(), crate::dpi::PhysicalSize::new(size, size));
+    }
+
+    #[test]
+    fn loads_file_type_icons() {
+        assert!(Icon::from_extension(".txt").is_ok());
+        assert!(Icon::from_extension("txt").is_ok());
+        assert!(Icon::from_extension(".this-extension-does-not-exist").is_ok());
+        assert!(Icon::from_associated_file("C:/does/not/exist.pdf").is_ok());
+        assert!(Icon::from_associated_file(file!()).is_ok());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn converts_from_image() {
+        let image = image::RgbaImage::from_pixel(16, 16, image::Rgba([10, 20, 30, 255]));
+        let icon = Icon::try_from(image::DynamicImage::ImageRgba8(image)).unwrap();
+        assert_eq!(icon.to_rgba().unwrap().0[..4], [10, 20, 30, 255]);
+
+        let err = image::load_from_memory(b"not an image").unwrap_err();
+        assert!(matches!(BadIcon::from(err), BadIcon::DecodeError(_)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes() {
+        let icon = Icon::from_rgba(vec![255, 0, 0, 255], 1, 1).unwrap();
+        let value = serde_jsonc2::to_value(&icon).unwrap();
+        assert_eq!(
+            value,
+            serde_jsonc2::jsonc!({
+                "rgba": "/wAA/w==",
+                "width": 1,
+                "height": 1,
+            })
+        );
+
+        let icon: Icon = serde_jsonc2::from_value(value.clone()).unwrap();
+        assert_eq!(serde_jsonc2::to_value(&icon).unwrap(), value);
+
+        let corrupt = serde_jsonc2::jsonc!({
+            "rgba": "/wAA/w==",
+            "width": 2,
+            "height": 2,
+        });
+        let err = serde_jsonc2::from_value::<Icon>(corrupt).unwrap_err();
+        assert!(err.to_string().contains("2x2"));
+    }
+
+    #[test]
+    fn creates_opaque_icons_from_rgb() {
+        let rgb: Vec<u8> = (0..16 * 16 * 3).map(|i| i as u8).collect();
+        let icon = Icon::from_rgb(rgb, 16, 16).unwrap();
+        assert!(icon
+            .to_rgba()
+            .unwrap()
+            .0
+            .chunks_exact(PIXEL_SIZE)
+            .all(|p| p[3] == u8::MAX));
+
+        assert!(matches!(
+            Icon::from_rgb(vec![0; 4], 1, 1),
+            Err(BadIcon::ByteCountNotDivisibleBy3 { byte_count: 4 })
+        ));
+        assert!(matches!(
+            Icon::from_rgb(vec![0; 6], 1, 1),
+            Err(BadIcon::DimensionsVsPixelCount {
+                width_x_height: 1,
+                pixel_count: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn compares_pixels() {
+        use std::collections::HashSet;
+
+        let rgba: Vec<u8> = (0..16 * 16u32)
+            .flat_map(|i| [0, i as u8, 0, u8::MAX])
+            .collect();
+        let icon = Icon::from_rgba(rgba.clone(), 16, 16).unwrap();
+        let same = Icon::from_rgba(rgba.clone(), 16, 16).unwrap();
+        let other = Icon::from_rgba(vec![255; 16 * 16 * PIXEL_SIZE], 16, 16).unwrap();
+        assert_eq!(icon, same);
+        assert_ne!(icon, other);
+
+        // the cached content hash only ever gets filled in, it never changes
+        #[allow(clippy::mutable_key_type)]
+        let set: HashSet<_> = [icon, same, other].into_iter().collect();
+        assert_eq!(set.len(), 2);
+
+        // separately extracted icons have distinct handles but the same pixels
+        let info = Icon::from_system(StockIcon::Info, Some(16)).unwrap();
+        let info_again = Icon::from_system(StockIcon::Info, Some(16)).unwrap();
+        assert_ne!(info.as_raw_handle(), info_again.as_raw_handle());
+        assert_eq!(info, info_again);
+    }
+
+    #[test]
+    fn creates_placeholder_icons() {
+        let icon = Icon::transparent(16, 16).unwrap();
+        assert_eq!(icon.size(), crate::dpi::PhysicalSize::new(16, 16));
+        assert!(icon.to_rgba().unwrap().0.iter().all(|&c| c == 0));
+        assert_eq!(
+            icon.as_raw_handle(),
+            Icon::transparent(16, 16).unwrap().as_raw_handle()
+        );
+
+        let icon = Icon::solid([255, 0, 0, 255], 24, 24).unwrap();
+        assert_eq!(icon.to_rgba().unwrap().0, [255, 0, 0, 255].repeat(24 * 24));
+    }
+
+    #[test]
+    fn stores_dimensions() {
+        let options = IconOptions { no_resize: true };
+        let icon =
+            Icon::from_rgba_with_options(vec![255; 24 * 16 * PIXEL_SIZE], 24, 16, options).unwrap();
+        assert_eq!(icon.width(), 24);
+        assert_eq!(icon.height(), 16);
+        assert_eq!(icon.size(), crate::dpi::PhysicalSize::new(24, 16));
+
+        let wrapped = unsafe { Icon::from_handle(icon.as_raw_handle(), false) };
+        assert_eq!(wrapped.size(), icon.size());
+    }
+
+    #[test]
+    fn resizes_icons() {
+        let options = IconOptions { no_resize: true };
+        let icon =
+            Icon::from_rgba_with_options(vec![255; 16 * 16 * PIXEL_SIZE], 16, 16, options).unwrap();
+
+        let resized = icon.resized(32).unwrap();
+        assert_eq!(resized.size(), crate::dpi::PhysicalSize::new(32, 32));
+        assert_eq!(
+            resized.to_rgba().unwrap().0,
+            vec![255; 32 * 32 * PIXEL_SIZE]
+        );
+        assert_eq!(
+            icon.resized(16).unwrap().as_raw_handle(),
+            icon.as_raw_handle()
+        );
+    }
+
+    #[test]
+    fn reports_mismatched_buffers() {
+        let err = Icon::from_rgba(vec![0; 7], 1, 1).unwrap_err();
+        assert!(matches!(
+            err,
+            BadIcon::ByteCountNotDivisibleBy4 { byte_count: 7 }
+        ));
+        assert!(err.to_string().contains("(7)"));
+        assert!(err.source().is_none());
+
+        let err = Icon::from_rgba(vec![0; 16 * 15 * PIXEL_SIZE], 16, 16).unwrap_err();
+        assert!(matches!(
+            err,
+            BadIcon::DimensionsVsPixelCount {
+                width: 16,
+                height: 16,
+                width_x_height: 256,
+                pixel_count: 240,
+            }
+        ));
+        let message = err.to_string();
+        assert!(message.contains("16x16"));
+        assert!(message.contains("240"));
+        assert!(message.contains("256"));
+
+        let err = BadIcon::OsError(io::Error::from_raw_os_error(5));
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn rescales_to_tray_icon_size() {
+        let size = crate::platform_impl::tray_icon_size();
+        let icon = Icon::from_rgba(vec![255; 256 * 256 * PIXEL_SIZE], 256, 256).unwrap();
+        assert_eq!(icon.size(), crate::dpi::PhysicalSize::new(size, size));
+
+        let options = IconOptions { no_resize: true };
+        let icon =
+            Icon::from_rgba_with_options(vec![255; 256 * 256 * PIXEL_SIZE], 256, 256, options)
+                .unwrap();
+        assert_eq!(icon.size(), crate::dpi::PhysicalSize::new(256, 256));
+    }
+
+    #[test]
+    fn rgba_round_trip() {
+        let opaque: Vec<u8> = (0..16 * 16u32)
+            .flat_map(|i| [i as u8, (i * 3) as u8, (i * 7) as u8, u8::MAX])
+            .collect();
+        // kept at 16x16, tray icons are rescaled at DPIs other than 100%
+        let options = IconOptions { no_resize: true };
+        let icon = Icon::from_rgba_with_options(opaque.clone(), 16, 16, options).unwrap();
+        assert_eq!(icon.to_rgba().unwrap(), (opaque, 16, 16));
+
+        let translucent: Vec<u8> = (0..16 * 16u32).flat_map(|i| [0, 0, 0, i as u8]).collect();
+        let icon = Icon::from_rgba_with_options(translucent.clone(), 16, 16, options).unwrap();
+        assert_eq!(icon.to_rgba().unwrap().0, translucent);
+    }
+
+    #[test]
+    fn premultiplies_straight_alpha() {
+        // 50% alpha, the stored pixels are premultiplied so color precision is lost
+        let size = crate::platform_impl::tray_icon_size();
+        let pixels = |pixel: [u8; 4]| pixel.repeat((size * size) as usize);
+
+        let icon = Icon::from_rgba(pixels([200, 100, 50, 128]), size, size).unwrap();
+        assert_eq!(icon.to_rgba().unwrap().0, pixels([199, 100, 50, 128]));
+
+        let icon = Icon::from_rgba_premultiplied(pixels([100, 50, 25, 128]), size, size).unwrap();
+        assert_eq!(icon.to_rgba().unwrap().0, pixels([199, 100, 50, 128]));
+
+        let icon = Icon::from_rgba(pixels([255, 255, 255, 128]), size, size).unwrap();
+        assert_eq!(icon.to_rgba().unwrap().0, pixels([255, 255, 255, 128]));
+    }
+
+    #[test]
+    fn decodes_png_bytes() {
+        let bytes = include_bytes!("../examples/icon.png");
+        let icon = RgbaIcon::from_png(&bytes[..]).unwrap();
+        assert_eq!(
+            icon.rgba.len(),
+            (icon.width * icon.height) as usize * PIXEL_SIZE
+        );
+        assert!(Icon::from_png_bytes(bytes).is_ok());
+
+        assert!(matches!(
+            Icon::from_png_bytes(&bytes[..bytes.len() / 2]),
+            Err(BadIcon::DecodeError(_))
+        ));
+    }
+
+    #[test]
+    fn packs_padded_rows() {
+        // 2x2 image with 4 bytes of padding per row
+        let rgba = [
+            1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, //
+            9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0,
+        ];
+        let icon = RgbaIcon::from_rgba_with_stride(&rgba, 2, 2, 12).unwrap();
+        assert_eq!(icon.rgba, (1..=16).collect::<Vec<u8>>());
+
+        assert!(matches!(
+            RgbaIcon::from_rgba_with_stride(&rgba, 2, 2, 7),
+            Err(BadIcon::StrideTooSmall {
+                stride: 7,
+                min_stride: 8
+            })
+        ));
+        assert!(matches!(
+            RgbaIcon::from_rgba_with_stride(&rgba[..20], 2, 2, 12),
+            Err(BadIcon::BufferTooSmall {
+                byte_count: 20,
+                expected: 24
+            })
+        ));
+        assert!(matches!(
+            RgbaIcon::from_rgba_with_stride(&rgba, 2, u32::MAX, usize::MAX / 2),
+            Err(BadIcon::BufferTooSmall {
+                byte_count: 24,
+                expected: usize::MAX
+            })
+        ));
+    }
 }
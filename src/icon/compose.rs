@@ -0,0 +1,581 @@
+//! Pixel compositing helpers operating on 32bpp straight-alpha RGBA buffers.
+
+use super::{OverlayCorner, TintMode, PIXEL_SIZE};
+
+const BADGE_COLOR: [u8; 4] = [220, 38, 38, 255];
+const BADGE_TEXT_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+/// 3x5 pixel glyphs for the badge label, each row uses the low 3 bits, MSB on the left.
+const DIGITS: [[u8; 5]; 10] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+];
+const PLUS: [u8; 5] = [0b000, 0b010, 0b111, 0b010, 0b000];
+
+/// Blends `src` over the pixel `dst`, scaling the source alpha by `coverage`.
+pub(crate) fn blend(dst: &mut [u8], src: [u8; 4], coverage: f32) {
+    let src_a = src[3] as f32 / 255. * coverage.clamp(0., 1.);
+    let dst_a = dst[3] as f32 / 255.;
+    let out_a = src_a + dst_a * (1. - src_a);
+    if out_a <= 0. {
+        dst.copy_from_slice(&[0; 4]);
+        return;
+    }
+
+    for (d, s) in dst[..3].iter_mut().zip(&src[..3]) {
+        let value = (*s as f32 * src_a + *d as f32 * dst_a * (1. - src_a)) / out_a;
+        *d = value.round() as u8;
+    }
+    dst[3] = (out_a * 255.).round() as u8;
+}
+
+/// Converts straight-alpha `rgba` pixels to premultiplied alpha in place.
+pub(crate) fn premultiply(rgba: &mut [u8]) {
+    for pixel in rgba.chunks_exact_mut(PIXEL_SIZE) {
+        let alpha = pixel[3] as u32;
+        for c in &mut pixel[..3] {
+            *c = ((*c as u32 * alpha + 127) / 255) as u8;
+        }
+    }
+}
+
+/// Converts premultiplied-alpha `rgba` pixels back to straight alpha in place.
+pub(crate) fn demultiply(rgba: &mut [u8]) {
+    for pixel in rgba.chunks_exact_mut(PIXEL_SIZE) {
+        let alpha = pixel[3] as u32;
+        if alpha == 0 {
+            continue;
+        }
+        for c in &mut pixel[..3] {
+            *c = ((*c as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+        }
+    }
+}
+
+/// Returns the source samples and their weights contributing to each of the `dst_len`
+/// samples when resampling `src_len` samples.
+///
+/// Downscaling averages the area covered by each destination sample, upscaling
+/// interpolates linearly between the two nearest source samples.
+fn resample_weights(src_len: u32, dst_len: u32) -> Vec<Vec<(usize, f32)>> {
+    let scale = src_len as f32 / dst_len as f32;
+    let last = src_len as usize - 1;
+    (0..dst_len)
+        .map(|i| {
+            if scale > 1. {
+                let (start, end) = (i as f32 * scale, (i + 1) as f32 * scale);
+                (start.floor() as usize..(end.ceil() as usize).min(src_len as usize))
+                    .map(|s| {
+                        let coverage = end.min(s as f32 + 1.) - start.max(s as f32);
+                        (s, coverage / scale)
+                    })
+                    .collect()
+            } else {
+                let center = ((i as f32 + 0.5) * scale - 0.5).max(0.);
+                let left = (center.floor() as usize).min(last);
+                let t = center - left as f32;
+                vec![(left, 1. - t), ((left + 1).min(last), t)]
+            }
+        })
+        .collect()
+}
+
+/// Resizes `rgba` from `width` x `height` to `new_width` x `new_height` pixels.
+///
+/// The pixels should use premultiplied alpha, otherwise the color of transparent
+/// pixels bleeds into their neighbours.
+pub(crate) fn resize(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    new_width: u32,
+    new_height: u32,
+) -> Vec<u8> {
+    let (width, new_width) = (width as usize, new_width as usize);
+
+    // resize horizontally first, then vertically
+    let mut rows = vec![0f32; new_width * height as usize * PIXEL_SIZE];
+    let x_weights = resample_weights(width as u32, new_width as u32);
+    for (y, row) in rows.chunks_exact_mut(new_width * PIXEL_SIZE).enumerate() {
+        for (pixel, weights) in row.chunks_exact_mut(PIXEL_SIZE).zip(&x_weights) {
+            for &(x, weight) in weights {
+                let i = (y * width + x) * PIXEL_SIZE;
+                for (c, s) in pixel.iter_mut().zip(&rgba[i..i + PIXEL_SIZE]) {
+                    *c += *s as f32 * weight;
+                }
+            }
+        }
+    }
+
+    let mut resized = vec![0u8; new_width * new_height as usize * PIXEL_SIZE];
+    let y_weights = resample_weights(height, new_height);
+    for (row, weights) in resized
+        .chunks_exact_mut(new_width * PIXEL_SIZE)
+        .zip(&y_weights)
+    {
+        for (x, pixel) in row.chunks_exact_mut(PIXEL_SIZE).enumerate() {
+            let mut value = [0f32; PIXEL_SIZE];
+            for &(y, weight) in weights {
+                let i = (y * new_width + x) * PIXEL_SIZE;
+                for (v, s) in value.iter_mut().zip(&rows[i..i + PIXEL_SIZE]) {
+                    *v += s * weight;
+                }
+            }
+            for (c, v) in pixel.iter_mut().zip(value) {
+                *c = v.round().clamp(0., 255.) as u8;
+            }
+        }
+    }
+    resized
+}
+
+/// Composites `overlay` over `rgba` in `corner`.
+///
+/// The overlay is scaled, keeping its aspect ratio, to fit within `scale` times the size
+/// of `rgba`.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn draw_overlay(
+    rgba: &mut [u8],
+    width: u32,
+    height: u32,
+    overlay: &[u8],
+    overlay_width: u32,
+    overlay_height: u32,
+    corner: OverlayCorner,
+    scale: f32,
+) {
+    if width == 0 || height == 0 || overlay_width == 0 || overlay_height == 0 || scale <= 0. {
+        return;
+    }
+
+    let scale = scale.min(1.);
+    let fit = (width as f32 * scale / overlay_width as f32)
+        .min(height as f32 * scale / overlay_height as f32);
+    let new_width = ((overlay_width as f32 * fit).round() as u32).clamp(1, width);
+    let new_height = ((overlay_height as f32 * fit).round() as u32).clamp(1, height);
+
+    let mut overlay = overlay.to_vec();
+    premultiply(&mut overlay);
+    let mut overlay = resize(
+        &overlay,
+        overlay_width,
+        overlay_height,
+        new_width,
+        new_height,
+    );
+    demultiply(&mut overlay);
+
+    let left = match corner {
+        OverlayCorner::TopLeft | OverlayCorner::BottomLeft => 0,
+        OverlayCorner::TopRight | OverlayCorner::BottomRight => width - new_width,
+    };
+    let top = match corner {
+        OverlayCorner::TopLeft | OverlayCorner::TopRight => 0,
+        OverlayCorner::BottomLeft | OverlayCorner::BottomRight => height - new_height,
+    };
+    for (y, row) in overlay
+        .chunks_exact(new_width as usize * PIXEL_SIZE)
+        .enumerate()
+    {
+        for (x, pixel) in row.chunks_exact(PIXEL_SIZE).enumerate() {
+            let i = ((top as usize + y) * width as usize + left as usize + x) * PIXEL_SIZE;
+            let src = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            blend(&mut rgba[i..i + PIXEL_SIZE], src, 1.);
+        }
+    }
+}
+
+/// Samples premultiplied `rgba` at `(x, y)` in pixel coordinates using bilinear
+/// interpolation, pixels outside of the image are transparent.
+fn sample(rgba: &[u8], width: u32, height: u32, x: f32, y: f32) -> [f32; PIXEL_SIZE] {
+    let (left, top) = (x.floor(), y.floor());
+    let (tx, ty) = (x - left, y - top);
+
+    let mut value = [0f32; PIXEL_SIZE];
+    for (sy, wy) in [(top, 1. - ty), (top + 1., ty)] {
+        for (sx, wx) in [(left, 1. - tx), (left + 1., tx)] {
+            let weight = wx * wy;
+            if weight == 0. || sx < 0. || sy < 0. || sx >= width as f32 || sy >= height as f32 {
+                continue;
+            }
+            let i = (sy as usize * width as usize + sx as usize) * PIXEL_SIZE;
+            for (v, c) in value.iter_mut().zip(&rgba[i..i + PIXEL_SIZE]) {
+                *v += *c as f32 * weight;
+            }
+        }
+    }
+    value
+}
+
+/// Returns `rgba` rotated clockwise by `degrees` around its center.
+///
+/// The image keeps its size, so corners may be cut off, and areas not covered by the
+/// rotated image are transparent.
+pub(crate) fn rotate(rgba: &[u8], width: u32, height: u32, degrees: f32) -> Vec<u8> {
+    // snap to exact values so multiples of 90 degrees don't blur
+    let snap = |v: f32| {
+        if (v - v.round()).abs() < 1e-5 {
+            v.round()
+        } else {
+            v
+        }
+    };
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (sin, cos) = (snap(sin), snap(cos));
+
+    let mut premultiplied = rgba.to_vec();
+    premultiply(&mut premultiplied);
+
+    let (center_x, center_y) = (width as f32 / 2., height as f32 / 2.);
+    let mut rotated = vec![0u8; rgba.len()];
+    for (i, pixel) in rotated.chunks_exact_mut(PIXEL_SIZE).enumerate() {
+        let dx = (i as u32 % width) as f32 + 0.5 - center_x;
+        let dy = (i as u32 / width) as f32 + 0.5 - center_y;
+        // rotate the destination pixel back to find where it comes from
+        let x = cos * dx + sin * dy + center_x - 0.5;
+        let y = cos * dy - sin * dx + center_y - 0.5;
+        let value = sample(&premultiplied, width, height, x, y);
+        for (c, v) in pixel.iter_mut().zip(value) {
+            *c = v.round().clamp(0., 255.) as u8;
+        }
+    }
+    demultiply(&mut rotated);
+    rotated
+}
+
+/// Returns `rgba` mirrored left to right if `horizontal`, otherwise top to bottom.
+pub(crate) fn flip(rgba: &[u8], width: u32, height: u32, horizontal: bool) -> Vec<u8> {
+    let row_len = width as usize * PIXEL_SIZE;
+    let rows = rgba.chunks_exact(row_len).take(height as usize);
+    if horizontal {
+        rows.flat_map(|row| row.chunks_exact(PIXEL_SIZE).rev().flatten())
+            .copied()
+            .collect()
+    } else {
+        rows.rev().flatten().copied().collect()
+    }
+}
+
+/// Tints `rgba` with `color` in place, see [`TintMode`].
+pub(crate) fn tint(rgba: &mut [u8], color: [u8; 4], mode: TintMode) {
+    let multiply = |a: u8, b: u8| ((a as u32 * b as u32 + 127) / 255) as u8;
+    for pixel in rgba.chunks_exact_mut(PIXEL_SIZE) {
+        for (c, t) in pixel[..3].iter_mut().zip(&color[..3]) {
+            *c = match mode {
+                TintMode::Multiply => multiply(*c, *t),
+                TintMode::Replace => *t,
+            };
+        }
+        pixel[3] = multiply(pixel[3], color[3]);
+    }
+}
+
+/// Converts `rgba` to grayscale using Rec. 709 luminance, multiplying alpha by `opacity`.
+pub(crate) fn grayscale(rgba: &mut [u8], opacity: f32) {
+    let opacity = opacity.clamp(0., 1.);
+    for pixel in rgba.chunks_exact_mut(PIXEL_SIZE) {
+        let luminance =
+            0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32;
+        let luminance = luminance.round() as u8;
+        pixel[..3].fill(luminance);
+        pixel[3] = (pixel[3] as f32 * opacity).round() as u8;
+    }
+}
+
+/// Returns the text shown in a badge for `count`.
+pub(crate) fn badge_label(count: u32) -> String {
+    if count > 99 {
+        "99+".to_string()
+    } else {
+        count.to_string()
+    }
+}
+
+/// Draws a red badge showing `count` in the bottom-right corner of `rgba`, unless the icon
+/// is too small to fit a digit.
+pub(crate) fn draw_badge(rgba: &mut [u8], width: u32, height: u32, count: u32) {
+    if height < GLYPH_HEIGHT + 2 || width < GLYPH_WIDTH + 2 {
+        return;
+    }
+
+    let glyphs: Vec<&[u8; 5]> = badge_label(count)
+        .chars()
+        .map(|c| match c.to_digit(10) {
+            Some(digit) => &DIGITS[digit as usize],
+            None => &PLUS,
+        })
+        .collect();
+
+    let badge_height = (height * 5 / 8).max(GLYPH_HEIGHT + 2).min(height);
+    let scale = ((badge_height - 2) / GLYPH_HEIGHT).max(1);
+    let text_width = (glyphs.len() as u32 * (GLYPH_WIDTH + 1) - 1) * scale;
+    let text_height = GLYPH_HEIGHT * scale;
+    let badge_width = (text_width + badge_height / 2).max(badge_height).min(width);
+
+    let left = (width - badge_width) as f32;
+    let top = (height - badge_height) as f32;
+
+    // a pill shape: the set of points within `radius` of the segment between both centers
+    let radius = badge_height as f32 / 2.;
+    let center_y = top + radius;
+    let (start_x, end_x) = (left + radius, left + badge_width as f32 - radius);
+    for y in (height - badge_height)..height {
+        for x in (width - badge_width)..width {
+            let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+            let dx = px - px.clamp(start_x, end_x.max(start_x));
+            let dy = py - center_y;
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            let i = (y * width + x) as usize * PIXEL_SIZE;
+            blend(
+                &mut rgba[i..i + PIXEL_SIZE],
+                BADGE_COLOR,
+                radius - distance + 0.5,
+            );
+        }
+    }
+
+    let text_left = (width - badge_width) + badge_width.saturating_sub(text_width) / 2;
+    let text_top = (height - badge_height) + badge_height.saturating_sub(text_height) / 2;
+    for (n, glyph) in glyphs.iter().enumerate() {
+        let glyph_left = text_left + n as u32 * (GLYPH_WIDTH + 1) * scale;
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (0b100 >> col) == 0 {
+                    continue;
+                }
+                for sy in 0..scale {
+                    for sx in 0..scale {
+                        let x = glyph_left + col * scale + sx;
+                        let y = text_top + row as u32 * scale + sy;
+                        if x < width && y < height {
+                            let i = (y * width + x) as usize * PIXEL_SIZE;
+                            blend(&mut rgba[i..i + PIXEL_SIZE], BADGE_TEXT_COLOR, 1.);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn badge_labels() {
+        assert_eq!(badge_label(0), "0");
+        assert_eq!(badge_label(7), "7");
+        assert_eq!(badge_label(99), "99");
+        assert_eq!(badge_label(100), "99+");
+    }
+
+    #[test]
+    fn draws_badge_in_bottom_right_corner() {
+        let (width, height) = (16, 16);
+        let mut rgba = vec![0; (width * height) as usize * PIXEL_SIZE];
+        draw_badge(&mut rgba, width, height, 5);
+
+        let pixel = |x: u32, y: u32| {
+            let i = (y * width + x) as usize * PIXEL_SIZE;
+            &rgba[i..i + PIXEL_SIZE]
+        };
+        assert_eq!(pixel(0, 0), [0, 0, 0, 0]);
+        // the edge of the pill, left of the label
+        assert_eq!(pixel(8, 11), BADGE_COLOR);
+        assert!(rgba.chunks_exact(PIXEL_SIZE).any(|p| p == BADGE_TEXT_COLOR));
+    }
+
+    #[test]
+    fn skips_badge_on_tiny_icons() {
+        let mut rgba = vec![0; PIXEL_SIZE];
+        draw_badge(&mut rgba, 1, 1, 5);
+        assert_eq!(rgba, [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn premultiplies_alpha() {
+        let mut rgba = [200, 100, 50, 128, 255, 255, 255, 255, 90, 80, 70, 0];
+        premultiply(&mut rgba);
+        assert_eq!(rgba, [100, 50, 25, 128, 255, 255, 255, 255, 0, 0, 0, 0]);
+
+        demultiply(&mut rgba);
+        assert_eq!(rgba, [199, 100, 50, 128, 255, 255, 255, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn resizes_pixels() {
+        // a 4x4 checkerboard of 2x2 squares averages down to one pixel per square
+        let mut rgba = Vec::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                let value = if (x / 2 + y / 2) % 2 == 0 { 255 } else { 0 };
+                rgba.extend_from_slice(&[value, value, value, 255]);
+            }
+        }
+        let resized = resize(&rgba, 4, 4, 2, 2);
+        assert_eq!(
+            resized,
+            [255, 255, 255, 255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255, 255]
+        );
+
+        let resized = resize(&rgba, 4, 4, 1, 1);
+        assert_eq!(resized, [128, 128, 128, 255]);
+
+        // uniform images stay uniform when upscaling
+        let resized = resize(&[10, 20, 30, 40].repeat(4), 2, 2, 5, 3);
+        assert_eq!(resized, [10, 20, 30, 40].repeat(15));
+    }
+
+    #[test]
+    fn draws_overlay_in_corner() {
+        const BLUE: [u8; 4] = [0, 0, 255, 255];
+        let (width, height) = (4, 4);
+        let base = BLUE.repeat((width * height) as usize);
+        let pixel = |rgba: &[u8], x: u32, y: u32| {
+            let i = (y * width + x) as usize * PIXEL_SIZE;
+            [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+        };
+
+        // a larger overlay is scaled down to half the base size
+        let mut rgba = base.clone();
+        let overlay = [255, 0, 0, 255].repeat(64);
+        draw_overlay(
+            &mut rgba,
+            width,
+            height,
+            &overlay,
+            8,
+            8,
+            OverlayCorner::BottomRight,
+            0.5,
+        );
+        for y in 0..height {
+            for x in 0..width {
+                let expected = if x >= 2 && y >= 2 {
+                    [255, 0, 0, 255]
+                } else {
+                    BLUE
+                };
+                assert_eq!(pixel(&rgba, x, y), expected, "pixel ({x}, {y})");
+            }
+        }
+
+        // translucent overlays are blended with the base
+        let mut rgba = base.clone();
+        let overlay = [255, 0, 0, 128].repeat(4);
+        draw_overlay(
+            &mut rgba,
+            width,
+            height,
+            &overlay,
+            2,
+            2,
+            OverlayCorner::TopLeft,
+            0.5,
+        );
+        assert_eq!(pixel(&rgba, 0, 0), [128, 0, 127, 255]);
+        assert_eq!(pixel(&rgba, 1, 1), [128, 0, 127, 255]);
+        assert_eq!(pixel(&rgba, 2, 0), BLUE);
+    }
+
+    #[test]
+    fn rotates_by_right_angles() {
+        // a 3x3 image where each pixel is unique
+        let rgba: Vec<u8> = (0..9u8).flat_map(|i| [i * 20, 0, 0, 255]).collect();
+        let index =
+            |rgba: &[u8]| -> Vec<u8> { rgba.chunks_exact(PIXEL_SIZE).map(|p| p[0] / 20).collect() };
+
+        assert_eq!(index(&rotate(&rgba, 3, 3, 0.)), [0, 1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(
+            index(&rotate(&rgba, 3, 3, 90.)),
+            [6, 3, 0, 7, 4, 1, 8, 5, 2]
+        );
+        assert_eq!(
+            index(&rotate(&rgba, 3, 3, 180.)),
+            [8, 7, 6, 5, 4, 3, 2, 1, 0]
+        );
+        assert_eq!(
+            index(&rotate(&rgba, 3, 3, 270.)),
+            [2, 5, 8, 1, 4, 7, 0, 3, 6]
+        );
+        assert_eq!(
+            index(&rotate(&rgba, 3, 3, -90.)),
+            [2, 5, 8, 1, 4, 7, 0, 3, 6]
+        );
+        assert!(rotate(&rgba, 3, 3, 90.)
+            .chunks_exact(PIXEL_SIZE)
+            .all(|p| p[3] == 255));
+    }
+
+    #[test]
+    fn rotation_exposes_transparent_corners() {
+        let rgba = [255, 255, 255, 255].repeat(16 * 16);
+        let rotated = rotate(&rgba, 16, 16, 45.);
+        assert_eq!(&rotated[..PIXEL_SIZE], [0, 0, 0, 0]);
+        let center = (8 * 16 + 8) * PIXEL_SIZE;
+        assert_eq!(&rotated[center..center + PIXEL_SIZE], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn flips_pixels() {
+        let rgba: Vec<u8> = (0..6u8).flat_map(|i| [i, i, i, 255]).collect();
+        let index =
+            |rgba: Vec<u8>| -> Vec<u8> { rgba.chunks_exact(PIXEL_SIZE).map(|p| p[0]).collect() };
+
+        assert_eq!(index(flip(&rgba, 3, 2, true)), [2, 1, 0, 5, 4, 3]);
+        assert_eq!(index(flip(&rgba, 3, 2, false)), [3, 4, 5, 0, 1, 2]);
+    }
+
+    #[test]
+    fn tints_pixels() {
+        let rgba = [255, 255, 255, 255, 128, 64, 0, 128, 10, 20, 30, 0];
+
+        let mut multiplied = rgba;
+        tint(&mut multiplied, [0, 255, 128, 255], TintMode::Multiply);
+        assert_eq!(multiplied, [0, 255, 128, 255, 0, 64, 0, 128, 0, 20, 15, 0]);
+
+        let mut replaced = rgba;
+        tint(&mut replaced, [0, 255, 128, 128], TintMode::Replace);
+        assert_eq!(
+            replaced,
+            [0, 255, 128, 128, 0, 255, 128, 64, 0, 255, 128, 0]
+        );
+    }
+
+    #[test]
+    fn converts_to_grayscale() {
+        let mut rgba = [255, 0, 0, 255, 0, 255, 0, 128, 255, 255, 255, 0];
+        grayscale(&mut rgba, 1.);
+        assert_eq!(
+            rgba,
+            [54, 54, 54, 255, 182, 182, 182, 128, 255, 255, 255, 0]
+        );
+
+        let mut rgba = [10, 10, 10, 200];
+        grayscale(&mut rgba, 0.5);
+        assert_eq!(rgba, [10, 10, 10, 100]);
+    }
+
+    #[test]
+    fn blends_over_transparent_and_opaque() {
+        let mut pixel = [0, 0, 0, 0];
+        blend(&mut pixel, [10, 20, 30, 255], 1.);
+        assert_eq!(pixel, [10, 20, 30, 255]);
+
+        let mut pixel = [0, 0, 255, 255];
+        blend(&mut pixel, [255, 0, 0, 255], 0.5);
+        assert_eq!(pixel, [128, 0, 128, 255]);
+    }
+}
@@ -0,0 +1,144 @@
+//! Minimal parser for the `.ico` container format.
+//!
+//! See <https://learn.microsoft.com/en-us/previous-versions/ms997538(v=msdn.10)>
+
+use super::BadIcon;
+
+const ICONDIR_SIZE: usize = 6;
+const ICONDIRENTRY_SIZE: usize = 16;
+const ICON_TYPE: u16 = 1;
+
+/// A single image stored in an `.ico` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct IcoFrame<'a> {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) bit_count: u16,
+    /// The frame's image data, either a DIB without the file header or a PNG stream.
+    pub(crate) data: &'a [u8],
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+/// Parses the ICONDIR of `bytes` and returns its frames.
+pub(crate) fn parse(bytes: &[u8]) -> Result<Vec<IcoFrame<'_>>, BadIcon> {
+    if bytes.len() < ICONDIR_SIZE {
+        return Err(BadIcon::MalformedIco(
+            "data is shorter than the ICONDIR header",
+        ));
+    }
+    if read_u16(bytes, 0) != 0 || read_u16(bytes, 2) != ICON_TYPE {
+        return Err(BadIcon::MalformedIco(
+            "data doesn't start with an icon ICONDIR",
+        ));
+    }
+
+    let count = read_u16(bytes, 4) as usize;
+    if count == 0 {
+        return Err(BadIcon::MalformedIco("ICONDIR contains no frames"));
+    }
+    if bytes.len() < ICONDIR_SIZE + count * ICONDIRENTRY_SIZE {
+        return Err(BadIcon::MalformedIco("ICONDIR entries are truncated"));
+    }
+
+    (0..count)
+        .map(|i| {
+            let entry = ICONDIR_SIZE + i * ICONDIRENTRY_SIZE;
+            // a width or height of 0 means 256 pixels
+            let dimension = |byte: u8| if byte == 0 { 256 } else { byte as u32 };
+            let size = read_u32(bytes, entry + 8) as usize;
+            let offset = read_u32(bytes, entry + 12) as usize;
+            let data = offset
+                .checked_add(size)
+                .and_then(|end| bytes.get(offset..end))
+                .ok_or(BadIcon::MalformedIco("frame data is out of bounds"))?;
+
+            Ok(IcoFrame {
+                width: dimension(bytes[entry]),
+                height: dimension(bytes[entry + 1]),
+                bit_count: read_u16(bytes, entry + 6),
+                data,
+            })
+        })
+        .collect()
+}
+
+/// Returns the frame whose width is closest to `size`.
+///
+/// When two frames are equally close, the larger one is preferred, then the one
+/// with the higher color depth.
+pub(crate) fn closest_frame<'a>(frames: &[IcoFrame<'a>], size: u32) -> Option<IcoFrame<'a>> {
+    frames
+        .iter()
+        .min_by_key(|frame| {
+            (
+                frame.width.abs_diff(size),
+                std::cmp::Reverse(frame.width),
+                std::cmp::Reverse(frame.bit_count),
+            )
+        })
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds an ICO file with one frame per `(width, bit_count)` pair, each frame's
+    /// data being a single byte equal to its index.
+    fn fixture(frames: &[(u8, u16)]) -> Vec<u8> {
+        let mut bytes = vec![0, 0, 1, 0, frames.len() as u8, 0];
+        let data_start = ICONDIR_SIZE + frames.len() * ICONDIRENTRY_SIZE;
+        for (i, (width, bit_count)) in frames.iter().enumerate() {
+            bytes.extend_from_slice(&[*width, *width, 0, 0, 1, 0]);
+            bytes.extend_from_slice(&bit_count.to_le_bytes());
+            bytes.extend_from_slice(&1u32.to_le_bytes());
+            bytes.extend_from_slice(&((data_start + i) as u32).to_le_bytes());
+        }
+        bytes.extend(0..frames.len() as u8);
+        bytes
+    }
+
+    #[test]
+    fn selects_closest_frame() {
+        let bytes = fixture(&[(16, 32), (24, 32), (32, 8), (32, 32), (48, 32), (0, 32)]);
+        let frames = parse(&bytes).unwrap();
+        assert_eq!(frames.len(), 6);
+        assert_eq!(frames[5].width, 256);
+
+        let selected = |size| closest_frame(&frames, size).unwrap().data[0];
+        assert_eq!(selected(16), 0);
+        assert_eq!(selected(20), 1);
+        // prefers the higher color depth when sizes match
+        assert_eq!(selected(32), 3);
+        assert_eq!(selected(40), 4);
+        assert_eq!(selected(512), 5);
+    }
+
+    #[test]
+    fn rejects_malformed_data() {
+        let bytes = fixture(&[(16, 32), (32, 32)]);
+
+        assert!(matches!(parse(&bytes[..4]), Err(BadIcon::MalformedIco(_))));
+        assert!(matches!(parse(&bytes[..20]), Err(BadIcon::MalformedIco(_))));
+        assert!(matches!(
+            parse(&bytes[..bytes.len() - 1]),
+            Err(BadIcon::MalformedIco(_))
+        ));
+        assert!(matches!(
+            parse(&[0, 0, 2, 0, 0, 0]),
+            Err(BadIcon::MalformedIco(_))
+        ));
+    }
+}
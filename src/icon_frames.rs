@@ -0,0 +1,300 @@
+use std::time::Duration;
+
+use crate::{
+    icon::{compose, png_to_rgba, PIXEL_SIZE},
+    BadIcon, Icon,
+};
+
+/// The delay used for frames without one, matching what browsers do.
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// Decoders for animated images, producing frames for
+/// [`TrayIcon::set_icon_animation_frames`](crate::TrayIcon::set_icon_animation_frames).
+///
+/// Frames are composited according to their disposal and blend methods and converted to
+/// [`Icon`]s up front, so playing the animation doesn't allocate.
+pub struct IconFrames;
+
+impl IconFrames {
+    /// Decodes an animated GIF into its frames and their delays.
+    ///
+    /// Invalid data returns [`BadIcon::DecodeError`].
+    pub fn from_gif_bytes(bytes: &[u8]) -> Result<Vec<(Icon, Duration)>, BadIcon> {
+        let (width, height, frames) = decode_gif(bytes)?;
+        to_icons(width, height, frames)
+    }
+
+    /// Decodes an animated PNG into its frames and their delays.
+    ///
+    /// Invalid data or a PNG without an animation returns [`BadIcon::DecodeError`].
+    pub fn from_apng_bytes(bytes: &[u8]) -> Result<Vec<(Icon, Duration)>, BadIcon> {
+        let (width, height, frames) = decode_apng(bytes)?;
+        to_icons(width, height, frames)
+    }
+}
+
+/// Fully composited RGBA frames along with their delays.
+type Frames = Vec<(Vec<u8>, Duration)>;
+
+fn to_icons(width: u32, height: u32, frames: Frames) -> Result<Vec<(Icon, Duration)>, BadIcon> {
+    if frames.is_empty() {
+        return Err(BadIcon::DecodeError("image contains no frames".to_string()));
+    }
+
+    frames
+        .into_iter()
+        .map(|(rgba, delay)| Ok((Icon::from_rgba(rgba, width, height)?, delay)))
+        .collect()
+}
+
+/// A region of the canvas covered by a frame.
+#[derive(Clone, Copy)]
+struct Region {
+    left: u32,
+    top: u32,
+    width: u32,
+    height: u32,
+}
+
+impl Region {
+    /// Calls `f` with the canvas index and frame index of each pixel of the region
+    /// that lies within a canvas `canvas_width` x `canvas_height` pixels large.
+    fn for_each_pixel(
+        self,
+        canvas_width: u32,
+        canvas_height: u32,
+        mut f: impl FnMut(usize, usize),
+    ) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (cx, cy) = (self.left + x, self.top + y);
+                if cx < canvas_width && cy < canvas_height {
+                    f(
+                        (cy * canvas_width + cx) as usize * PIXEL_SIZE,
+                        (y * self.width + x) as usize * PIXEL_SIZE,
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn decode_gif(bytes: &[u8]) -> Result<(u32, u32, Frames), BadIcon> {
+    let decode_error = |e: gif::DecodingError| BadIcon::DecodeError(e.to_string());
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(bytes).map_err(decode_error)?;
+    let (width, height) = (decoder.width() as u32, decoder.height() as u32);
+
+    let mut canvas = vec![0u8; (width * height) as usize * PIXEL_SIZE];
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().map_err(decode_error)? {
+        let region = Region {
+            left: frame.left as u32,
+            top: frame.top as u32,
+            width: frame.width as u32,
+            height: frame.height as u32,
+        };
+        if frame.buffer.len() < (region.width * region.height) as usize * PIXEL_SIZE {
+            return Err(BadIcon::DecodeError("frame data is truncated".to_string()));
+        }
+
+        let previous = (frame.dispose == gif::DisposalMethod::Previous).then(|| canvas.clone());
+
+        // GIF transparency is binary, transparent pixels keep what's below them
+        region.for_each_pixel(width, height, |c, f| {
+            if frame.buffer[f + 3] != 0 {
+                canvas[c..c + PIXEL_SIZE].copy_from_slice(&frame.buffer[f..f + PIXEL_SIZE]);
+            }
+        });
+
+        // delays are in hundredths of a second
+        let delay = match frame.delay {
+            0 => DEFAULT_FRAME_DELAY,
+            delay => Duration::from_millis(delay as u64 * 10),
+        };
+        frames.push((canvas.clone(), delay));
+
+        match frame.dispose {
+            gif::DisposalMethod::Background => region.for_each_pixel(width, height, |c, _| {
+                canvas[c..c + PIXEL_SIZE].fill(0);
+            }),
+            gif::DisposalMethod::Previous => canvas = previous.unwrap_or(canvas),
+            gif::DisposalMethod::Keep | gif::DisposalMethod::Any => {}
+        }
+    }
+
+    Ok((width, height, frames))
+}
+
+fn decode_apng(bytes: &[u8]) -> Result<(u32, u32, Frames), BadIcon> {
+    let decode_error = |e: png::DecodingError| BadIcon::DecodeError(e.to_string());
+
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(decode_error)?;
+
+    let info = reader.info();
+    let (width, height) = (info.width, info.height);
+    let Some(animation_control) = info.animation_control else {
+        return Err(BadIcon::DecodeError("PNG isn't animated".to_string()));
+    };
+
+    let mut buf = vec![0; reader.output_buffer_size()];
+    // the default image is only part of the animation when it has a frame control
+    if reader.info().frame_control.is_none() {
+        reader.next_frame(&mut buf).map_err(decode_error)?;
+    }
+
+    let mut canvas = vec![0u8; (width * height) as usize * PIXEL_SIZE];
+    let mut frames = Vec::new();
+    for _ in 0..animation_control.num_frames {
+        let output = reader.next_frame(&mut buf).map_err(decode_error)?;
+        let control = reader
+            .info()
+            .frame_control
+            .ok_or_else(|| BadIcon::DecodeError("frame has no frame control".to_string()))?;
+        let pixels = png_to_rgba(buf[..output.buffer_size()].to_vec(), output.color_type)?;
+
+        let region = Region {
+            left: control.x_offset,
+            top: control.y_offset,
+            width: control.width,
+            height: control.height,
+        };
+        if pixels.len() < (region.width * region.height) as usize * PIXEL_SIZE {
+            return Err(BadIcon::DecodeError("frame data is truncated".to_string()));
+        }
+
+        let previous = (control.dispose_op == png::DisposeOp::Previous).then(|| canvas.clone());
+
+        region.for_each_pixel(width, height, |c, f| {
+            let src = [pixels[f], pixels[f + 1], pixels[f + 2], pixels[f + 3]];
+            match control.blend_op {
+                png::BlendOp::Source => canvas[c..c + PIXEL_SIZE].copy_from_slice(&src),
+                png::BlendOp::Over => compose::blend(&mut canvas[c..c + PIXEL_SIZE], src, 1.),
+            }
+        });
+
+        // a denominator of 0 means hundredths of a second
+        let denominator = match control.delay_den {
+            0 => 100,
+            den => den,
+        };
+        let delay = match control.delay_num {
+            0 => DEFAULT_FRAME_DELAY,
+            num => Duration::from_secs_f64(num as f64 / denominator as f64),
+        };
+        frames.push((canvas.clone(), delay));
+
+        match control.dispose_op {
+            png::DisposeOp::Background => region.for_each_pixel(width, height, |c, _| {
+                canvas[c..c + PIXEL_SIZE].fill(0);
+            }),
+            png::DisposeOp::Previous => canvas = previous.unwrap_or(canvas),
+            png::DisposeOp::None => {}
+        }
+    }
+
+    Ok((width, height, frames))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 4] = [255, 0, 0, 255];
+    const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+    fn pixel(rgba: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+        let i = (y * width + x) as usize * PIXEL_SIZE;
+        [rgba[i], rgba[i + 1], rgba[i + 2], rgba[i + 3]]
+    }
+
+    #[test]
+    fn decodes_gif_frames() {
+        let mut bytes = Vec::new();
+        {
+            // palette: red, blue, transparent
+            let mut encoder =
+                gif::Encoder::new(&mut bytes, 2, 2, &[255, 0, 0, 0, 0, 255, 0, 0, 0]).unwrap();
+            let frame = |left, top, width, height, buffer: &'static [u8], dispose| gif::Frame {
+                left,
+                top,
+                width,
+                height,
+                buffer: buffer.into(),
+                transparent: Some(2),
+                delay: 5,
+                dispose,
+                ..Default::default()
+            };
+            encoder
+                .write_frame(&frame(0, 0, 2, 2, &[0; 4], gif::DisposalMethod::Keep))
+                .unwrap();
+            encoder
+                .write_frame(&frame(1, 1, 1, 1, &[1], gif::DisposalMethod::Background))
+                .unwrap();
+            encoder
+                .write_frame(&frame(0, 0, 2, 1, &[2, 1], gif::DisposalMethod::Keep))
+                .unwrap();
+        }
+
+        let (width, height, frames) = decode_gif(&bytes).unwrap();
+        assert_eq!((width, height, frames.len()), (2, 2, 3));
+        assert!(frames
+            .iter()
+            .all(|(_, delay)| *delay == Duration::from_millis(50)));
+
+        assert_eq!(frames[0].0, RED.repeat(4));
+        assert_eq!(pixel(&frames[1].0, 2, 1, 1), BLUE);
+        assert_eq!(pixel(&frames[1].0, 2, 0, 0), RED);
+        // the second frame was cleared to transparent, the third one keeps it
+        assert_eq!(pixel(&frames[2].0, 2, 0, 0), RED);
+        assert_eq!(pixel(&frames[2].0, 2, 1, 0), BLUE);
+        assert_eq!(pixel(&frames[2].0, 2, 1, 1), [0; 4]);
+    }
+
+    #[test]
+    fn decodes_apng_frames() {
+        let mut bytes = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut bytes, 2, 2);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.set_animated(2, 0).unwrap();
+            encoder.set_frame_delay(1, 4).unwrap();
+            let mut writer = encoder.write_header().unwrap();
+            writer.write_image_data(&RED.repeat(4)).unwrap();
+
+            writer.set_frame_dimension(1, 1).unwrap();
+            writer.set_frame_position(1, 1).unwrap();
+            writer.set_blend_op(png::BlendOp::Over).unwrap();
+            writer.write_image_data(&[0, 0, 255, 128]).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let (width, height, frames) = decode_apng(&bytes).unwrap();
+        assert_eq!((width, height, frames.len()), (2, 2, 2));
+        assert_eq!(frames[0].1, Duration::from_millis(250));
+
+        assert_eq!(frames[0].0, RED.repeat(4));
+        let mut blended = RED;
+        compose::blend(&mut blended, [0, 0, 255, 128], 1.);
+        assert_eq!(pixel(&frames[1].0, 2, 1, 1), blended);
+        assert_eq!(pixel(&frames[1].0, 2, 0, 0), RED);
+    }
+
+    #[test]
+    fn rejects_malformed_data() {
+        assert!(matches!(
+            IconFrames::from_gif_bytes(b"GIF89a"),
+            Err(BadIcon::DecodeError(_))
+        ));
+        assert!(matches!(
+            IconFrames::from_apng_bytes(include_bytes!("../examples/icon.png")),
+            Err(BadIcon::DecodeError(_))
+        ));
+    }
+}
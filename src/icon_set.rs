@@ -0,0 +1,85 @@
+use crate::Icon;
+
+/// A set of [`Icon`]s tagged with their pixel sizes.
+///
+/// When used with [`TrayIconBuilder::with_icon_set`](crate::TrayIconBuilder::with_icon_set) or
+/// [`TrayIcon::set_icon_set`](crate::TrayIcon::set_icon_set), the icon closest to the tray
+/// icon size for the DPI of the taskbar monitor is picked, so icons stay crisp on scaled displays.
+///
+/// Icons created from pixel data should use [`IconOptions::no_resize`](crate::IconOptions::no_resize),
+/// otherwise they are all rescaled to the current tray icon size anyway.
+#[derive(Debug, Clone, Default)]
+pub struct IconSet {
+    icons: Vec<(u32, Icon)>,
+}
+
+impl IconSet {
+    /// Creates a new empty [`IconSet`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an icon that is `size` x `size` pixels to this set.
+    pub fn with_icon(mut self, size: u32, icon: Icon) -> Self {
+        self.add(size, icon);
+        self
+    }
+
+    /// Adds an icon that is `size` x `size` pixels to this set.
+    ///
+    /// If the set already contains an icon of that size, it is replaced.
+    pub fn add(&mut self, size: u32, icon: Icon) {
+        match self.icons.iter_mut().find(|(s, _)| *s == size) {
+            Some(entry) => entry.1 = icon,
+            None => self.icons.push((size, icon)),
+        }
+    }
+
+    /// Returns `true` if this set contains no icons.
+    pub fn is_empty(&self) -> bool {
+        self.icons.is_empty()
+    }
+
+    /// Returns the icon whose size is closest to `size`.
+    ///
+    /// When two icons are equally close, the larger one is preferred since
+    /// downscaling looks better than upscaling.
+    pub fn closest(&self, size: u32) -> Option<&Icon> {
+        self.icons
+            .iter()
+            .min_by_key(|(s, _)| (s.abs_diff(size), std::cmp::Reverse(*s)))
+            .map(|(_, icon)| icon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IconOptions;
+
+    fn icon(size: u32) -> Icon {
+        let options = IconOptions { no_resize: true };
+        Icon::from_rgba_with_options(vec![255; (size * size * 4) as usize], size, size, options)
+            .unwrap()
+    }
+
+    #[test]
+    fn picks_closest_size() {
+        let small = icon(16);
+        let medium = icon(24);
+        let large = icon(32);
+        let set = IconSet::new()
+            .with_icon(16, small.clone())
+            .with_icon(24, medium.clone())
+            .with_icon(32, large.clone());
+
+        let handle = |size| set.closest(size).unwrap().as_raw_handle();
+        assert_eq!(handle(8), small.as_raw_handle());
+        assert_eq!(handle(16), small.as_raw_handle());
+        assert_eq!(handle(20), medium.as_raw_handle());
+        assert_eq!(handle(28), large.as_raw_handle());
+        assert_eq!(handle(40), large.as_raw_handle());
+
+        assert!(IconSet::new().closest(16).is_none());
+    }
+}
@@ -92,23 +92,64 @@
 //! [winit]: https://docs.rs/winit
 //! [tao]: https://docs.rs/tao
 
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::PathBuf,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, RwLock,
+    },
+    time::Duration,
+};
 
 use counter::Counter;
-use crossbeam_channel::{unbounded, Receiver, Sender};
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender, TrySendError};
 use muda_win::{MenuEvent, MenuEventHandler};
 use platform_impl::TrayIcon as PlatformTrayIcon;
 use std::sync::{LazyLock, OnceLock};
 
+#[cfg(feature = "async")]
+pub mod async_support;
+mod attention;
 mod counter;
 mod error;
 mod icon;
+#[cfg(feature = "animation")]
+mod icon_frames;
+mod icon_set;
+mod notification;
 mod platform_impl;
+mod proxy;
+#[cfg(feature = "schemars")]
+mod schema;
+#[cfg(feature = "test-util")]
+mod test_util;
+mod timestamp;
+#[cfg(feature = "toast")]
+mod toast;
+mod tooltip;
 mod tray_icon_id;
+mod update;
 
+pub use self::attention::AttentionConfig;
 pub use self::error::*;
-pub use self::icon::{BadIcon, Icon};
+pub use self::icon::{BadIcon, Icon, IconOptions, OverlayCorner, StockIcon, TintMode};
+#[cfg(feature = "animation")]
+pub use self::icon_frames::IconFrames;
+pub use self::icon_set::IconSet;
+pub use self::notification::{
+    user_notification_state, Notification, NotificationId, NotificationKind, NotificationOverflow,
+    SuppressionReason, UserNotificationState,
+};
+pub use self::proxy::{ProxyReply, TrayIconProxy};
+pub use self::timestamp::Timestamp;
+#[cfg(feature = "toast")]
+pub use self::toast::{ShownToast, Toast};
+pub use self::tooltip::Tooltip;
 pub use self::tray_icon_id::TrayIconId;
+pub use self::update::TrayIconUpdate;
 
 /// Re-export of [muda-win](::muda_win) crate and used for tray context menu.
 pub mod menu {
@@ -117,6 +158,7 @@ pub mod menu {
 pub use muda_win::dpi;
 
 static COUNTER: Counter = Counter::new();
+static NOTIFICATION_COUNTER: Counter = Counter::new();
 
 /// Attributes to use when creating a tray icon.
 pub struct TrayIconAttributes {
@@ -132,8 +174,48 @@ pub struct TrayIconAttributes {
     /// Tray icon
     pub icon: Option<Icon>,
 
-    /// Whether to show the tray menu on left click or not, default is `true`.
+    /// Tray icon set, the icon closest to the tray icon size is picked from it.
+    ///
+    /// Takes precedence over `icon` when set.
+    pub icon_set: Option<IconSet>,
+
+    /// Tray icons for light and dark taskbars as `(light, dark)`, swapped automatically
+    /// when the system theme changes.
+    ///
+    /// Takes precedence over `icon` and `icon_set` when set.
+    pub icon_pair: Option<(Icon, Icon)>,
+
+    /// Whether to show the tray menu on left click or keyboard selection, default is `true`.
     pub menu_on_left_click: bool,
+
+    /// Whether the shell shows the standard tooltip, default is `true`.
+    pub standard_tooltip: bool,
+
+    /// Whether to send [`TrayIconEvent::Wheel`] events, default is `false`.
+    pub wheel_events: bool,
+
+    /// Which [`TrayIconEvent::Move`] events to send, default is [`MoveEventPolicy::All`].
+    pub move_events: MoveEventPolicy,
+
+    /// Whether to send [`TrayIconEvent::Enter`], [`TrayIconEvent::Move`] and
+    /// [`TrayIconEvent::Leave`] events, default is `true`.
+    pub hover_events: bool,
+
+    /// Whether to drop the [`TrayIconEvent::Click`]s of double clicks, default is `false`.
+    pub double_click_detection: bool,
+
+    /// How long the mouse has to stay over the tray icon for a [`TrayIconEvent::HoverDwell`],
+    /// default is `None`.
+    pub hover_dwell: Option<Duration>,
+
+    /// The GUID identifying the tray icon to the shell, default is `None`.
+    pub guid: Option<[u8; 16]>,
+
+    /// Whether the id may be the one of another live tray icon, default is `false`.
+    pub allow_duplicate_ids: bool,
+
+    /// Whether the tray icon is shown once built, default is `true`.
+    pub visible: bool,
 }
 
 impl Default for TrayIconAttributes {
@@ -142,8 +224,19 @@ impl Default for TrayIconAttributes {
             tooltip: None,
             menu: None,
             icon: None,
+            icon_set: None,
+            icon_pair: None,
             menu_on_left_click: true,
             menu_event: None,
+            standard_tooltip: true,
+            wheel_events: false,
+            move_events: MoveEventPolicy::All,
+            hover_events: true,
+            double_click_detection: false,
+            hover_dwell: None,
+            guid: None,
+            allow_duplicate_ids: false,
+            visible: true,
         }
     }
 }
@@ -153,6 +246,8 @@ impl Default for TrayIconAttributes {
 pub struct TrayIconBuilder {
     id: TrayIconId,
     attrs: TrayIconAttributes,
+    /// An icon file and size to load in [`TrayIconBuilder::build`].
+    icon_path: Option<(PathBuf, Option<(u32, u32)>)>,
 }
 
 impl TrayIconBuilder {
@@ -163,6 +258,7 @@ impl TrayIconBuilder {
         Self {
             id: TrayIconId(COUNTER.next().to_string()),
             attrs: TrayIconAttributes::default(),
+            icon_path: None,
         }
     }
 
@@ -181,16 +277,144 @@ impl TrayIconBuilder {
     /// Set an icon for this tray icon.
     pub fn with_icon(mut self, icon: Icon) -> Self {
         self.attrs.icon = Some(icon);
+        self.attrs.icon_set = None;
+        self.attrs.icon_pair = None;
+        self.icon_path = None;
+        self
+    }
+
+    /// Set an icon for this tray icon that is loaded from a file with [`Icon::from_path`]
+    /// when [`TrayIconBuilder::build`] is called.
+    ///
+    /// If the icon can't be loaded, `build` returns [`Error::BadIcon`].
+    pub fn with_icon_from_path<P: AsRef<std::path::Path>>(
+        mut self,
+        path: P,
+        size: Option<(u32, u32)>,
+    ) -> Self {
+        self.icon_path = Some((path.as_ref().to_path_buf(), size));
+        self.attrs.icon = None;
+        self.attrs.icon_set = None;
+        self.attrs.icon_pair = None;
+        self
+    }
+
+    /// Set an icon set for this tray icon, the icon closest to the tray icon size
+    /// for the DPI of the taskbar monitor is used.
+    pub fn with_icon_set(mut self, icon_set: IconSet) -> Self {
+        self.attrs.icon_set = Some(icon_set);
+        self.attrs.icon = None;
+        self.attrs.icon_pair = None;
+        self.icon_path = None;
+        self
+    }
+
+    /// Set icons for light and dark taskbars, the one matching the current system theme
+    /// is used and they are swapped automatically when the theme changes.
+    ///
+    /// `light` is shown on a light taskbar, so it should typically be a dark glyph, and vice versa.
+    pub fn with_icon_pair(mut self, light: Icon, dark: Icon) -> Self {
+        self.attrs.icon_pair = Some((light, dark));
+        self.attrs.icon = None;
+        self.attrs.icon_set = None;
+        self.icon_path = None;
         self
     }
 
-    /// Set a tooltip for this tray icon.
+    /// Set a tooltip for this tray icon, see [`Tooltip`] for multiline tooltips.
+    ///
+    /// If it's longer than 127 UTF-16 code units, `build` returns [`Error::TooltipTooLong`].
     pub fn with_tooltip<S: AsRef<str>>(mut self, s: S) -> Self {
         self.attrs.tooltip = Some(s.as_ref().to_string());
         self
     }
 
-    /// Whether to show the tray menu on left click or not, default is `true`. **macOS only**.
+    /// Whether the shell shows the standard tooltip when hovering the tray icon, default is `true`.
+    ///
+    /// See [`TrayIcon::set_standard_tooltip`].
+    pub fn with_standard_tooltip(mut self, enable: bool) -> Self {
+        self.attrs.standard_tooltip = enable;
+        self
+    }
+
+    /// Whether to send [`TrayIconEvent::Wheel`] events when the mouse wheel is scrolled over the
+    /// tray icon, default is `false`.
+    ///
+    /// The shell doesn't forward mouse wheel messages to tray icons, so this installs a
+    /// low-level mouse hook, which sees all mouse input of the session while the tray icon exists.
+    pub fn with_wheel_events(mut self, enable: bool) -> Self {
+        self.attrs.wheel_events = enable;
+        self
+    }
+
+    /// Which [`TrayIconEvent::Move`] events to send while the cursor hovers the tray icon,
+    /// default is [`MoveEventPolicy::All`].
+    pub fn with_move_events(mut self, policy: MoveEventPolicy) -> Self {
+        self.attrs.move_events = policy;
+        self
+    }
+
+    /// Whether to send [`TrayIconEvent::Enter`], [`TrayIconEvent::Move`] and
+    /// [`TrayIconEvent::Leave`] events, default is `true`.
+    ///
+    /// See [`TrayIcon::set_hover_events`].
+    pub fn with_hover_events(mut self, enable: bool) -> Self {
+        self.attrs.hover_events = enable;
+        self
+    }
+
+    /// Whether to hold back [`TrayIconEvent::Click`]s for the double click time and drop
+    /// them when a [`TrayIconEvent::DoubleClick`] follows, default is `false`.
+    ///
+    /// Single clicks are delayed by the double click time when enabled, while the menu is
+    /// still shown right away.
+    pub fn with_double_click_detection(mut self, enable: bool) -> Self {
+        self.attrs.double_click_detection = enable;
+        self
+    }
+
+    /// Identifies the tray icon to the shell by `guid`, with the bytes in the order of
+    /// RFC 4122 as returned by `uuid::Uuid::as_bytes`.
+    ///
+    /// Windows keeps user preferences, like whether the icon is shown in the overflow area,
+    /// per GUID, so they survive restarts and reinstalls. The GUID is tied to the path of the
+    /// executable it was first used by, from other paths building or showing the icon fails
+    /// with [`Error::GuidRejected`] and should fall back to a tray icon without a GUID.
+    pub fn with_guid(mut self, guid: [u8; 16]) -> Self {
+        self.attrs.guid = Some(guid);
+        self
+    }
+
+    /// Whether the tray icon is shown once built, default is `true`.
+    ///
+    /// A hidden icon is only added to the notification area by [`TrayIcon::set_visible`],
+    /// with the attributes set until then. A GUID the shell rejects makes that fail instead
+    /// of building, see [`TrayIconBuilder::with_guid`].
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.attrs.visible = visible;
+        self
+    }
+
+    /// Allows building the tray icon while another live one has the same id, instead of
+    /// failing with [`Error::DuplicateId`]. Events of both icons then carry the same id.
+    pub fn allow_duplicate_ids(mut self) -> Self {
+        self.attrs.allow_duplicate_ids = true;
+        self
+    }
+
+    /// Sends a [`TrayIconEvent::HoverDwell`] once the mouse stayed over the tray icon for
+    /// `duration`, leaving the icon earlier cancels it. Requires hover events, see
+    /// [`TrayIconBuilder::with_hover_events`].
+    pub fn with_hover_dwell(mut self, duration: Duration) -> Self {
+        self.attrs.hover_dwell = Some(duration);
+        self
+    }
+
+    /// Whether to show the tray menu on left click or not, default is `true`.
+    ///
+    /// The menu shows when the left button is released over the icon, after the
+    /// [`TrayIconEvent::Click`] of the button up was sent. With double click detection, that
+    /// click may only be sent while the menu is open. Right clicks always show the menu.
     pub fn with_menu_on_left_click(mut self, enable: bool) -> Self {
         self.attrs.menu_on_left_click = enable;
         self
@@ -203,7 +427,10 @@ impl TrayIconBuilder {
     }
 
     /// Builds and adds a new [`TrayIcon`] to the system tray.
-    pub fn build(self) -> Result<TrayIcon> {
+    pub fn build(mut self) -> Result<TrayIcon> {
+        if let Some((path, size)) = self.icon_path {
+            self.attrs.icon = Some(Icon::from_path(path, size)?);
+        }
         TrayIcon::with_id(self.id, self.attrs)
     }
 
@@ -221,11 +448,13 @@ impl TrayIconBuilder {
 
 // Tray icon struct and associated methods.
 ///
-/// This type is reference-counted and the icon is removed when the last instance is dropped.
+/// This type is reference-counted and the icon is removed when the last instance is dropped,
+/// or by [`TrayIcon::remove`].
 #[derive(Clone, Debug)]
 pub struct TrayIcon {
     id: TrayIconId,
-    tray: Rc<RefCell<PlatformTrayIcon>>,
+    /// `None` once the icon was removed.
+    tray: Rc<RefCell<Option<PlatformTrayIcon>>>,
 }
 
 impl TrayIcon {
@@ -233,18 +462,27 @@ impl TrayIcon {
     pub fn new(attrs: TrayIconAttributes) -> Result<Self> {
         let id = TrayIconId(COUNTER.next().to_string());
         Ok(Self {
-            tray: Rc::new(RefCell::new(PlatformTrayIcon::new(id.clone(), attrs)?)),
+            tray: Rc::new(RefCell::new(Some(PlatformTrayIcon::new(
+                id.clone(),
+                attrs,
+            )?))),
             id,
         })
     }
 
     /// Builds and adds a new tray icon to the system tray with the specified Id.
     ///
+    /// Fails with [`Error::DuplicateId`] while another tray icon that wasn't dropped or
+    /// removed has the id, see [`TrayIconBuilder::allow_duplicate_ids`].
+    ///
     /// See [`TrayIcon::new`] for more info.
     pub fn with_id<I: Into<TrayIconId>>(id: I, attrs: TrayIconAttributes) -> Result<Self> {
         let id = id.into();
         Ok(Self {
-            tray: Rc::new(RefCell::new(PlatformTrayIcon::new(id.clone(), attrs)?)),
+            tray: Rc::new(RefCell::new(Some(PlatformTrayIcon::new(
+                id.clone(),
+                attrs,
+            )?))),
             id,
         })
     }
@@ -254,40 +492,438 @@ impl TrayIcon {
         &self.id
     }
 
+    /// Removes the tray icon right away, for this and all other clones of it.
+    ///
+    /// Afterwards, methods returning a [`Result`] fail with [`Error::Removed`], including
+    /// this one, and other methods do nothing.
+    pub fn remove(&self) -> Result<()> {
+        let tray = self.tray.borrow_mut().take().ok_or(Error::Removed)?;
+        tray.remove()
+    }
+
+    /// Returns the handle of the hidden window owning the tray icon, `0` once removed.
+    ///
+    /// The window is destroyed when the tray icon is removed or its last clone is dropped,
+    /// and must only be used on the thread that built the tray icon.
+    pub fn window_handle(&self) -> isize {
+        self.platform().map_or(0, |tray| tray.window_handle())
+    }
+
+    /// Returns a proxy to control the tray icon from other threads.
+    pub fn proxy(&self) -> Result<TrayIconProxy> {
+        let proxy = self.platform()?.proxy();
+        Ok(TrayIconProxy::new(self.id.clone(), proxy))
+    }
+
+    fn platform(&self) -> Result<std::cell::Ref<'_, PlatformTrayIcon>> {
+        std::cell::Ref::filter_map(self.tray.borrow(), Option::as_ref).map_err(|_| Error::Removed)
+    }
+
+    fn platform_mut(&self) -> Result<std::cell::RefMut<'_, PlatformTrayIcon>> {
+        std::cell::RefMut::filter_map(self.tray.borrow_mut(), Option::as_mut)
+            .map_err(|_| Error::Removed)
+    }
+
     /// Set new tray icon. If `None` is provided, it will remove the icon.
+    ///
+    /// Setting the icon that is already shown doesn't call the shell.
     pub fn set_icon(&self, icon: Option<Icon>) -> Result<()> {
-        self.tray.borrow_mut().set_icon(icon)
+        self.platform_mut()?.set_icon(icon)
+    }
+
+    /// Set a new tray icon loaded from a file with [`Icon::from_path`]. If `None` is provided,
+    /// it will remove the icon like [`TrayIcon::set_icon`].
+    ///
+    /// If the icon can't be loaded, [`Error::BadIcon`] is returned and the current icon is kept.
+    pub fn set_icon_from_path<P: AsRef<std::path::Path>>(&self, path: Option<P>) -> Result<()> {
+        let icon = path.map(|path| Icon::from_path(path, None)).transpose()?;
+        self.set_icon(icon)
+    }
+
+    /// Returns the icon currently displayed, sharing its handle with the tray icon.
+    ///
+    /// This is the icon matching the taskbar for icon sets and pairs, with the badge if one
+    /// is set, or the current frame of an animation.
+    pub fn icon(&self) -> Option<Icon> {
+        self.platform().ok()?.icon()
+    }
+
+    /// Set icons for light and dark taskbars, the one matching the current system theme
+    /// is used and they are swapped automatically when the theme changes.
+    ///
+    /// `light` is shown on a light taskbar, so it should typically be a dark glyph, and vice versa.
+    /// Calling [`TrayIcon::set_icon`] or [`TrayIcon::set_icon_set`] stops the automatic switching
+    /// until this is called again.
+    pub fn set_icon_pair(&self, light: Icon, dark: Icon) -> Result<()> {
+        self.platform_mut()?.set_icon_pair(light, dark)
+    }
+
+    /// Animates the tray icon by cycling through `frames`, showing each one for `frame_duration`.
+    ///
+    /// The frames are driven by a timer on the tray icon's thread, so the event loop doesn't
+    /// have to be involved. Frames are shown as is, without the badge set with [`TrayIcon::set_badge`].
+    ///
+    /// The animation runs until [`TrayIcon::stop_animation`] is called, or a new icon is set with
+    /// [`TrayIcon::set_icon`], [`TrayIcon::set_icon_set`] or [`TrayIcon::set_icon_pair`].
+    /// An empty `frames` stops the animation.
+    pub fn set_icon_animation(&self, frames: Vec<Icon>, frame_duration: Duration) -> Result<()> {
+        let frames = frames
+            .into_iter()
+            .map(|icon| (icon, frame_duration))
+            .collect();
+        self.set_icon_animation_frames(frames)
+    }
+
+    /// Animates the tray icon like [`TrayIcon::set_icon_animation`], but each frame is shown
+    /// for its own duration, e.g. frames decoded from an animated image.
+    pub fn set_icon_animation_frames(&self, frames: Vec<(Icon, Duration)>) -> Result<()> {
+        self.platform_mut()?.set_icon_animation(Some(frames))
+    }
+
+    /// Stops the animation started with [`TrayIcon::set_icon_animation`] or
+    /// [`TrayIcon::set_icon_animation_frames`] and restores the last static icon.
+    pub fn stop_animation(&self) -> Result<()> {
+        self.platform_mut()?.set_icon_animation(None)
+    }
+
+    /// Draws attention to the tray icon by alternating it with the icon of `config`, e.g.
+    /// when something happened in the background. `None` stops it.
+    ///
+    /// Clicking or selecting the icon stops it as well, and the current icon is shown again,
+    /// including icons set meanwhile.
+    pub fn set_attention(&self, config: Option<AttentionConfig>) -> Result<()> {
+        self.platform_mut()?.set_attention(config)
+    }
+
+    /// Shows a numeric badge, a red circle with `count`, in the corner of the tray icon.
+    /// Counts above 99 are shown as "99+". If `None` is provided, it will remove the badge.
+    ///
+    /// The badge stays applied when the icon is later changed with [`TrayIcon::set_icon`],
+    /// [`TrayIcon::set_icon_set`] or [`TrayIcon::set_icon_pair`]. Icons whose pixels can't be
+    /// read back, e.g. monochrome ones, are shown without a badge.
+    pub fn set_badge(&self, count: Option<u32>) -> Result<()> {
+        self.platform_mut()?.set_badge(count)
+    }
+
+    /// Set new tray icon set, the icon closest to the tray icon size for the DPI
+    /// of the taskbar monitor is used. If `None` is provided, it will remove the icon.
+    ///
+    /// The icon is picked again whenever the tray icon is re-added by the shell,
+    /// until [`TrayIcon::set_icon`] is called.
+    pub fn set_icon_set(&self, icon_set: Option<IconSet>) -> Result<()> {
+        self.platform_mut()?.set_icon_set(icon_set)
+    }
+
+    /// Watches the icon file at `path` and sets it as the tray icon with [`Icon::from_path`]
+    /// whenever it changes, which is handy while iterating on icon artwork.
+    ///
+    /// Rapid successive writes are debounced, and the current icon is kept if the changed file
+    /// can't be loaded. Watching stops when [`TrayIcon::unwatch_icon_path`] is called, another
+    /// path is watched or the tray icon is dropped.
+    #[cfg(feature = "hot-reload")]
+    pub fn watch_icon_path<P: AsRef<std::path::Path>>(&self, path: P) -> Result<()> {
+        self.platform_mut()?.watch_icon_path(path.as_ref())
+    }
+
+    /// Stops watching the icon file set with [`TrayIcon::watch_icon_path`].
+    #[cfg(feature = "hot-reload")]
+    pub fn unwatch_icon_path(&self) {
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.unwatch_icon_path();
+        }
+    }
+
+    /// Shows a notification balloon with `title` and `body` next to this tray icon,
+    /// replacing the one currently shown.
+    ///
+    /// Titles longer than 63 and bodies longer than 255 UTF-16 code units return
+    /// [`Error::NotificationTitleTooLong`] and [`Error::NotificationBodyTooLong`].
+    /// Balloons are attached to the tray icon, so a hidden icon is shown first.
+    ///
+    /// If `silent` is `true`, no sound is played. The returned id is included in the
+    /// notification's events, e.g. [`TrayIconEvent::NotificationClicked`].
+    pub fn show_notification(
+        &self,
+        title: &str,
+        body: &str,
+        kind: NotificationKind,
+        silent: bool,
+    ) -> Result<NotificationId> {
+        self.show_notification_with(
+            Notification::new()
+                .title(title)
+                .body(body)
+                .kind(kind)
+                .silent(silent),
+        )
+    }
+
+    /// Shows `notification` as a balloon next to this tray icon, like [`TrayIcon::show_notification`].
+    ///
+    /// If queueing is enabled with [`TrayIcon::set_notification_queueing`] and a balloon is
+    /// shown, the notification is queued instead.
+    ///
+    /// [`Notification::validate`] is called first, so a notification that doesn't fit
+    /// is rejected before anything is shown.
+    pub fn show_notification_with(&self, notification: Notification) -> Result<NotificationId> {
+        notification.validate()?;
+        let id = NotificationId(NOTIFICATION_COUNTER.next());
+        self.platform_mut()?.show_notification(id, notification)?;
+        Ok(id)
+    }
+
+    /// Shows `toast` as a toast notification, which unlike balloons persists in the Action Center.
+    ///
+    /// Clicking the toast emits [`TrayIconEvent::NotificationClicked`]. Unpackaged apps get an
+    /// AppUserModelID derived from the tray icon id registered for the current user.
+    /// If WinRT toasts aren't available, e.g. before Windows 10, the toast is shown as a
+    /// notification balloon instead, which returns [`ShownToast::Balloon`] and the balloon's
+    /// errors such as [`Error::NotificationBodyTooLong`].
+    #[cfg(feature = "toast")]
+    pub fn show_toast(&self, toast: Toast) -> Result<ShownToast> {
+        let id = NotificationId(NOTIFICATION_COUNTER.next());
+        self.platform_mut()?.show_toast(&self.id, id, &toast)
+    }
+
+    /// Removes the notification balloon currently shown and discards the queued ones.
+    pub fn clear_notification(&self) -> Result<()> {
+        self.platform_mut()?.clear_notification()
+    }
+
+    /// Whether notifications shown while a balloon is open wait in a queue until it closes,
+    /// instead of replacing it. Disabled by default.
+    ///
+    /// The queue holds 16 notifications and drops the oldest ones when full, see
+    /// [`TrayIcon::set_notification_queue_limit`]. Disabling queueing discards the
+    /// queued notifications.
+    pub fn set_notification_queueing(&self, enable: bool) {
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.set_notification_queueing(enable);
+        }
+    }
+
+    /// Sets how many notifications the queue holds, at least one, and what happens when a
+    /// notification is shown while it is full.
+    pub fn set_notification_queue_limit(&self, max_len: usize, overflow: NotificationOverflow) {
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.set_notification_queue_limit(max_len, overflow);
+        }
     }
 
     /// Set new tray menu.
     pub fn set_menu(&self, menu: Option<Box<dyn menu::ContextMenu>>) {
-        self.tray.borrow_mut().set_menu(menu)
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.set_menu(menu);
+        }
     }
 
-    /// Sets the tooltip for this tray icon.
+    /// Sets the tooltip for this tray icon, see [`Tooltip`] for multiline tooltips.
+    ///
+    /// Tooltips longer than 127 UTF-16 code units return [`Error::TooltipTooLong`]. Setting
+    /// the tooltip that is already shown doesn't call the shell.
     pub fn set_tooltip<S: AsRef<str>>(&self, tooltip: Option<S>) -> Result<()> {
-        self.tray.borrow_mut().set_tooltip(tooltip)
+        let tooltip = tooltip.as_ref().map(|t| tooltip::normalize(t.as_ref()));
+        if let Some(tooltip) = &tooltip {
+            tooltip::validate(tooltip)?;
+        }
+        self.platform_mut()?.set_tooltip(tooltip)
+    }
+
+    /// Sets the tooltip for this tray icon like [`TrayIcon::set_tooltip`], but tooltips that
+    /// are too long are truncated on a character boundary instead.
+    pub fn set_tooltip_lossy<S: AsRef<str>>(&self, tooltip: Option<S>) -> Result<()> {
+        let tooltip = tooltip.as_ref().map(|t| tooltip::normalize(t.as_ref()));
+        self.set_tooltip(tooltip.as_deref().map(tooltip::truncate))
+    }
+
+    /// Returns the tooltip of this tray icon, as set with the builder or the last successful
+    /// [`TrayIcon::set_tooltip`] call.
+    pub fn tooltip(&self) -> Option<String> {
+        self.platform().ok()?.tooltip()
+    }
+
+    /// Sets a provider that is called for the tooltip when the cursor hovers this tray icon,
+    /// instead of updating the tooltip periodically. [`TrayIcon::set_tooltip`] removes it again.
+    ///
+    /// The provider is called at most once a second while hovering, its tooltip is truncated
+    /// like with [`TrayIcon::set_tooltip_lossy`]. While a provider is set,
+    /// [`TrayIcon::tooltip`] returns `None`.
+    ///
+    /// The provider runs on the thread of the tray icon while it handles mouse messages, so it
+    /// should return quickly, e.g. by reading cached values. It must not call back into this
+    /// tray icon.
+    pub fn set_tooltip_provider(&self, provider: Option<Box<dyn FnMut() -> String>>) {
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.set_tooltip_provider(provider);
+        }
+    }
+
+    /// Sets a hook called with the message, `wparam` and `lparam` of messages the hidden
+    /// window of the tray icon doesn't handle itself, e.g. `WM_POWERBROADCAST` or messages
+    /// registered by the app. Returning `Some` returns that result instead of passing the
+    /// message to `DefWindowProcW`.
+    ///
+    /// The tray icon callback message never reaches the hook. The hook runs on the thread of
+    /// the tray icon and may call back into it, a hook that panics is removed.
+    pub fn set_message_hook(&self, hook: Option<MessageHook>) {
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.set_message_hook(hook);
+        }
+    }
+
+    /// Whether the shell shows the standard tooltip when hovering the tray icon, e.g. to turn
+    /// it off for apps drawing their own hover popup. Enabled by default.
+    ///
+    /// The tooltip text is kept either way. Without a tooltip, e.g. after
+    /// `set_tooltip(None::<&str>)`, no standard tooltip is shown even when enabled.
+    pub fn set_standard_tooltip(&self, enable: bool) -> Result<()> {
+        self.platform_mut()?.set_standard_tooltip(enable)
+    }
+
+    /// Sends the icon and tooltip to the shell again.
+    ///
+    /// Setters skip the shell when nothing changed, this is for when the shell is suspected
+    /// to show stale values. Does nothing while the icon is hidden.
+    pub fn force_refresh(&self) -> Result<()> {
+        self.platform_mut()?.force_refresh()
+    }
+
+    /// Applies the changes of `update` with a single call to the shell, so they show up
+    /// together without flickering.
+    ///
+    /// Nothing is changed if the shell call fails or the tooltip is invalid, as with
+    /// [`TrayIcon::set_tooltip`]. The menu is only changed once the other changes are applied.
+    pub fn update(&self, mut update: TrayIconUpdate) -> Result<()> {
+        if let Some(Some(tooltip)) = &mut update.tooltip {
+            *tooltip = tooltip::normalize(tooltip).into_owned();
+            tooltip::validate(tooltip)?;
+        }
+        self.platform_mut()?.update(update)
     }
 
     /// Show or hide this tray icon
     pub fn set_visible(&self, visible: bool) -> Result<()> {
-        self.tray.borrow_mut().set_visible(visible)
+        self.platform_mut()?.set_visible(visible)
     }
 
-    /// Disable or enable showing the tray menu on left click.
+    /// Returns whether the tray icon is shown in the notification area.
+    ///
+    /// Showing a notification shows a hidden icon, while an icon the shell rejected, e.g.
+    /// when re-adding it after explorer restarted, is reported hidden.
+    pub fn visible(&self) -> bool {
+        self.platform().is_ok_and(|tray| tray.visible())
+    }
+
+    /// Disable or enable showing the tray menu on left click, taking effect with the next click.
+    ///
+    /// See [`TrayIconBuilder::with_menu_on_left_click`].
     pub fn set_show_menu_on_left_click(&self, enable: bool) {
-        self.tray.borrow_mut().set_show_menu_on_left_click(enable);
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.set_show_menu_on_left_click(enable);
+        }
+    }
+
+    /// Enables or disables [`TrayIconEvent::Enter`], [`TrayIconEvent::Move`] and
+    /// [`TrayIconEvent::Leave`] events of this tray icon.
+    ///
+    /// While disabled, the cursor isn't tracked at all. Disabling them while the cursor
+    /// hovers the icon doesn't send a [`TrayIconEvent::Leave`].
+    pub fn set_hover_events(&self, enable: bool) {
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.set_hover_events(enable);
+        }
+    }
+
+    /// Whether files can be dropped on the tray icon, sending [`TrayIconEvent::FilesDropped`].
+    ///
+    /// The notification area doesn't accept drops, so an invisible window is kept over the
+    /// icon while enabled. Clicks on it are handled as clicks on the icon.
+    pub fn enable_file_drop(&self, enable: bool) -> Result<()> {
+        self.platform_mut()?.enable_file_drop(enable)
+    }
+
+    /// Shows the tray menu at `position` in screen coordinates, e.g. in response to a global
+    /// hotkey. Logical positions are scaled for the monitor of the taskbar.
+    ///
+    /// Returns [`Error::NoMenu`] without a menu and [`Error::NotMainThread`] when not called
+    /// on the thread of the tray icon. Nothing happens while a tray menu is already open.
+    pub fn show_context_menu_at<P: Into<dpi::Position>>(&self, position: P) -> Result<()> {
+        // the borrow must end before the modal menu loop, which runs event handlers
+        let menu = self.platform()?.context_menu_at(position.into())?;
+        menu.show();
+        Ok(())
+    }
+
+    /// Shows the tray menu attached to the tray icon, opening away from the taskbar, e.g. in
+    /// response to [`TrayIconEvent::Select`].
+    ///
+    /// Fails like [`TrayIcon::show_context_menu_at`], and when the icon has no position.
+    pub fn show_context_menu(&self) -> Result<()> {
+        let menu = self.platform()?.context_menu()?;
+        menu.show();
+        Ok(())
+    }
+
+    /// Moves the keyboard focus to the tray icon, e.g. after UI opened from it was dismissed
+    /// with Escape.
+    ///
+    /// Fails when the tray icon is hidden, and with [`Error::NotMainThread`] when not called
+    /// on the thread of the tray icon.
+    pub fn focus(&self) -> Result<()> {
+        self.platform()?.focus()
+    }
+
+    /// Stops the tray menu from showing when it is requested, while still sending
+    /// [`TrayIconEvent::ContextMenuRequested`].
+    ///
+    /// Whether to show the menu can't be decided from the event itself, so apps that decide
+    /// per request should suppress the menu and show their own UI in response to the event.
+    pub fn set_menu_suppressed(&self, suppressed: bool) {
+        if let Ok(mut tray) = self.platform_mut() {
+            tray.set_menu_suppressed(suppressed);
+        }
     }
 
     /// Get tray icon rect.
     pub fn rect(&self) -> Option<Rect> {
-        self.tray.borrow().rect()
+        self.platform().ok()?.rect()
+    }
+
+    /// Returns whether the tray icon is hidden in the overflow flyout instead of shown on the
+    /// taskbar, e.g. to point the user at it.
+    ///
+    /// This is best effort, `None` is returned when the icon has no position or the windows
+    /// of the notification area can't be found, e.g. on future Windows versions.
+    pub fn is_in_overflow(&self) -> Option<bool> {
+        self.platform().ok()?.is_in_overflow()
+    }
+
+    /// Returns the scale factor of the monitor the tray icon is on, for converting its
+    /// [`TrayIcon::rect`] with [`Rect::to_logical`], or `None` when it has no position.
+    pub fn scale_factor(&self) -> Option<f64> {
+        self.platform().ok()?.scale_factor()
+    }
+}
+
+/// The handle is the one of [`TrayIcon::window_handle`], unavailable once removed.
+#[cfg(feature = "rwh")]
+impl raw_window_handle::HasWindowHandle for TrayIcon {
+    fn window_handle(
+        &self,
+    ) -> std::result::Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError>
+    {
+        let hwnd = std::num::NonZeroIsize::new(TrayIcon::window_handle(self))
+            .ok_or(raw_window_handle::HandleError::Unavailable)?;
+        let handle = raw_window_handle::Win32WindowHandle::new(hwnd);
+        // Safety: the window stays valid until the tray icon is removed or dropped
+        Ok(unsafe { raw_window_handle::WindowHandle::borrow_raw(handle.into()) })
     }
 }
 
 /// Describes a tray icon event.
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(feature = "serde", serde(tag = "type"))]
 #[non_exhaustive]
 pub enum TrayIconEvent {
@@ -297,117 +933,576 @@ pub enum TrayIconEvent {
         /// Id of the tray icon which triggered this event.
         id: TrayIconId,
         /// Physical Position of this event.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
         position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
         /// Position and size of the tray icon.
         rect: Rect,
         /// Mouse button that triggered this event.
         button: MouseButton,
         /// Mouse button state when this event was triggered.
         button_state: MouseButtonState,
+        /// Monitor the tray icon is shown on.
+        monitor: Monitor,
+        /// When this event happened.
+        timestamp: Timestamp,
     },
     /// A double click happened on the tray icon. **Windows Only**
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
     DoubleClick {
         /// Id of the tray icon which triggered this event.
         id: TrayIconId,
         /// Physical Position of this event.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
         position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
         /// Position and size of the tray icon.
         rect: Rect,
         /// Mouse button that triggered this event.
         button: MouseButton,
+        /// Monitor the tray icon is shown on.
+        monitor: Monitor,
+        /// When this event happened.
+        timestamp: Timestamp,
     },
     /// The mouse entered the tray icon region.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
     Enter {
         /// Id of the tray icon which triggered this event.
         id: TrayIconId,
         /// Physical Position of this event.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
         position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
         /// Position and size of the tray icon.
         rect: Rect,
+        /// When this event happened.
+        timestamp: Timestamp,
     },
     /// The mouse moved over the tray icon region.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
     Move {
         /// Id of the tray icon which triggered this event.
         id: TrayIconId,
         /// Physical Position of this event.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
         position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
         /// Position and size of the tray icon.
         rect: Rect,
+        /// When this event happened.
+        timestamp: Timestamp,
     },
     /// The mouse left the tray icon region.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
     Leave {
         /// Id of the tray icon which triggered this event.
         id: TrayIconId,
         /// Physical Position of this event.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
         position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
         /// Position and size of the tray icon.
         rect: Rect,
+        /// When this event happened.
+        timestamp: Timestamp,
     },
-}
-
-/// Describes the mouse button state.
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum MouseButtonState {
-    Up,
-    Down,
-}
-
-impl Default for MouseButtonState {
-    fn default() -> Self {
-        Self::Up
-    }
-}
-
-/// Describes which mouse button triggered the event..
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub enum MouseButton {
-    Left,
-    Right,
-    Middle,
-}
-
-impl Default for MouseButton {
-    fn default() -> Self {
-        Self::Left
-    }
-}
-
-/// Describes a rectangle including position (x - y axis) and size.
-#[derive(Debug, PartialEq, Clone, Copy)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Rect {
-    pub size: dpi::PhysicalSize<u32>,
-    pub position: dpi::PhysicalPosition<f64>,
-}
-
-impl Default for Rect {
-    fn default() -> Self {
-        Self {
-            size: dpi::PhysicalSize::new(0, 0),
-            position: dpi::PhysicalPosition::new(0., 0.),
-        }
-    }
-}
-
-/// A reciever that could be used to listen to tray events.
-pub type TrayIconEventReceiver = Receiver<TrayIconEvent>;
-type TrayIconEventHandler = Box<dyn Fn(TrayIconEvent) + Send + Sync + 'static>;
-
-static TRAY_CHANNEL: LazyLock<(Sender<TrayIconEvent>, TrayIconEventReceiver)> =
-    LazyLock::new(unbounded);
-static TRAY_EVENT_HANDLER: OnceLock<Option<TrayIconEventHandler>> = OnceLock::new();
-
-impl TrayIconEvent {
-    /// Returns the id of the tray icon which triggered this event.
-    pub fn id(&self) -> &TrayIconId {
-        match self {
-            TrayIconEvent::Click { id, .. } => id,
-            TrayIconEvent::DoubleClick { id, .. } => id,
-            TrayIconEvent::Enter { id, .. } => id,
-            TrayIconEvent::Move { id, .. } => id,
-            TrayIconEvent::Leave { id, .. } => id,
+    /// The tray icon was selected, either with a left click or with the keyboard by pressing
+    /// Enter or Space on it, e.g. after focusing the notification area with Win+B.
+    ///
+    /// Left clicks are also reported as [`TrayIconEvent::Click`].
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    Select {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Position and size of the tray icon.
+        rect: Rect,
+        /// Whether the tray icon was selected with the keyboard.
+        keyboard: bool,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// The context menu of the tray icon was requested, sent before the menu is shown.
+    ///
+    /// It is also sent when no menu is attached or the menu is suppressed with
+    /// [`TrayIcon::set_menu_suppressed`], so apps can show their own UI instead.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    ContextMenuRequested {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Physical Position the menu is shown at, the top left corner of the tray icon
+        /// for keyboard requests.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
+        position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
+        /// Position and size of the tray icon.
+        rect: Rect,
+        /// What requested the menu.
+        source: ContextMenuSource,
+        /// Monitor the tray icon is shown on.
+        monitor: Monitor,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// The mouse wheel was scrolled over the tray icon, only sent when enabled with
+    /// [`TrayIconBuilder::with_wheel_events`].
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    Wheel {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Scrolled distance in notches, positive when scrolling up and negative when scrolling down.
+        ///
+        /// High resolution wheels may scroll by fractions of a notch.
+        delta: f64,
+        /// Physical Position of this event.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
+        position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
+        /// Position and size of the tray icon.
+        rect: Rect,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// The mouse stayed over the tray icon region for the duration set with
+    /// [`TrayIconBuilder::with_hover_dwell`].
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    HoverDwell {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Physical Position of this event.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
+        position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
+        /// Position and size of the tray icon.
+        rect: Rect,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// Files were dropped on the tray icon, see [`TrayIcon::enable_file_drop`].
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    FilesDropped {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Paths of the dropped files and directories.
+        paths: Vec<PathBuf>,
+        /// Physical Position of this event.
+        #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
+        position: dpi::PhysicalPosition<f64>,
+        /// Scale factor of the monitor at `position`, to convert it to logical pixels.
+        scale_factor: f64,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// A notification balloon of the tray icon was shown.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    NotificationShown {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Id of the notification, as returned by [`TrayIcon::show_notification`].
+        notification: NotificationId,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// A notification balloon of the tray icon was clicked.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    NotificationClicked {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Id of the notification, as returned by [`TrayIcon::show_notification`].
+        notification: NotificationId,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// A notification balloon of the tray icon was removed without being clicked or timing out,
+    /// e.g. because it was replaced or the tray icon was hidden.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    NotificationDismissed {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Id of the notification, as returned by [`TrayIcon::show_notification`].
+        notification: NotificationId,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// A notification balloon of the tray icon timed out or was closed by the user.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    NotificationTimedOut {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Id of the notification, as returned by [`TrayIcon::show_notification`].
+        notification: NotificationId,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// A notification balloon of the tray icon was held back by the shell, e.g. because of
+    /// [`Notification::respect_quiet_time`] or Focus Assist.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    NotificationSuppressed {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// Id of the notification, as returned by [`TrayIcon::show_notification`].
+        notification: NotificationId,
+        /// Why the notification was suppressed.
+        reason: SuppressionReason,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// The taskbar switched between the light and dark theme.
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    ThemeChanged {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// The new theme of the taskbar.
+        theme: Theme,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+    /// The tray icon was added again after the taskbar was recreated, e.g. when Explorer
+    /// restarted. Hidden icons stay hidden, see [`TrayIcon::visible`].
+    #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+    Recreated {
+        /// Id of the tray icon which triggered this event.
+        id: TrayIconId,
+        /// When this event happened.
+        timestamp: Timestamp,
+    },
+}
+
+/// Describes the mouse button state.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MouseButtonState {
+    Up,
+    Down,
+}
+
+impl Default for MouseButtonState {
+    fn default() -> Self {
+        Self::Up
+    }
+}
+
+/// Describes which mouse button triggered the event..
+///
+/// More buttons may be added, so matches should have a fallback arm.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    /// The first X button, usually on the side of the mouse to navigate back.
+    Back,
+    /// The second X button, usually on the side of the mouse to navigate forward.
+    Forward,
+}
+
+impl Default for MouseButton {
+    fn default() -> Self {
+        Self::Left
+    }
+}
+
+/// The theme of the taskbar, see [`system_theme`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+/// Returns the theme of the taskbar, which is dark when it can't be determined.
+///
+/// Tray icons send [`TrayIconEvent::ThemeChanged`] when it changes.
+pub fn system_theme() -> Theme {
+    platform_impl::system_theme()
+}
+
+/// Describes which [`TrayIconEvent::Move`] events a tray icon sends.
+///
+/// [`TrayIconEvent::Enter`] and [`TrayIconEvent::Leave`] are always sent.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum MoveEventPolicy {
+    /// Send an event for every mouse move.
+    #[default]
+    All,
+    /// Send at most one event per interval, the final position is still sent
+    /// once the interval passed or before the cursor leaves.
+    Throttled(Duration),
+    /// Don't send any move events.
+    None,
+}
+
+/// Describes what requested the context menu of a tray icon.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ContextMenuSource {
+    /// A left click, when [`TrayIconBuilder::with_menu_on_left_click`] is enabled.
+    LeftClick,
+    /// A right click.
+    RightClick,
+    /// The menu key, Shift+F10, or Enter and Space when left clicks show the menu.
+    Keyboard,
+}
+
+/// Describes the monitor a tray icon is shown on.
+///
+/// Icons in the overflow flyout are reported on the primary monitor, where the flyout opens.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct Monitor {
+    /// Device name of the monitor, e.g. `\\.\DISPLAY1`, which identifies it while the display
+    /// configuration doesn't change. Empty if the monitor couldn't be queried.
+    pub name: String,
+    /// Position and size of the monitor.
+    pub rect: Rect,
+    /// Position and size of the monitor without the taskbar and docked app bars.
+    pub work_area: Rect,
+    /// Whether this is the primary monitor.
+    pub primary: bool,
+}
+
+/// Describes a rectangle including position (x - y axis) and size.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Rect {
+    #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalSize"))]
+    pub size: dpi::PhysicalSize<u32>,
+    #[cfg_attr(feature = "schemars", schemars(with = "schema::PhysicalPosition"))]
+    pub position: dpi::PhysicalPosition<f64>,
+}
+
+impl Default for Rect {
+    fn default() -> Self {
+        Self {
+            size: dpi::PhysicalSize::new(0, 0),
+            position: dpi::PhysicalPosition::new(0., 0.),
+        }
+    }
+}
+
+impl Rect {
+    /// Converts this rect to logical pixels for `scale_factor`, e.g. that of
+    /// [`TrayIcon::scale_factor`].
+    ///
+    /// Like winit window positions, the position is divided by the scale factor of the monitor
+    /// the rect is on, regardless of the scale factors of the monitors to its left or top.
+    pub fn to_logical(&self, scale_factor: f64) -> LogicalRect {
+        LogicalRect {
+            size: self.size.to_logical(scale_factor),
+            position: self.position.to_logical(scale_factor),
+        }
+    }
+}
+
+/// Describes a rectangle in logical pixels, see [`Rect::to_logical`].
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LogicalRect {
+    #[cfg_attr(feature = "schemars", schemars(with = "schema::LogicalSize"))]
+    pub size: dpi::LogicalSize<f64>,
+    #[cfg_attr(feature = "schemars", schemars(with = "schema::LogicalPosition"))]
+    pub position: dpi::LogicalPosition<f64>,
+}
+
+/// A reciever that could be used to listen to tray events.
+pub type TrayIconEventReceiver = Receiver<TrayIconEvent>;
+type TrayIconEventHandler = Box<dyn Fn(TrayIconEvent) + Send + Sync + 'static>;
+type TrayIconEventFilter = Box<dyn Fn(&TrayIconEvent) -> bool + Send + Sync + 'static>;
+type MessageHook = Box<dyn FnMut(u32, usize, isize) -> Option<isize>>;
+
+/// The capacity set with [`TrayIconEvent::set_channel_capacity`], fixed once the channel is used.
+static TRAY_CHANNEL_CAPACITY: OnceLock<Option<usize>> = OnceLock::new();
+static TRAY_CHANNEL: LazyLock<EventChannel> =
+    LazyLock::new(|| EventChannel::new(*TRAY_CHANNEL_CAPACITY.get_or_init(|| None)));
+static TRAY_EVENT_HANDLER: RwLock<Option<Arc<TrayIconEventHandler>>> = RwLock::new(None);
+static TRAY_EVENT_FILTER: RwLock<Option<Arc<TrayIconEventFilter>>> = RwLock::new(None);
+static TRAY_EVENT_SUBSCRIPTIONS: LazyLock<RwLock<HashMap<TrayIconId, Vec<Sender<TrayIconEvent>>>>> =
+    LazyLock::new(Default::default);
+/// Whether there are subscriptions, so events aren't looked up without any.
+static HAS_TRAY_EVENT_SUBSCRIPTIONS: AtomicBool = AtomicBool::new(false);
+static TRAY_EVENT_ID_HANDLERS: LazyLock<RwLock<HashMap<TrayIconId, Arc<TrayIconEventHandler>>>> =
+    LazyLock::new(Default::default);
+
+impl TrayIconEvent {
+    /// Returns the id of the tray icon which triggered this event.
+    pub fn id(&self) -> &TrayIconId {
+        match self {
+            TrayIconEvent::Click { id, .. } => id,
+            TrayIconEvent::DoubleClick { id, .. } => id,
+            TrayIconEvent::Enter { id, .. } => id,
+            TrayIconEvent::Move { id, .. } => id,
+            TrayIconEvent::Leave { id, .. } => id,
+            TrayIconEvent::Select { id, .. } => id,
+            TrayIconEvent::ContextMenuRequested { id, .. } => id,
+            TrayIconEvent::Wheel { id, .. } => id,
+            TrayIconEvent::HoverDwell { id, .. } => id,
+            TrayIconEvent::FilesDropped { id, .. } => id,
+            TrayIconEvent::NotificationShown { id, .. } => id,
+            TrayIconEvent::NotificationClicked { id, .. } => id,
+            TrayIconEvent::NotificationDismissed { id, .. } => id,
+            TrayIconEvent::NotificationTimedOut { id, .. } => id,
+            TrayIconEvent::NotificationSuppressed { id, .. } => id,
+            TrayIconEvent::ThemeChanged { id, .. } => id,
+            TrayIconEvent::Recreated { id, .. } => id,
+        }
+    }
+
+    /// Returns when this event happened.
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            TrayIconEvent::Click { timestamp, .. } => *timestamp,
+            TrayIconEvent::DoubleClick { timestamp, .. } => *timestamp,
+            TrayIconEvent::Enter { timestamp, .. } => *timestamp,
+            TrayIconEvent::Move { timestamp, .. } => *timestamp,
+            TrayIconEvent::Leave { timestamp, .. } => *timestamp,
+            TrayIconEvent::Select { timestamp, .. } => *timestamp,
+            TrayIconEvent::ContextMenuRequested { timestamp, .. } => *timestamp,
+            TrayIconEvent::Wheel { timestamp, .. } => *timestamp,
+            TrayIconEvent::HoverDwell { timestamp, .. } => *timestamp,
+            TrayIconEvent::FilesDropped { timestamp, .. } => *timestamp,
+            TrayIconEvent::NotificationShown { timestamp, .. } => *timestamp,
+            TrayIconEvent::NotificationClicked { timestamp, .. } => *timestamp,
+            TrayIconEvent::NotificationDismissed { timestamp, .. } => *timestamp,
+            TrayIconEvent::NotificationTimedOut { timestamp, .. } => *timestamp,
+            TrayIconEvent::NotificationSuppressed { timestamp, .. } => *timestamp,
+            TrayIconEvent::ThemeChanged { timestamp, .. } => *timestamp,
+            TrayIconEvent::Recreated { timestamp, .. } => *timestamp,
+        }
+    }
+
+    /// Returns the physical position of this event, or `None` for events without a position.
+    pub fn position(&self) -> Option<dpi::PhysicalPosition<f64>> {
+        match self {
+            TrayIconEvent::Click { position, .. }
+            | TrayIconEvent::DoubleClick { position, .. }
+            | TrayIconEvent::Enter { position, .. }
+            | TrayIconEvent::Move { position, .. }
+            | TrayIconEvent::Leave { position, .. }
+            | TrayIconEvent::ContextMenuRequested { position, .. }
+            | TrayIconEvent::Wheel { position, .. }
+            | TrayIconEvent::HoverDwell { position, .. }
+            | TrayIconEvent::FilesDropped { position, .. } => Some(*position),
+            TrayIconEvent::Select { .. }
+            | TrayIconEvent::NotificationShown { .. }
+            | TrayIconEvent::NotificationClicked { .. }
+            | TrayIconEvent::NotificationDismissed { .. }
+            | TrayIconEvent::NotificationTimedOut { .. }
+            | TrayIconEvent::NotificationSuppressed { .. }
+            | TrayIconEvent::ThemeChanged { .. }
+            | TrayIconEvent::Recreated { .. } => None,
+        }
+    }
+
+    /// Returns the position and size of the tray icon at the time of this event, or `None`
+    /// for events without one.
+    pub fn rect(&self) -> Option<Rect> {
+        match self {
+            TrayIconEvent::Click { rect, .. }
+            | TrayIconEvent::DoubleClick { rect, .. }
+            | TrayIconEvent::Enter { rect, .. }
+            | TrayIconEvent::Move { rect, .. }
+            | TrayIconEvent::Leave { rect, .. }
+            | TrayIconEvent::Select { rect, .. }
+            | TrayIconEvent::ContextMenuRequested { rect, .. }
+            | TrayIconEvent::Wheel { rect, .. }
+            | TrayIconEvent::HoverDwell { rect, .. } => Some(*rect),
+            TrayIconEvent::FilesDropped { .. }
+            | TrayIconEvent::NotificationShown { .. }
+            | TrayIconEvent::NotificationClicked { .. }
+            | TrayIconEvent::NotificationDismissed { .. }
+            | TrayIconEvent::NotificationTimedOut { .. }
+            | TrayIconEvent::NotificationSuppressed { .. }
+            | TrayIconEvent::ThemeChanged { .. }
+            | TrayIconEvent::Recreated { .. } => None,
+        }
+    }
+
+    /// Returns the mouse button of a [`TrayIconEvent::Click`] or [`TrayIconEvent::DoubleClick`].
+    pub fn button(&self) -> Option<MouseButton> {
+        match self {
+            TrayIconEvent::Click { button, .. } | TrayIconEvent::DoubleClick { button, .. } => {
+                Some(*button)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the mouse button state of a [`TrayIconEvent::Click`].
+    pub fn button_state(&self) -> Option<MouseButtonState> {
+        match self {
+            TrayIconEvent::Click { button_state, .. } => Some(*button_state),
+            _ => None,
+        }
+    }
+
+    /// Returns the position of this event in logical pixels, or `None` for events without
+    /// a position.
+    pub fn logical_position(&self) -> Option<dpi::LogicalPosition<f64>> {
+        match self {
+            TrayIconEvent::Click {
+                position,
+                scale_factor,
+                ..
+            }
+            | TrayIconEvent::DoubleClick {
+                position,
+                scale_factor,
+                ..
+            }
+            | TrayIconEvent::Enter {
+                position,
+                scale_factor,
+                ..
+            }
+            | TrayIconEvent::Move {
+                position,
+                scale_factor,
+                ..
+            }
+            | TrayIconEvent::Leave {
+                position,
+                scale_factor,
+                ..
+            }
+            | TrayIconEvent::ContextMenuRequested {
+                position,
+                scale_factor,
+                ..
+            }
+            | TrayIconEvent::Wheel {
+                position,
+                scale_factor,
+                ..
+            }
+            | TrayIconEvent::HoverDwell {
+                position,
+                scale_factor,
+                ..
+            }
+            | TrayIconEvent::FilesDropped {
+                position,
+                scale_factor,
+                ..
+            } => Some(position.to_logical(*scale_factor)),
+            _ => None,
         }
     }
 
@@ -418,7 +1513,42 @@ impl TrayIconEvent {
     ///
     /// This will not receive any events if [`TrayIconEvent::set_event_handler`] has been called with a `Some` value.
     pub fn receiver<'a>() -> &'a TrayIconEventReceiver {
-        &TRAY_CHANNEL.1
+        &TRAY_CHANNEL.receiver
+    }
+
+    /// Waits up to `timeout` for the next event of the channel associated with
+    /// [`TrayIconEvent::receiver`], returning `None` if there was none.
+    ///
+    /// ## Note
+    ///
+    /// This will not receive any events if [`TrayIconEvent::set_event_handler`] has been called with a `Some` value.
+    pub fn recv_timeout(timeout: Duration) -> Option<TrayIconEvent> {
+        TRAY_CHANNEL.receiver.recv_timeout(timeout).ok()
+    }
+
+    /// Takes all events queued in the channel associated with [`TrayIconEvent::receiver`],
+    /// without waiting for new ones.
+    ///
+    /// ## Note
+    ///
+    /// This will not receive any events if [`TrayIconEvent::set_event_handler`] has been called with a `Some` value.
+    pub fn drain() -> Vec<TrayIconEvent> {
+        TRAY_CHANNEL.receiver.try_iter().collect()
+    }
+
+    /// Limits the channel associated with [`TrayIconEvent::receiver`] to `capacity` events,
+    /// the channel is unbounded by default.
+    ///
+    /// When the channel is full, the oldest event is dropped to make room for the new one, so
+    /// an app that stops receiving for a while doesn't pile up events without limit.
+    /// A capacity of `0` is taken as `1`.
+    ///
+    /// Must be called before the channel is first used, e.g. by [`TrayIconEvent::receiver`]
+    /// or an event being sent, otherwise [`Error::EventChannelInUse`] is returned.
+    pub fn set_channel_capacity(capacity: usize) -> Result<()> {
+        TRAY_CHANNEL_CAPACITY
+            .set(Some(capacity))
+            .map_err(|_| Error::EventChannelInUse)
     }
 
     /// Set a handler to be called for new events. Useful for implementing custom event sender.
@@ -426,33 +1556,490 @@ impl TrayIconEvent {
     /// ## Note
     ///
     /// Calling this function with a `Some` value,
-    /// will not send new events to the channel associated with [`TrayIconEvent::receiver`]
+    /// will not send new events to the channel associated with [`TrayIconEvent::receiver`],
+    /// until it is called with `None` again.
+    ///
+    /// Events of tray icons with their own handler, see [`TrayIconEvent::set_event_handler_for`],
+    /// aren't passed to this handler.
+    ///
+    /// The handler replaces any handler set before, e.g. when the event loop it forwards
+    /// events to is recreated.
     pub fn set_event_handler<F: Fn(TrayIconEvent) + Send + Sync + 'static>(f: Option<F>) {
+        let handler = f.map(|f| Arc::new(Box::new(f) as TrayIconEventHandler));
+        *TRAY_EVENT_HANDLER
+            .write()
+            .unwrap_or_else(|e| e.into_inner()) = handler;
+    }
+
+    /// Set a filter deciding which events are delivered, events it returns `false` for are
+    /// discarded before reaching any handler, stream or channel.
+    ///
+    /// ## Note
+    ///
+    /// The filter is called on the thread sending the event, usually the thread running the
+    /// event loop of the tray icon, so it should return quickly and must not block.
+    ///
+    /// The filter replaces any filter set before, `None` delivers all events again.
+    pub fn set_event_filter<F: Fn(&TrayIconEvent) -> bool + Send + Sync + 'static>(f: Option<F>) {
+        let filter = f.map(|f| Arc::new(Box::new(f) as TrayIconEventFilter));
+        *TRAY_EVENT_FILTER.write().unwrap_or_else(|e| e.into_inner()) = filter;
+    }
+
+    /// Returns a stream of new events, useful in async code instead of polling
+    /// [`TrayIconEvent::receiver`].
+    ///
+    /// While the stream exists, it receives all events instead of the handler set with
+    /// [`TrayIconEvent::set_event_handler`] or the channel, except for events of tray icons
+    /// with their own handler. Only one stream may exist at a time, requesting another one
+    /// returns [`Error::EventStreamActive`].
+    ///
+    /// ```no_run
+    /// # async fn run() -> tray_icon_win::Result<()> {
+    /// use futures::StreamExt;
+    /// use tray_icon_win::TrayIconEvent;
+    ///
+    /// let mut events = TrayIconEvent::stream()?;
+    /// while let Some(event) = events.next().await {
+    ///     println!("{event:?}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn stream() -> Result<async_support::TrayIconEventStream> {
+        async_support::TrayIconEventStream::new()
+    }
+
+    /// Returns a receiver of the events of the tray icon with the given id, e.g. for one
+    /// component of an app with several tray icons.
+    ///
+    /// Events are received in addition to the handlers, stream or channel they're sent to
+    /// otherwise, except for events discarded by [`TrayIconEvent::set_event_filter`]. The
+    /// subscription ends when the receiver is dropped.
+    pub fn subscribe(id: impl Into<TrayIconId>) -> TrayIconEventReceiver {
+        let (sender, receiver) = unbounded();
+        TRAY_EVENT_SUBSCRIPTIONS
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(id.into())
+            .or_default()
+            .push(sender);
+        HAS_TRAY_EVENT_SUBSCRIPTIONS.store(true, Ordering::Release);
+        receiver
+    }
+
+    /// Sends `event` to the subscriptions of its tray icon, removing those whose receiver
+    /// was dropped.
+    fn send_to_subscriptions(event: &TrayIconEvent) {
+        let mut dropped = Vec::new();
+        if let Some(senders) = TRAY_EVENT_SUBSCRIPTIONS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(event.id())
+        {
+            for sender in senders {
+                if sender.send(event.clone()).is_err() {
+                    dropped.push(sender.clone());
+                }
+            }
+        }
+        if dropped.is_empty() {
+            return;
+        }
+
+        let mut subscriptions = TRAY_EVENT_SUBSCRIPTIONS
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(senders) = subscriptions.get_mut(event.id()) {
+            senders.retain(|sender| !dropped.iter().any(|d| d.same_channel(sender)));
+            if senders.is_empty() {
+                subscriptions.remove(event.id());
+            }
+        }
+        HAS_TRAY_EVENT_SUBSCRIPTIONS.store(!subscriptions.is_empty(), Ordering::Release);
+    }
+
+    /// Set a handler to be called for new events of the tray icon with the given id, instead of
+    /// the handler set with [`TrayIconEvent::set_event_handler`] or the channel.
+    ///
+    /// Passing `None` removes the handler of the id, its events are then sent to the global
+    /// handler or channel again. Handlers may be set and removed from any thread.
+    pub fn set_event_handler_for<F: Fn(TrayIconEvent) + Send + Sync + 'static>(
+        id: impl Into<TrayIconId>,
+        f: Option<F>,
+    ) {
+        let mut handlers = TRAY_EVENT_ID_HANDLERS
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
         if let Some(f) = f {
-            let _ = TRAY_EVENT_HANDLER.set(Some(Box::new(f)));
+            handlers.insert(id.into(), Arc::new(Box::new(f)));
         } else {
-            let _ = TRAY_EVENT_HANDLER.set(None);
+            handlers.remove(&id.into());
         }
     }
 
     #[allow(unused)]
     pub(crate) fn send(event: TrayIconEvent) {
-        if let Some(handler) = TRAY_EVENT_HANDLER.get_or_init(|| None) {
+        // the lock isn't held while calling the filter or handler, so they may change them
+        let filter = TRAY_EVENT_FILTER
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if filter.is_some_and(|filter| !filter(&event)) {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                id = event.id().as_ref(),
+                dispatch = "filtered",
+                "TrayIconEvent::send"
+            );
+            return;
+        }
+
+        if HAS_TRAY_EVENT_SUBSCRIPTIONS.load(Ordering::Acquire) {
+            TrayIconEvent::send_to_subscriptions(&event);
+        }
+
+        let handler = TRAY_EVENT_ID_HANDLERS
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(event.id())
+            .cloned();
+        if let Some(handler) = handler {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                id = event.id().as_ref(),
+                dispatch = "id_handler",
+                "TrayIconEvent::send"
+            );
+            return handler(event);
+        }
+
+        // the stream takes the event, so its id is kept for the trace
+        #[cfg(all(feature = "async", feature = "tracing"))]
+        let id = event.id().clone();
+        #[cfg(feature = "async")]
+        let Some(event) = async_support::send_tray_event(event) else {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(id = id.as_ref(), dispatch = "stream", "TrayIconEvent::send");
+            return;
+        };
+
+        let handler = TRAY_EVENT_HANDLER
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        if let Some(handler) = handler {
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                id = event.id().as_ref(),
+                dispatch = "handler",
+                "TrayIconEvent::send"
+            );
             handler(event);
         } else {
-            let _ = TRAY_CHANNEL.0.send(event);
+            #[cfg(feature = "tracing")]
+            tracing::trace!(
+                id = event.id().as_ref(),
+                dispatch = "channel",
+                "TrayIconEvent::send"
+            );
+            TRAY_CHANNEL.send(event);
+        }
+    }
+}
+
+/// The channel of [`TrayIconEvent::receiver`].
+struct EventChannel {
+    sender: Sender<TrayIconEvent>,
+    receiver: TrayIconEventReceiver,
+}
+
+impl EventChannel {
+    fn new(capacity: Option<usize>) -> Self {
+        let (sender, receiver) = match capacity {
+            Some(capacity) => bounded(capacity.max(1)),
+            None => unbounded(),
+        };
+        Self { sender, receiver }
+    }
+
+    /// Sends `event`, dropping the oldest events while the channel is full.
+    fn send(&self, mut event: TrayIconEvent) {
+        loop {
+            match self.sender.try_send(event) {
+                Ok(()) | Err(TrySendError::Disconnected(_)) => return,
+                Err(TrySendError::Full(rejected)) => {
+                    // the app may receive in the meantime, which only makes more room
+                    event = rejected;
+                    let _ = self.receiver.try_recv();
+                }
+            }
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn reports_missing_icon_file_on_build() {
+        use super::*;
+        let result = TrayIconBuilder::new()
+            .with_icon_from_path("does/not/exist.ico", None)
+            .build();
+        assert!(matches!(
+            result,
+            Err(Error::BadIcon(BadIcon::FileNotFound(_)))
+        ));
+
+        // the last icon call wins
+        let builder = TrayIconBuilder::new()
+            .with_icon_from_path("does/not/exist.ico", None)
+            .with_icon(Icon::from_rgba(vec![255; 16 * 16 * 4], 16, 16).unwrap());
+        assert!(builder.icon_path.is_none());
+        assert!(builder.attrs.icon.is_some());
+    }
+
+    #[test]
+    fn reports_long_tooltip_on_build() {
+        use super::*;
+        let result = TrayIconBuilder::new().with_tooltip("🦀".repeat(64)).build();
+        assert!(matches!(
+            result,
+            Err(Error::TooltipTooLong { len: 128, max: 127 })
+        ));
+    }
+
+    #[test]
+    fn returns_displayed_icon() {
+        use super::*;
+        let icon = Icon::from_rgba(vec![255; 16 * 16 * 4], 16, 16).unwrap();
+        let tray_icon = TrayIconBuilder::new()
+            .with_icon(icon.clone())
+            .build()
+            .unwrap();
+        let handle = |icon: Option<Icon>| icon.map(|icon| icon.inner.as_raw_handle());
+        assert_eq!(handle(tray_icon.icon()), handle(Some(icon)));
+
+        let other = Icon::from_rgba(vec![0; 16 * 16 * 4], 16, 16).unwrap();
+        tray_icon.set_icon(Some(other.clone())).unwrap();
+        assert_eq!(handle(tray_icon.icon()), handle(Some(other)));
+
+        tray_icon.set_icon(None).unwrap();
+        assert!(tray_icon.icon().is_none());
+    }
+
+    #[test]
+    fn restores_icon_after_attention() {
+        use super::*;
+        let icon = Icon::from_rgba(vec![255; 16 * 16 * 4], 16, 16).unwrap();
+        let tray_icon = TrayIconBuilder::new()
+            .with_icon(icon.clone())
+            .build()
+            .unwrap();
+        let handle = |icon: Option<Icon>| icon.map(|icon| icon.inner.as_raw_handle());
+
+        // the alternate icon, here a blank one, is shown right away
+        tray_icon
+            .set_attention(Some(AttentionConfig::default()))
+            .unwrap();
+        assert!(tray_icon.icon().is_none());
+
+        tray_icon.set_attention(None).unwrap();
+        assert_eq!(handle(tray_icon.icon()), handle(Some(icon)));
+    }
+
+    #[test]
+    fn keeps_icon_when_loading_fails() {
+        use super::*;
+        let icon = Icon::from_rgba(vec![255; 16 * 16 * 4], 16, 16).unwrap();
+        let tray_icon = TrayIconBuilder::new().with_icon(icon).build().unwrap();
+
+        let result = tray_icon.set_icon_from_path(Some("does/not/exist.ico"));
+        assert!(matches!(
+            result,
+            Err(Error::BadIcon(BadIcon::FileNotFound(_)))
+        ));
+        assert!(tray_icon.icon().is_some());
+
+        tray_icon.set_icon_from_path(None::<&str>).unwrap();
+        assert!(tray_icon.icon().is_none());
+    }
+
+    #[test]
+    fn requires_menu_to_show_it() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new().build().unwrap();
+        let result = tray_icon.show_context_menu_at(dpi::PhysicalPosition::new(0, 0));
+        assert!(matches!(result, Err(Error::NoMenu)));
+    }
+
+    #[test]
+    fn requires_visible_icon_to_focus() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new().build().unwrap();
+        tray_icon.set_visible(false).unwrap();
+        assert!(matches!(tray_icon.focus(), Err(Error::OsError(_))));
+    }
+
+    #[test]
+    fn removes_once() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new()
+            .with_tooltip("tooltip")
+            .build()
+            .unwrap();
+        let clone = tray_icon.clone();
+
+        tray_icon.remove().unwrap();
+        assert!(matches!(clone.remove(), Err(Error::Removed)));
+        assert!(matches!(
+            clone.set_tooltip(Some("other")),
+            Err(Error::Removed)
+        ));
+        assert!(!clone.visible());
+        assert_eq!(clone.tooltip(), None);
+    }
+
+    #[test]
+    fn rejects_duplicate_ids() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new().with_id("duplicate").build().unwrap();
+        assert!(matches!(
+            TrayIconBuilder::new().with_id("duplicate").build(),
+            Err(Error::DuplicateId(id)) if id == "duplicate"
+        ));
+
+        let duplicate = TrayIconBuilder::new()
+            .with_id("duplicate")
+            .allow_duplicate_ids()
+            .build()
+            .unwrap();
+        tray_icon.remove().unwrap();
+        drop(duplicate);
+        assert!(TrayIconBuilder::new().with_id("duplicate").build().is_ok());
+    }
+
+    #[test]
+    fn builds_hidden_icon() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new()
+            .with_visible(false)
+            .with_tooltip("tooltip")
+            .build()
+            .unwrap();
+        assert!(!tray_icon.visible());
+        assert_eq!(tray_icon.rect(), None);
+
+        tray_icon.set_tooltip(Some("shown")).unwrap();
+        tray_icon.set_visible(true).unwrap();
+        assert!(tray_icon.visible());
+        assert_eq!(tray_icon.tooltip().as_deref(), Some("shown"));
+    }
+
+    #[test]
+    fn exposes_window_handle() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new().build().unwrap();
+        assert_ne!(tray_icon.window_handle(), 0);
+
+        tray_icon.remove().unwrap();
+        assert_eq!(tray_icon.window_handle(), 0);
+    }
+
+    #[test]
+    fn tracks_visibility() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new().build().unwrap();
+        assert!(tray_icon.visible());
+
+        tray_icon.set_visible(false).unwrap();
+        assert!(!tray_icon.visible());
+        // hiding a hidden icon and showing a shown one don't change anything
+        tray_icon.set_visible(false).unwrap();
+        assert!(!tray_icon.visible());
+        tray_icon.set_visible(true).unwrap();
+        assert!(tray_icon.visible());
+        tray_icon.set_visible(true).unwrap();
+        assert!(tray_icon.visible());
+        tray_icon.set_visible(false).unwrap();
+        assert!(!tray_icon.visible());
+    }
+
+    #[test]
+    fn updates_attributes_at_once() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new().with_tooltip("idle").build().unwrap();
+
+        tray_icon
+            .update(TrayIconUpdate::new().tooltip(Some("busy")).visible(false))
+            .unwrap();
+        assert_eq!(tray_icon.tooltip().as_deref(), Some("busy"));
+        assert!(!tray_icon.visible());
+
+        // an invalid tooltip fails the whole update
+        let result = tray_icon.update(
+            TrayIconUpdate::new()
+                .tooltip(Some("a".repeat(128)))
+                .visible(true),
+        );
+        assert!(matches!(result, Err(Error::TooltipTooLong { .. })));
+        assert_eq!(tray_icon.tooltip().as_deref(), Some("busy"));
+        assert!(!tray_icon.visible());
+
+        tray_icon
+            .update(TrayIconUpdate::new().tooltip(None::<&str>).visible(true))
+            .unwrap();
+        assert_eq!(tray_icon.tooltip(), None);
+        assert!(tray_icon.visible());
+    }
+
+    #[test]
+    fn remembers_tooltip() {
+        use super::*;
+        let tray_icon = TrayIconBuilder::new()
+            .with_tooltip("first")
+            .build()
+            .unwrap();
+        assert_eq!(tray_icon.tooltip().as_deref(), Some("first"));
+
+        tray_icon.set_tooltip(Some("second")).unwrap();
+        assert_eq!(tray_icon.tooltip().as_deref(), Some("second"));
+
+        // a rejected tooltip leaves the current one in place
+        assert!(tray_icon.set_tooltip(Some("🦀".repeat(64))).is_err());
+        assert_eq!(tray_icon.tooltip().as_deref(), Some("second"));
+
+        tray_icon.set_tooltip(None::<&str>).unwrap();
+        assert_eq!(tray_icon.tooltip(), None);
+
+        tray_icon.set_tooltip_lossy(Some("🦀".repeat(64))).unwrap();
+        assert_eq!(tray_icon.tooltip(), Some("🦀".repeat(63)));
+    }
+
+    #[test]
+    fn shows_standard_tooltip_by_default() {
+        use super::*;
+        assert!(TrayIconAttributes::default().standard_tooltip);
+        let builder = TrayIconBuilder::new().with_standard_tooltip(false);
+        assert!(!builder.attrs.standard_tooltip);
+    }
+
+    #[test]
+    fn disables_hover_dwell_by_default() {
+        use super::*;
+        assert_eq!(TrayIconAttributes::default().hover_dwell, None);
+        let builder = TrayIconBuilder::new().with_hover_dwell(Duration::from_millis(700));
+        assert_eq!(builder.attrs.hover_dwell, Some(Duration::from_millis(700)));
+    }
 
     #[cfg(feature = "serde")]
     #[test]
     fn it_serializes() {
         use super::*;
         let event = TrayIconEvent::Click {
+            timestamp: Timestamp::default(),
+            scale_factor: 1.0,
+            monitor: Monitor::default(),
             button: MouseButton::Left,
             button_state: MouseButtonState::Down,
             id: TrayIconId::new("id"),
@@ -467,7 +2054,12 @@ mod tests {
                 "type": "Click",
                 "button": "Left",
                 "buttonState": "Down",
+                "scaleFactor": 1.0,
                 "id": "id",
+                "timestamp": {
+                    "time": 0,
+                    "tick": 0,
+                },
                 "position": {
                     "x": 0.0,
                     "y": 0.0,
@@ -481,8 +2073,639 @@ mod tests {
                         "x": 0.0,
                         "y": 0.0,
                     },
-                }
+                },
+                "monitor": {
+                    "name": "",
+                    "rect": {
+                        "size": {
+                            "width": 0,
+                            "height": 0,
+                        },
+                        "position": {
+                            "x": 0.0,
+                            "y": 0.0,
+                        },
+                    },
+                    "workArea": {
+                        "size": {
+                            "width": 0,
+                            "height": 0,
+                        },
+                        "position": {
+                            "x": 0.0,
+                            "y": 0.0,
+                        },
+                    },
+                    "primary": false,
+                },
             })
-        )
+        );
+
+        // multi-word fields are camel case in every variant
+        let event = TrayIconEvent::DoubleClick {
+            timestamp: Timestamp::default(),
+            scale_factor: 1.0,
+            monitor: Monitor::default(),
+            button: MouseButton::Left,
+            id: TrayIconId::new("id"),
+            position: dpi::PhysicalPosition::default(),
+            rect: Rect::default(),
+        };
+        let value = serde_jsonc2::to_value(&event).unwrap();
+        assert_eq!(value["type"], "DoubleClick");
+        assert_eq!(value["scaleFactor"], 1.0);
+        assert!(value.get("scale_factor").is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_notification_events() {
+        use super::*;
+        let event = TrayIconEvent::NotificationClicked {
+            timestamp: Timestamp::default(),
+            id: TrayIconId::new("id"),
+            notification: NotificationId(1),
+        };
+
+        let value = serde_jsonc2::to_value(&event).unwrap();
+        assert_eq!(
+            value,
+            serde_jsonc2::jsonc!({
+                "type": "NotificationClicked",
+                "id": "id",
+                "timestamp": {
+                    "time": 0,
+                    "tick": 0,
+                },
+                "notification": 1,
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_select_events() {
+        use super::*;
+        let event = TrayIconEvent::Select {
+            timestamp: Timestamp::default(),
+            id: TrayIconId::new("id"),
+            rect: Rect::default(),
+            keyboard: true,
+        };
+
+        let value = serde_jsonc2::to_value(&event).unwrap();
+        assert_eq!(value["type"], "Select");
+        assert_eq!(value["keyboard"], true);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_context_menu_events() {
+        use super::*;
+        let event = TrayIconEvent::ContextMenuRequested {
+            timestamp: Timestamp::default(),
+            scale_factor: 1.0,
+            monitor: Monitor::default(),
+            id: TrayIconId::new("id"),
+            position: dpi::PhysicalPosition::default(),
+            rect: Rect::default(),
+            source: ContextMenuSource::Keyboard,
+        };
+
+        let value = serde_jsonc2::to_value(&event).unwrap();
+        assert_eq!(value["type"], "ContextMenuRequested");
+        assert_eq!(value["source"], "Keyboard");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_wheel_events() {
+        use super::*;
+        let event = TrayIconEvent::Wheel {
+            timestamp: Timestamp::default(),
+            scale_factor: 1.0,
+            id: TrayIconId::new("id"),
+            delta: -1.5,
+            position: dpi::PhysicalPosition::default(),
+            rect: Rect::default(),
+        };
+
+        let value = serde_jsonc2::to_value(&event).unwrap();
+        assert_eq!(value["type"], "Wheel");
+        assert_eq!(value["delta"], -1.5);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_dropped_files() {
+        use super::*;
+        let event = TrayIconEvent::FilesDropped {
+            id: TrayIconId::new("id"),
+            paths: vec![PathBuf::from(r"C:\report.pdf")],
+            position: dpi::PhysicalPosition::default(),
+            scale_factor: 1.0,
+            timestamp: Timestamp::default(),
+        };
+
+        let value = serde_jsonc2::to_value(&event).unwrap();
+        assert_eq!(value["type"], "FilesDropped");
+        assert_eq!(value["paths"][0], r"C:\report.pdf");
+    }
+
+    /// Held by tests that send events, since the handlers are global.
+    static EVENTS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn leave_event(id: &str) -> super::TrayIconEvent {
+        use super::*;
+        TrayIconEvent::Leave {
+            id: TrayIconId::new(id),
+            position: dpi::PhysicalPosition::default(),
+            scale_factor: 1.0,
+            rect: Rect::default(),
+            timestamp: Timestamp::default(),
+        }
+    }
+
+    #[test]
+    fn dispatches_events_by_id() {
+        use super::*;
+        use std::sync::Mutex;
+        let _events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+
+        static GLOBAL: Mutex<Vec<TrayIconId>> = Mutex::new(Vec::new());
+        static PLUGIN: Mutex<Vec<TrayIconId>> = Mutex::new(Vec::new());
+
+        TrayIconEvent::set_event_handler(Some(|e: TrayIconEvent| {
+            GLOBAL.lock().unwrap().push(e.id().clone())
+        }));
+        TrayIconEvent::set_event_handler_for(
+            "plugin",
+            Some(|e: TrayIconEvent| PLUGIN.lock().unwrap().push(e.id().clone())),
+        );
+
+        let event = leave_event;
+        TrayIconEvent::send(event("plugin"));
+        TrayIconEvent::send(event("other"));
+        assert_eq!(*PLUGIN.lock().unwrap(), [TrayIconId::new("plugin")]);
+        assert_eq!(*GLOBAL.lock().unwrap(), [TrayIconId::new("other")]);
+
+        // once removed, events fall through to the global handler right away
+        TrayIconEvent::set_event_handler_for("plugin", None::<fn(TrayIconEvent)>);
+        TrayIconEvent::send(event("plugin"));
+        assert_eq!(PLUGIN.lock().unwrap().len(), 1);
+        assert_eq!(
+            *GLOBAL.lock().unwrap(),
+            [TrayIconId::new("other"), TrayIconId::new("plugin")]
+        );
+
+        TrayIconEvent::set_event_handler(None::<fn(TrayIconEvent)>);
+    }
+
+    #[test]
+    fn clearing_event_handler_restores_channel() {
+        use super::*;
+        let _events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+
+        TrayIconEvent::set_event_handler(Some(|_| {}));
+        TrayIconEvent::send(leave_event("handled"));
+        TrayIconEvent::set_event_handler(None::<fn(TrayIconEvent)>);
+        TrayIconEvent::send(leave_event("cleared"));
+
+        let ids: Vec<_> = TrayIconEvent::receiver()
+            .try_iter()
+            .map(|e| e.id().clone())
+            .collect();
+        assert!(ids.contains(&TrayIconId::new("cleared")));
+        assert!(!ids.contains(&TrayIconId::new("handled")));
+    }
+
+    #[test]
+    fn filters_events() {
+        use super::*;
+        let _events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+
+        TrayIconEvent::drain();
+        TrayIconEvent::set_event_filter(Some(|event: &TrayIconEvent| {
+            event.id() != &TrayIconId::new("filtered")
+        }));
+        TrayIconEvent::send(leave_event("filtered"));
+        TrayIconEvent::send(leave_event("kept"));
+        TrayIconEvent::set_event_filter(None::<fn(&TrayIconEvent) -> bool>);
+        TrayIconEvent::send(leave_event("filtered"));
+
+        let ids: Vec<_> = TrayIconEvent::drain()
+            .iter()
+            .map(|e| e.id().clone())
+            .collect();
+        assert_eq!(ids, [TrayIconId::new("kept"), TrayIconId::new("filtered")]);
+    }
+
+    #[test]
+    fn subscribes_to_events_by_id() {
+        use super::*;
+        let _events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+
+        TrayIconEvent::drain();
+        let subscription = TrayIconEvent::subscribe("subscribed");
+        TrayIconEvent::send(leave_event("subscribed"));
+        TrayIconEvent::send(leave_event("other"));
+
+        let ids: Vec<_> = subscription.try_iter().map(|e| e.id().clone()).collect();
+        assert_eq!(ids, [TrayIconId::new("subscribed")]);
+        // the events are still sent to the channel
+        assert_eq!(TrayIconEvent::drain().len(), 2);
+
+        drop(subscription);
+        TrayIconEvent::send(leave_event("subscribed"));
+        assert!(!TRAY_EVENT_SUBSCRIPTIONS
+            .read()
+            .unwrap()
+            .contains_key(&TrayIconId::new("subscribed")));
+        TrayIconEvent::drain();
+    }
+
+    #[test]
+    fn drains_channel() {
+        use super::*;
+        let _events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+
+        TrayIconEvent::drain();
+        TrayIconEvent::send(leave_event("first"));
+        TrayIconEvent::send(leave_event("second"));
+
+        let ids: Vec<_> = TrayIconEvent::drain()
+            .iter()
+            .map(|e| e.id().clone())
+            .collect();
+        assert_eq!(ids, [TrayIconId::new("first"), TrayIconId::new("second")]);
+        assert!(TrayIconEvent::recv_timeout(Duration::from_millis(10)).is_none());
+
+        TrayIconEvent::send(leave_event("third"));
+        let event = TrayIconEvent::recv_timeout(Duration::from_millis(10)).unwrap();
+        assert_eq!(event.id(), &TrayIconId::new("third"));
+    }
+
+    #[test]
+    fn replaces_event_handler() {
+        use super::*;
+        use std::sync::Mutex;
+        let _events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+
+        static FIRST: Mutex<Vec<TrayIconId>> = Mutex::new(Vec::new());
+        static SECOND: Mutex<Vec<TrayIconId>> = Mutex::new(Vec::new());
+
+        TrayIconEvent::set_event_handler(Some(|e: TrayIconEvent| {
+            FIRST.lock().unwrap().push(e.id().clone())
+        }));
+        TrayIconEvent::send(leave_event("first"));
+        TrayIconEvent::set_event_handler(Some(|e: TrayIconEvent| {
+            SECOND.lock().unwrap().push(e.id().clone())
+        }));
+        TrayIconEvent::send(leave_event("second"));
+        TrayIconEvent::send(leave_event("third"));
+
+        assert_eq!(*FIRST.lock().unwrap(), [TrayIconId::new("first")]);
+        assert_eq!(
+            *SECOND.lock().unwrap(),
+            [TrayIconId::new("second"), TrayIconId::new("third")]
+        );
+        TrayIconEvent::set_event_handler(None::<fn(TrayIconEvent)>);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn streams_events() {
+        use super::*;
+        use futures::{FutureExt, StreamExt};
+        let _events = EVENTS.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut stream = TrayIconEvent::stream().unwrap();
+        assert!(matches!(
+            TrayIconEvent::stream(),
+            Err(Error::EventStreamActive)
+        ));
+
+        assert!(stream.next().now_or_never().is_none());
+        TrayIconEvent::send(leave_event("streamed"));
+        let event = stream.next().now_or_never().flatten().unwrap();
+        assert_eq!(event.id().as_ref(), "streamed");
+
+        // events go to the handler or channel again once the stream is dropped
+        drop(stream);
+        assert!(async_support::send_tray_event(leave_event("unstreamed")).is_some());
+        assert!(TrayIconEvent::stream().is_ok());
+    }
+
+    #[test]
+    fn converts_to_logical_position() {
+        use super::*;
+        // on a secondary monitor scaled to 175% right of a 1920 pixels wide one at 100%
+        let event = TrayIconEvent::Move {
+            id: TrayIconId::new("id"),
+            position: dpi::PhysicalPosition::new(1920. + 1750., 875.),
+            scale_factor: 1.75,
+            rect: Rect::default(),
+            timestamp: Timestamp::default(),
+        };
+        let position = event.logical_position().unwrap();
+        assert_eq!((position.x, position.y), ((1920. + 1750.) / 1.75, 500.));
+
+        let event = TrayIconEvent::Select {
+            id: TrayIconId::new("id"),
+            rect: Rect::default(),
+            keyboard: true,
+            timestamp: Timestamp::default(),
+        };
+        assert!(event.logical_position().is_none());
+    }
+
+    #[test]
+    fn converts_rect_to_logical() {
+        use super::*;
+        let rect = Rect {
+            size: dpi::PhysicalSize::new(36, 48),
+            position: dpi::PhysicalPosition::new(1500., 1032.),
+        };
+        let logical = rect.to_logical(1.5);
+        assert_eq!(logical.size, dpi::LogicalSize::new(24., 32.));
+        assert_eq!(logical.position, dpi::LogicalPosition::new(1000., 688.));
+        assert_eq!(
+            rect.to_logical(1.),
+            LogicalRect {
+                size: dpi::LogicalSize::new(36., 48.),
+                position: dpi::LogicalPosition::new(1500., 1032.),
+            }
+        );
+
+        // on a secondary monitor at 200% right of a 1920 pixels wide one at 100%, the position
+        // is divided by the secondary monitor's scale factor only
+        let rect = Rect {
+            size: dpi::PhysicalSize::new(48, 48),
+            position: dpi::PhysicalPosition::new(1920. + 3000., 2100.),
+        };
+        let logical = rect.to_logical(2.);
+        assert_eq!(logical.position, dpi::LogicalPosition::new(2460., 1050.));
+        assert_eq!(logical.size, dpi::LogicalSize::new(24., 24.));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn it_serializes_x_buttons() {
+        use super::*;
+        for (button, name) in [
+            (MouseButton::Back, "Back"),
+            (MouseButton::Forward, "Forward"),
+        ] {
+            assert_eq!(serde_jsonc2::to_value(button).unwrap(), name);
+        }
+    }
+
+    #[test]
+    fn bounds_event_channel() {
+        use super::*;
+        let channel = EventChannel::new(Some(100));
+        for i in 0..1_000_000 {
+            channel.send(TrayIconEvent::Move {
+                id: TrayIconId::new("id"),
+                position: dpi::PhysicalPosition::new(i as f64, 0.),
+                scale_factor: 1.0,
+                rect: Rect::default(),
+                timestamp: Timestamp::default(),
+            });
+        }
+        assert_eq!(channel.receiver.len(), 100);
+
+        // the oldest events were dropped
+        let Ok(TrayIconEvent::Move { position, .. }) = channel.receiver.try_recv() else {
+            panic!("expected a move");
+        };
+        assert_eq!(position.x, 999_900.);
+    }
+
+    #[test]
+    fn rejects_channel_capacity_once_used() {
+        use super::*;
+        let _ = TrayIconEvent::receiver();
+        assert!(matches!(
+            TrayIconEvent::set_channel_capacity(10),
+            Err(Error::EventChannelInUse)
+        ));
+    }
+
+    /// Returns one event of every variant.
+    fn all_events() -> Vec<super::TrayIconEvent> {
+        use super::*;
+        let id = TrayIconId::new("id");
+        let position = dpi::PhysicalPosition::new(1., 2.);
+        let rect = Rect {
+            position: dpi::PhysicalPosition::new(3., 4.),
+            size: dpi::PhysicalSize::new(5, 6),
+        };
+        let timestamp = Timestamp::default();
+        let notification = NotificationId(1);
+        vec![
+            TrayIconEvent::Click {
+                id: id.clone(),
+                position,
+                scale_factor: 1.0,
+                rect,
+                button: MouseButton::Right,
+                button_state: MouseButtonState::Up,
+                monitor: Monitor::default(),
+                timestamp,
+            },
+            TrayIconEvent::DoubleClick {
+                id: id.clone(),
+                position,
+                scale_factor: 1.0,
+                rect,
+                button: MouseButton::Middle,
+                monitor: Monitor::default(),
+                timestamp,
+            },
+            TrayIconEvent::Enter {
+                id: id.clone(),
+                position,
+                scale_factor: 1.0,
+                rect,
+                timestamp,
+            },
+            TrayIconEvent::Move {
+                id: id.clone(),
+                position,
+                scale_factor: 1.0,
+                rect,
+                timestamp,
+            },
+            TrayIconEvent::Leave {
+                id: id.clone(),
+                position,
+                scale_factor: 1.0,
+                rect,
+                timestamp,
+            },
+            TrayIconEvent::Select {
+                id: id.clone(),
+                rect,
+                keyboard: false,
+                timestamp,
+            },
+            TrayIconEvent::ContextMenuRequested {
+                id: id.clone(),
+                position,
+                scale_factor: 1.0,
+                rect,
+                source: ContextMenuSource::RightClick,
+                monitor: Monitor::default(),
+                timestamp,
+            },
+            TrayIconEvent::Wheel {
+                id: id.clone(),
+                delta: 1.,
+                position,
+                scale_factor: 1.0,
+                rect,
+                timestamp,
+            },
+            TrayIconEvent::HoverDwell {
+                id: id.clone(),
+                position,
+                scale_factor: 1.0,
+                rect,
+                timestamp,
+            },
+            TrayIconEvent::FilesDropped {
+                id: id.clone(),
+                paths: Vec::new(),
+                position,
+                scale_factor: 1.0,
+                timestamp,
+            },
+            TrayIconEvent::NotificationShown {
+                id: id.clone(),
+                notification,
+                timestamp,
+            },
+            TrayIconEvent::NotificationClicked {
+                id: id.clone(),
+                notification,
+                timestamp,
+            },
+            TrayIconEvent::NotificationDismissed {
+                id: id.clone(),
+                notification,
+                timestamp,
+            },
+            TrayIconEvent::NotificationTimedOut {
+                id: id.clone(),
+                notification,
+                timestamp,
+            },
+            TrayIconEvent::NotificationSuppressed {
+                id: id.clone(),
+                notification,
+                reason: SuppressionReason::NotShown,
+                timestamp,
+            },
+            TrayIconEvent::ThemeChanged {
+                id: id.clone(),
+                theme: Theme::Dark,
+                timestamp,
+            },
+            TrayIconEvent::Recreated { id, timestamp },
+        ]
+    }
+
+    #[test]
+    fn accesses_event_fields() {
+        use super::*;
+        for event in all_events() {
+            let has_position = !matches!(
+                event,
+                TrayIconEvent::Select { .. }
+                    | TrayIconEvent::NotificationShown { .. }
+                    | TrayIconEvent::NotificationClicked { .. }
+                    | TrayIconEvent::NotificationDismissed { .. }
+                    | TrayIconEvent::NotificationTimedOut { .. }
+                    | TrayIconEvent::NotificationSuppressed { .. }
+                    | TrayIconEvent::ThemeChanged { .. }
+                    | TrayIconEvent::Recreated { .. }
+            );
+            let has_rect = !matches!(
+                event,
+                TrayIconEvent::FilesDropped { .. }
+                    | TrayIconEvent::NotificationShown { .. }
+                    | TrayIconEvent::NotificationClicked { .. }
+                    | TrayIconEvent::NotificationDismissed { .. }
+                    | TrayIconEvent::NotificationTimedOut { .. }
+                    | TrayIconEvent::NotificationSuppressed { .. }
+                    | TrayIconEvent::ThemeChanged { .. }
+                    | TrayIconEvent::Recreated { .. }
+            );
+            let (button, button_state) = match event {
+                TrayIconEvent::Click { .. } => {
+                    (Some(MouseButton::Right), Some(MouseButtonState::Up))
+                }
+                TrayIconEvent::DoubleClick { .. } => (Some(MouseButton::Middle), None),
+                _ => (None, None),
+            };
+
+            assert_eq!(event.id(), &TrayIconId::new("id"), "{event:?}");
+            assert_eq!(
+                event.position(),
+                has_position.then(|| dpi::PhysicalPosition::new(1., 2.)),
+                "{event:?}"
+            );
+            assert_eq!(
+                event.rect().map(|rect| (rect.position.x, rect.size.width)),
+                has_rect.then_some((3., 5)),
+                "{event:?}"
+            );
+            assert_eq!(event.button(), button, "{event:?}");
+            assert_eq!(event.button_state(), button_state, "{event:?}");
+        }
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn matches_json_schema() {
+        use super::*;
+        let schema = serde_json::to_value(schemars::schema_for!(TrayIconEvent)).unwrap();
+        let schema = jsonschema::JSONSchema::compile(&schema).unwrap();
+
+        for event in all_events() {
+            let value = serde_json::to_value(&event).unwrap();
+            assert!(schema.is_valid(&value), "{value}");
+        }
+        // a click event as serialized in `it_serializes`
+        let click = serde_json::json!({
+            "type": "Click",
+            "id": "id",
+            "position": { "x": 0.0, "y": 0.0 },
+            "scaleFactor": 1.0,
+            "rect": {
+                "size": { "width": 0, "height": 0 },
+                "position": { "x": 0.0, "y": 0.0 },
+            },
+            "button": "Left",
+            "buttonState": "Down",
+            "monitor": {
+                "name": "",
+                "rect": {
+                    "size": { "width": 0, "height": 0 },
+                    "position": { "x": 0.0, "y": 0.0 },
+                },
+                "workArea": {
+                    "size": { "width": 0, "height": 0 },
+                    "position": { "x": 0.0, "y": 0.0 },
+                },
+                "primary": false,
+            },
+            "timestamp": { "time": 0, "tick": 0 },
+        });
+        assert!(schema.is_valid(&click));
+        let mut invalid = click;
+        invalid["button"] = "Sideways".into();
+        assert!(!schema.is_valid(&invalid));
     }
 }
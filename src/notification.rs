@@ -0,0 +1,236 @@
+use crate::Icon;
+
+/// The maximum length of a notification title, in UTF-16 code units.
+pub(crate) const MAX_TITLE_LEN: usize = 63;
+/// The maximum length of a notification body, in UTF-16 code units.
+pub(crate) const MAX_BODY_LEN: usize = 255;
+
+/// An unique id of a notification, returned by [`TrayIcon::show_notification`](crate::TrayIcon::show_notification)
+/// and included in its events.
+///
+/// Ids increase monotonically and are unique within the process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct NotificationId(pub u32);
+
+/// Whether the user is currently able to receive notifications, see [`user_notification_state`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub enum UserNotificationState {
+    /// The screen saver is running, the machine is locked or a fast user switch is in progress.
+    NotPresent,
+    /// A full-screen application is running or presentation settings are applied.
+    Busy,
+    /// A full-screen Direct3D application is running.
+    RunningD3DFullScreen,
+    /// The user turned on presentation mode.
+    PresentationMode,
+    /// Notifications can be freely shown.
+    AcceptsNotifications,
+    /// The user is in quiet time, the first hour after a new user logs in.
+    QuietTime,
+    /// A full-screen Windows Store app is running.
+    App,
+}
+
+/// Why a notification was reported with [`TrayIconEvent::NotificationSuppressed`](crate::TrayIconEvent::NotificationSuppressed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub enum SuppressionReason {
+    /// The user wasn't accepting notifications when it was shown, e.g. because of a full-screen
+    /// application. The shell may still show it later.
+    UserState(UserNotificationState),
+    /// The shell didn't show the balloon within a few seconds, e.g. because of quiet time
+    /// or Focus Assist.
+    NotShown,
+}
+
+/// Returns whether the user is currently able to receive notifications, e.g. to flash
+/// the tray icon instead of showing a notification that would be held back.
+pub fn user_notification_state() -> crate::Result<UserNotificationState> {
+    crate::platform_impl::user_notification_state()
+}
+
+/// The icon shown next to the title of a notification balloon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotificationKind {
+    /// No icon.
+    #[default]
+    None,
+    /// An information icon.
+    Info,
+    /// A warning icon.
+    Warning,
+    /// An error icon.
+    Error,
+    /// The tray icon itself.
+    User,
+}
+
+/// What happens when a notification is shown while the queue enabled with
+/// [`TrayIcon::set_notification_queueing`](crate::TrayIcon::set_notification_queueing) is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotificationOverflow {
+    /// The oldest queued notification is dropped to make room.
+    #[default]
+    DropOldest,
+    /// The new notification is rejected with [`Error::NotificationQueueFull`](crate::Error::NotificationQueueFull).
+    Reject,
+}
+
+/// A notification balloon shown with [`TrayIcon::show_notification_with`](crate::TrayIcon::show_notification_with).
+///
+/// ## Example
+///
+/// ```no_run
+/// # let tray_icon: tray_icon_win::TrayIcon = unimplemented!();
+/// use tray_icon_win::{Notification, NotificationKind};
+///
+/// let notification = Notification::new()
+///     .title("Download finished")
+///     .body("report.pdf was saved to Downloads")
+///     .kind(NotificationKind::Info);
+/// tray_icon.show_notification_with(notification).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Notification {
+    pub(crate) title: String,
+    pub(crate) body: String,
+    pub(crate) kind: NotificationKind,
+    pub(crate) silent: bool,
+    pub(crate) large_icon: bool,
+    pub(crate) respect_quiet_time: bool,
+    pub(crate) realtime: bool,
+    pub(crate) icon: Option<Icon>,
+}
+
+impl Notification {
+    /// Creates a new empty [`Notification`] without an icon.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the title, at most 63 UTF-16 code units long.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the body, at most 255 UTF-16 code units long.
+    pub fn body<S: Into<String>>(mut self, body: S) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Sets the icon shown next to the title.
+    pub fn kind(mut self, kind: NotificationKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// Whether to show the notification without playing a sound, default is `false`.
+    pub fn silent(mut self, silent: bool) -> Self {
+        self.silent = silent;
+        self
+    }
+
+    /// Sets a custom icon shown next to the title instead of the tray icon, e.g. an alert
+    /// glyph for errors. The tray icon itself is left untouched.
+    ///
+    /// The icon takes precedence over [`Notification::kind`], and is kept alive by the tray
+    /// icon until the next notification is shown, so it outlives the balloon.
+    pub fn icon(mut self, icon: Icon) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Whether to show the icon of a [`NotificationKind::User`] notification or the one set with
+    /// [`Notification::icon`] at the large icon size, default is `false`.
+    ///
+    /// For the tray icon, the icon closest to that size is picked when it uses an
+    /// [`IconSet`](crate::IconSet), other icons are upscaled. Large icons require a shell
+    /// supporting `NOTIFYICON_VERSION_4` (Windows Vista and later), older shells show the
+    /// small icon.
+    pub fn large_icon(mut self, large_icon: bool) -> Self {
+        self.large_icon = large_icon;
+        self
+    }
+
+    /// Whether the shell should hold the notification back during the user's quiet time,
+    /// the first hour after a new user logs in, default is `false`. Use it for notifications
+    /// that aren't urgent.
+    ///
+    /// A notification that isn't shown emits [`TrayIconEvent::NotificationSuppressed`](crate::TrayIconEvent::NotificationSuppressed).
+    /// Quiet time requires a shell supporting `NOTIFYICON_VERSION_4` (Windows 7 and later),
+    /// older shells ignore it.
+    pub fn respect_quiet_time(mut self, respect_quiet_time: bool) -> Self {
+        self.respect_quiet_time = respect_quiet_time;
+        self
+    }
+
+    /// Whether the shell should discard the notification if it can't be shown immediately,
+    /// e.g. because the user is away, instead of showing it later. Default is `false`.
+    ///
+    /// Realtime notifications skip the queue enabled with
+    /// [`TrayIcon::set_notification_queueing`](crate::TrayIcon::set_notification_queueing)
+    /// and replace the shown balloon.
+    pub fn realtime(mut self, realtime: bool) -> Self {
+        self.realtime = realtime;
+        self
+    }
+
+    /// Returns whether the balloon shows the tray icon or the custom icon.
+    pub(crate) fn has_user_icon(&self) -> bool {
+        self.icon.is_some() || self.kind == NotificationKind::User
+    }
+
+    /// Checks that the title and body fit into a notification balloon, which would
+    /// otherwise truncate them.
+    ///
+    /// [`TrayIcon::show_notification_with`](crate::TrayIcon::show_notification_with)
+    /// calls this before showing the notification.
+    pub fn validate(&self) -> crate::Result<()> {
+        let len = self.title.encode_utf16().count();
+        if len > MAX_TITLE_LEN {
+            return Err(crate::Error::NotificationTitleTooLong { len });
+        }
+        let len = self.body.encode_utf16().count();
+        if len > MAX_BODY_LEN {
+            return Err(crate::Error::NotificationBodyTooLong { len });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(title: &str, body: &str) -> crate::Result<()> {
+        Notification::new().title(title).body(body).validate()
+    }
+
+    #[test]
+    fn validates_lengths() {
+        assert!(validate(&"a".repeat(MAX_TITLE_LEN), &"a".repeat(MAX_BODY_LEN)).is_ok());
+        assert!(matches!(
+            validate(&"a".repeat(MAX_TITLE_LEN + 1), ""),
+            Err(crate::Error::NotificationTitleTooLong { len: 64 })
+        ));
+        assert!(matches!(
+            validate("", &"a".repeat(MAX_BODY_LEN + 1)),
+            Err(crate::Error::NotificationBodyTooLong { len: 256 })
+        ));
+        // lengths are counted in UTF-16 code units, not bytes or chars
+        assert!(validate(&"é".repeat(MAX_TITLE_LEN), "").is_ok());
+        assert!(matches!(
+            validate(&"🦀".repeat(32), ""),
+            Err(crate::Error::NotificationTitleTooLong { len: 64 })
+        ));
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{MouseButton, MouseButtonState, TrayIconEvent};
+
+/// Holds back single clicks until the double click time passed, so a double click
+/// results in a [`TrayIconEvent::DoubleClick`] without the clicks preceding it.
+#[derive(Default)]
+pub struct DoubleClickFilter {
+    /// The button down and up of a click that may still become a double click.
+    held: Vec<TrayIconEvent>,
+    /// The button whose up, following its double click, is dropped as well.
+    dropped_up: Option<MouseButton>,
+}
+
+impl DoubleClickFilter {
+    /// Returns the events to send for a [`TrayIconEvent::Click`] or
+    /// [`TrayIconEvent::DoubleClick`].
+    ///
+    /// When a button down is held back, the double click timer must be (re)started and
+    /// [`DoubleClickFilter::take_held`] called once it elapsed.
+    pub fn on_click(&mut self, event: TrayIconEvent) -> Vec<TrayIconEvent> {
+        match event {
+            TrayIconEvent::Click {
+                button,
+                button_state: MouseButtonState::Down,
+                ..
+            } => {
+                // a click of another button is not going to be part of the double click
+                let events = if self.held_button() == Some(button) {
+                    Vec::new()
+                } else {
+                    self.take_held()
+                };
+                self.dropped_up = None;
+                self.held.push(event);
+                events
+            }
+            TrayIconEvent::Click {
+                button,
+                button_state: MouseButtonState::Up,
+                ..
+            } => {
+                if self.dropped_up.take() == Some(button) {
+                    Vec::new()
+                } else if self.held_button() == Some(button) {
+                    self.held.push(event);
+                    Vec::new()
+                } else {
+                    vec![event]
+                }
+            }
+            TrayIconEvent::DoubleClick { button, .. } => {
+                let mut events = if self.held_button() == Some(button) {
+                    self.held.clear();
+                    self.dropped_up = Some(button);
+                    Vec::new()
+                } else {
+                    self.take_held()
+                };
+                events.push(event);
+                events
+            }
+            event => vec![event],
+        }
+    }
+
+    /// Returns whether a click is held back.
+    pub fn holding(&self) -> bool {
+        !self.held.is_empty()
+    }
+
+    /// Takes the held back click, once the double click time elapsed.
+    pub fn take_held(&mut self) -> Vec<TrayIconEvent> {
+        std::mem::take(&mut self.held)
+    }
+
+    fn held_button(&self) -> Option<MouseButton> {
+        match self.held.first() {
+            Some(TrayIconEvent::Click { button, .. }) => Some(*button),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dpi::PhysicalPosition, Monitor, Rect, Timestamp, TrayIconId};
+
+    fn click(button: MouseButton, button_state: MouseButtonState) -> TrayIconEvent {
+        TrayIconEvent::Click {
+            id: TrayIconId::new("id"),
+            position: PhysicalPosition::default(),
+            scale_factor: 1.0,
+            rect: Rect::default(),
+            button,
+            button_state,
+            monitor: Monitor::default(),
+            timestamp: Timestamp::default(),
+        }
+    }
+
+    fn double_click(button: MouseButton) -> TrayIconEvent {
+        TrayIconEvent::DoubleClick {
+            id: TrayIconId::new("id"),
+            position: PhysicalPosition::default(),
+            scale_factor: 1.0,
+            rect: Rect::default(),
+            button,
+            monitor: Monitor::default(),
+            timestamp: Timestamp::default(),
+        }
+    }
+
+    /// Describes events as button and state, `None` standing for a double click.
+    fn describe(events: &[TrayIconEvent]) -> Vec<(MouseButton, Option<MouseButtonState>)> {
+        events
+            .iter()
+            .map(|event| match event {
+                TrayIconEvent::Click {
+                    button,
+                    button_state,
+                    ..
+                } => (*button, Some(*button_state)),
+                TrayIconEvent::DoubleClick { button, .. } => (*button, None),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn filters_clicks_of_double_clicks() {
+        use MouseButton::{Left, Right};
+        use MouseButtonState::{Down, Up};
+
+        let mut filter = DoubleClickFilter::default();
+        let mut send = |event| describe(&filter.on_click(event));
+
+        // a double click, as the shell sends it
+        assert!(send(click(Left, Down)).is_empty());
+        assert!(send(click(Left, Up)).is_empty());
+        assert_eq!(send(double_click(Left)), [(Left, None)]);
+        assert!(send(click(Left, Up)).is_empty());
+
+        // a single click, sent once the double click time elapsed
+        assert!(send(click(Left, Down)).is_empty());
+        assert!(send(click(Left, Up)).is_empty());
+        assert!(filter.holding());
+        assert_eq!(
+            describe(&filter.take_held()),
+            [(Left, Some(Down)), (Left, Some(Up))]
+        );
+        assert!(!filter.holding());
+
+        // a click of another button sends the held one first
+        let mut send = |event| describe(&filter.on_click(event));
+        assert!(send(click(Left, Down)).is_empty());
+        assert_eq!(send(click(Right, Down)), [(Left, Some(Down))]);
+        assert!(send(click(Right, Up)).is_empty());
+        assert_eq!(send(click(Left, Up)), [(Left, Some(Up))]);
+        assert_eq!(
+            describe(&filter.take_held()),
+            [(Right, Some(Down)), (Right, Some(Up))]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! The notification area isn't a drop target, so files are dropped on an invisible window
+//! kept over the tray icon instead.
+
+use std::{ffi::OsString, os::windows::ffi::OsStringExt, path::PathBuf};
+
+use windows_sys::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, POINT, WPARAM},
+    Graphics::Gdi::ClientToScreen,
+    UI::{
+        Shell::{DragFinish, DragQueryFileW, DragQueryPoint, HDROP},
+        WindowsAndMessaging::{
+            CreateWindowExW, DefWindowProcW, DestroyWindow, RegisterClassW, SendMessageW,
+            SetLayeredWindowAttributes, SetTimer, SetWindowPos, ShowWindow, CREATESTRUCTW,
+            CS_DBLCLKS, GWL_USERDATA, HWND_TOPMOST, LWA_ALPHA, MA_NOACTIVATE, SWP_NOACTIVATE,
+            SWP_SHOWWINDOW, SW_HIDE, WM_CONTEXTMENU, WM_DROPFILES, WM_LBUTTONDBLCLK,
+            WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDBLCLK, WM_MBUTTONDOWN, WM_MBUTTONUP,
+            WM_MOUSEACTIVATE, WM_MOUSEMOVE, WM_NCCREATE, WM_NCDESTROY, WM_RBUTTONDBLCLK,
+            WM_RBUTTONDOWN, WM_RBUTTONUP, WM_TIMER, WM_XBUTTONDBLCLK, WM_XBUTTONDOWN, WM_XBUTTONUP,
+            WNDCLASSW, WS_EX_ACCEPTFILES, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
+            WS_EX_TOPMOST, WS_POPUP,
+        },
+    },
+};
+
+use super::{get_tray_rect, util, WM_USER_FILES_DROPPED, WM_USER_TRAYICON};
+
+/// How often the overlay follows the tray icon, which moves when other icons are added
+/// or removed, and is raised above the taskbar again.
+const TRACK_INTERVAL_MS: u32 = 500;
+const TRACK_TIMER_ID: usize = 1;
+
+/// An invisible window over a tray icon that accepts dropped files, posting
+/// `WM_USER_FILES_DROPPED` to the tray window.
+///
+/// The window is destroyed when this is dropped.
+pub struct DropOverlay {
+    hwnd: HWND,
+}
+
+struct OverlayData {
+    tray_hwnd: HWND,
+    internal_id: u32,
+}
+
+impl DropOverlay {
+    pub unsafe fn new(tray_hwnd: HWND, internal_id: u32) -> Option<Self> {
+        let class_name = util::encode_wide("tray_icon_drop_overlay");
+        let hinstance = util::get_instance_handle();
+
+        let wnd_class = WNDCLASSW {
+            style: CS_DBLCLKS,
+            lpfnWndProc: Some(overlay_proc),
+            lpszClassName: class_name.as_ptr(),
+            hInstance: hinstance,
+            ..std::mem::zeroed()
+        };
+        RegisterClassW(&wnd_class);
+
+        let data = Box::into_raw(Box::new(OverlayData {
+            tray_hwnd,
+            internal_id,
+        }));
+        let hwnd = CreateWindowExW(
+            WS_EX_ACCEPTFILES | WS_EX_LAYERED | WS_EX_NOACTIVATE | WS_EX_TOOLWINDOW | WS_EX_TOPMOST,
+            class_name.as_ptr(),
+            std::ptr::null(),
+            WS_POPUP,
+            0,
+            0,
+            0,
+            0,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            hinstance,
+            data as _,
+        );
+        if hwnd.is_null() {
+            drop(Box::from_raw(data));
+            return None;
+        }
+
+        // fully transparent windows are skipped when looking for the drop target
+        SetLayeredWindowAttributes(hwnd, 0, 1, LWA_ALPHA);
+        SetTimer(hwnd, TRACK_TIMER_ID, TRACK_INTERVAL_MS, None);
+
+        let overlay = Self { hwnd };
+        overlay.track();
+        Some(overlay)
+    }
+
+    /// Moves the overlay over the tray icon, hiding it while the icon has no position.
+    pub unsafe fn track(&self) {
+        let data = &*(util::get_window_long(self.hwnd, GWL_USERDATA) as *const OverlayData);
+        track(self.hwnd, data);
+    }
+
+    pub unsafe fn hide(&self) {
+        ShowWindow(self.hwnd, SW_HIDE);
+    }
+}
+
+impl Drop for DropOverlay {
+    fn drop(&mut self) {
+        unsafe {
+            DestroyWindow(self.hwnd);
+        }
+    }
+}
+
+unsafe fn track(hwnd: HWND, data: &OverlayData) {
+    match get_tray_rect(data.internal_id, data.tray_hwnd) {
+        Some(rect) => {
+            SetWindowPos(
+                hwnd,
+                HWND_TOPMOST,
+                rect.left,
+                rect.top,
+                rect.right - rect.left,
+                rect.bottom - rect.top,
+                SWP_NOACTIVATE | SWP_SHOWWINDOW,
+            );
+        }
+        None => {
+            ShowWindow(hwnd, SW_HIDE);
+        }
+    }
+}
+
+unsafe extern "system" fn overlay_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_NCCREATE {
+        let createstruct = &*(lparam as *const CREATESTRUCTW);
+        util::set_window_long(hwnd, GWL_USERDATA, createstruct.lpCreateParams as _);
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+
+    let data = util::get_window_long(hwnd, GWL_USERDATA) as *mut OverlayData;
+    if data.is_null() {
+        return DefWindowProcW(hwnd, msg, wparam, lparam);
+    }
+
+    match msg {
+        WM_NCDESTROY => {
+            util::set_window_long(hwnd, GWL_USERDATA, 0);
+            drop(Box::from_raw(data));
+        }
+        WM_TIMER if wparam == TRACK_TIMER_ID => {
+            track(hwnd, &*data);
+            return 0;
+        }
+        WM_MOUSEACTIVATE => return MA_NOACTIVATE as _,
+        WM_DROPFILES => {
+            let hdrop = wparam as HDROP;
+            let paths = dropped_paths(hdrop);
+            let mut point = POINT { x: 0, y: 0 };
+            DragQueryPoint(hdrop, &mut point);
+            ClientToScreen(hwnd, &mut point);
+            DragFinish(hdrop);
+
+            SendMessageW(
+                (*data).tray_hwnd,
+                WM_USER_FILES_DROPPED,
+                Box::into_raw(Box::new((paths, point))) as _,
+                0,
+            );
+            return 0;
+        }
+        // the overlay covers the icon, so its mouse messages are handled as the icon's,
+        // the tray window reads the cursor position itself
+        WM_MOUSEMOVE | WM_LBUTTONDOWN | WM_LBUTTONUP | WM_LBUTTONDBLCLK | WM_RBUTTONDOWN
+        | WM_RBUTTONUP | WM_RBUTTONDBLCLK | WM_MBUTTONDOWN | WM_MBUTTONUP | WM_MBUTTONDBLCLK
+        | WM_XBUTTONDOWN | WM_XBUTTONUP | WM_XBUTTONDBLCLK | WM_CONTEXTMENU => {
+            SendMessageW((*data).tray_hwnd, WM_USER_TRAYICON, 0, msg as LPARAM);
+        }
+        _ => {}
+    }
+
+    // lets a right button up be followed by WM_CONTEXTMENU, as on the icon itself
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
+
+/// Returns the paths of the files dropped with `hdrop`.
+unsafe fn dropped_paths(hdrop: HDROP) -> Vec<PathBuf> {
+    let count = DragQueryFileW(hdrop, u32::MAX, std::ptr::null_mut(), 0);
+    (0..count)
+        .filter_map(|index| {
+            let len = DragQueryFileW(hdrop, index, std::ptr::null_mut(), 0) as usize;
+            if len == 0 {
+                return None;
+            }
+            let mut path = vec![0u16; len + 1];
+            DragQueryFileW(hdrop, index, path.as_mut_ptr(), path.len() as u32);
+            Some(PathBuf::from(OsString::from_wide(&path[..len])))
+        })
+        .collect()
+}
@@ -0,0 +1,175 @@
+use std::{
+    ffi::OsString,
+    io, mem,
+    os::windows::ffi::OsStringExt,
+    path::Path,
+    thread::{self, JoinHandle},
+};
+
+use windows_sys::Win32::{
+    Foundation::{CloseHandle, FALSE, HANDLE, HWND, INVALID_HANDLE_VALUE, TRUE, WAIT_OBJECT_0},
+    Storage::FileSystem::{
+        CreateFileW, ReadDirectoryChangesW, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OVERLAPPED,
+        FILE_LIST_DIRECTORY, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE,
+        FILE_NOTIFY_CHANGE_SIZE, FILE_NOTIFY_INFORMATION, FILE_SHARE_DELETE, FILE_SHARE_READ,
+        FILE_SHARE_WRITE, OPEN_EXISTING,
+    },
+    System::{
+        Threading::{CreateEventW, SetEvent, WaitForMultipleObjects, INFINITE},
+        IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED},
+    },
+    UI::WindowsAndMessaging::PostMessageW,
+};
+
+use super::{util, WM_USER_ICON_FILE_CHANGED};
+
+/// Watches the directory of an icon file on a background thread and posts
+/// `WM_USER_ICON_FILE_CHANGED` to the tray window whenever the file changes.
+///
+/// The watcher thread is stopped when this is dropped.
+pub struct IconWatcher {
+    stop_event: HANDLE,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// Handles are only used by the watcher thread until it is joined.
+struct SendHandles {
+    hwnd: HWND,
+    directory: HANDLE,
+    stop_event: HANDLE,
+}
+
+unsafe impl Send for SendHandles {}
+
+impl IconWatcher {
+    /// `path` must be absolute.
+    pub fn new(hwnd: HWND, path: &Path) -> io::Result<Self> {
+        let (Some(directory), Some(file_name)) = (path.parent(), path.file_name()) else {
+            return Err(io::ErrorKind::InvalidInput.into());
+        };
+        let file_name = file_name.to_string_lossy().to_lowercase();
+
+        unsafe {
+            let directory = CreateFileW(
+                util::encode_wide(directory).as_ptr(),
+                FILE_LIST_DIRECTORY,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                std::ptr::null(),
+                OPEN_EXISTING,
+                FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OVERLAPPED,
+                std::ptr::null_mut(),
+            );
+            if directory == INVALID_HANDLE_VALUE {
+                return Err(io::Error::last_os_error());
+            }
+
+            let stop_event = CreateEventW(std::ptr::null(), TRUE, FALSE, std::ptr::null());
+            if stop_event.is_null() {
+                let err = io::Error::last_os_error();
+                CloseHandle(directory);
+                return Err(err);
+            }
+
+            let handles = SendHandles {
+                hwnd,
+                directory,
+                stop_event,
+            };
+            let thread = thread::spawn(move || {
+                let handles = handles;
+                watch(&handles, &file_name);
+                CloseHandle(handles.directory);
+            });
+
+            Ok(Self {
+                stop_event,
+                thread: Some(thread),
+            })
+        }
+    }
+}
+
+impl Drop for IconWatcher {
+    fn drop(&mut self) {
+        unsafe {
+            SetEvent(self.stop_event);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+            CloseHandle(self.stop_event);
+        }
+    }
+}
+
+/// Reads directory changes until the stop event is set or reading fails.
+unsafe fn watch(handles: &SendHandles, file_name: &str) {
+    let io_event = CreateEventW(std::ptr::null(), TRUE, FALSE, std::ptr::null());
+    if io_event.is_null() {
+        return;
+    }
+
+    // FILE_NOTIFY_INFORMATION entries are DWORD aligned
+    let mut buffer = vec![0u32; 1024];
+    loop {
+        let mut overlapped: OVERLAPPED = mem::zeroed();
+        overlapped.hEvent = io_event;
+
+        let started = ReadDirectoryChangesW(
+            handles.directory,
+            buffer.as_mut_ptr() as _,
+            (buffer.len() * mem::size_of::<u32>()) as u32,
+            FALSE,
+            FILE_NOTIFY_CHANGE_LAST_WRITE | FILE_NOTIFY_CHANGE_SIZE | FILE_NOTIFY_CHANGE_FILE_NAME,
+            std::ptr::null_mut(),
+            &mut overlapped,
+            None,
+        );
+        if started == 0 {
+            break;
+        }
+
+        let events = [io_event, handles.stop_event];
+        let mut bytes = 0;
+        if WaitForMultipleObjects(events.len() as u32, events.as_ptr(), FALSE, INFINITE)
+            != WAIT_OBJECT_0
+        {
+            CancelIoEx(handles.directory, &overlapped);
+            GetOverlappedResult(handles.directory, &overlapped, &mut bytes, TRUE);
+            break;
+        }
+        if GetOverlappedResult(handles.directory, &overlapped, &mut bytes, FALSE) == 0 {
+            break;
+        }
+
+        // the buffer overflowed, so any file might have changed
+        let changed = bytes == 0
+            || changed_file_names(&buffer)
+                .iter()
+                .any(|name| name == file_name);
+        if changed {
+            PostMessageW(handles.hwnd, WM_USER_ICON_FILE_CHANGED, 0, 0);
+        }
+    }
+
+    CloseHandle(io_event);
+}
+
+/// Returns the lowercase file names of the FILE_NOTIFY_INFORMATION entries in `buffer`.
+unsafe fn changed_file_names(buffer: &[u32]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut offset = 0;
+    loop {
+        let entry =
+            &*((buffer.as_ptr() as *const u8).add(offset) as *const FILE_NOTIFY_INFORMATION);
+        let name = std::slice::from_raw_parts(
+            entry.FileName.as_ptr(),
+            entry.FileNameLength as usize / mem::size_of::<u16>(),
+        );
+        names.push(OsString::from_wide(name).to_string_lossy().to_lowercase());
+
+        match entry.NextEntryOffset {
+            0 => return names,
+            next => offset += next as usize,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::{Duration, Instant};
+
+use windows_sys::Win32::UI::WindowsAndMessaging::WM_MOUSEMOVE;
+
+use crate::{dpi::PhysicalPosition, MoveEventPolicy, Rect};
+
+/// Returns whether the tray icon callback `event` is skipped, mouse moves are only
+/// tracked for Enter, Move and Leave events when `hover_events` are enabled.
+#[inline]
+pub fn skips(hover_events: bool, event: u32) -> bool {
+    !hover_events && event == WM_MOUSEMOVE
+}
+
+/// What to do with a [`TrayIconEvent::Move`](crate::TrayIconEvent::Move).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveAction {
+    Send,
+    /// Send the move later with [`MoveFilter::take_pending`], after this delay.
+    Delay(Duration),
+    Drop,
+}
+
+/// Applies the [`MoveEventPolicy`] of a tray icon.
+pub struct MoveFilter {
+    policy: MoveEventPolicy,
+    last_sent: Option<Instant>,
+    pending: Option<(PhysicalPosition<f64>, Rect)>,
+}
+
+impl MoveFilter {
+    pub fn new(policy: MoveEventPolicy) -> Self {
+        Self {
+            policy,
+            last_sent: None,
+            pending: None,
+        }
+    }
+
+    /// Decides what to do with a move to `position` at `now`.
+    pub fn on_move(
+        &mut self,
+        now: Instant,
+        position: PhysicalPosition<f64>,
+        rect: Rect,
+    ) -> MoveAction {
+        match self.policy {
+            MoveEventPolicy::All => MoveAction::Send,
+            MoveEventPolicy::None => MoveAction::Drop,
+            MoveEventPolicy::Throttled(interval) => {
+                let elapsed = self.last_sent.map(|at| now.duration_since(at));
+                match elapsed {
+                    Some(elapsed) if elapsed < interval => {
+                        // only the latest position is kept, so the final one is sent
+                        let delay = self.pending.is_none().then(|| interval - elapsed);
+                        self.pending = Some((position, rect));
+                        delay.map_or(MoveAction::Drop, MoveAction::Delay)
+                    }
+                    _ => {
+                        self.last_sent = Some(now);
+                        self.pending = None;
+                        MoveAction::Send
+                    }
+                }
+            }
+        }
+    }
+
+    /// Takes the delayed move, when the delay passed or the cursor left the icon.
+    pub fn take_pending(&mut self, now: Instant) -> Option<(PhysicalPosition<f64>, Rect)> {
+        let pending = self.pending.take();
+        if pending.is_some() {
+            self.last_sent = Some(now);
+        }
+        pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_mouse_moves_without_hover_events() {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{WM_LBUTTONDOWN, WM_RBUTTONUP};
+
+        assert!(skips(false, WM_MOUSEMOVE));
+        assert!(!skips(true, WM_MOUSEMOVE));
+        for event in [WM_LBUTTONDOWN, WM_RBUTTONUP] {
+            assert!(!skips(false, event));
+            assert!(!skips(true, event));
+        }
+    }
+
+    #[test]
+    fn throttles_moves() {
+        let interval = Duration::from_millis(100);
+        let mut filter = MoveFilter::new(MoveEventPolicy::Throttled(interval));
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let position = |x| PhysicalPosition::new(x, 0.);
+        let rect = Rect::default();
+
+        assert_eq!(filter.on_move(at(0), position(0.), rect), MoveAction::Send);
+        // the first held back move schedules the delivery, later ones replace it
+        assert_eq!(
+            filter.on_move(at(30), position(1.), rect),
+            MoveAction::Delay(Duration::from_millis(70))
+        );
+        assert_eq!(filter.on_move(at(60), position(2.), rect), MoveAction::Drop);
+        assert_eq!(filter.take_pending(at(100)).unwrap().0, position(2.));
+        assert!(filter.take_pending(at(100)).is_none());
+
+        // the delayed move counts as sent
+        assert_eq!(
+            filter.on_move(at(150), position(3.), rect),
+            MoveAction::Delay(Duration::from_millis(50))
+        );
+        assert_eq!(filter.take_pending(at(200)).unwrap().0, position(3.));
+        assert_eq!(
+            filter.on_move(at(300), position(4.), rect),
+            MoveAction::Send
+        );
+    }
+
+    #[test]
+    fn applies_policy() {
+        let mut all = MoveFilter::new(MoveEventPolicy::All);
+        let mut none = MoveFilter::new(MoveEventPolicy::None);
+        let now = Instant::now();
+        for _ in 0..3 {
+            let position = PhysicalPosition::default();
+            assert_eq!(
+                all.on_move(now, position, Rect::default()),
+                MoveAction::Send
+            );
+            assert_eq!(
+                none.on_move(now, position, Rect::default()),
+                MoveAction::Drop
+            );
+        }
+        assert!(none.take_pending(now).is_none());
+    }
+}
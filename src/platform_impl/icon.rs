@@ -1,9 +1,33 @@
-use std::{fmt, io, mem, path::Path, sync::Arc};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
+    fs::File,
+    hash::{Hash, Hasher},
+    io, mem,
+    path::Path,
+    sync::{Arc, LazyLock, Mutex, OnceLock, PoisonError, Weak},
+};
 
 use windows_sys::{
     core::PCWSTR,
-    Win32::UI::WindowsAndMessaging::{
-        CreateIcon, DestroyIcon, LoadImageW, HICON, IMAGE_ICON, LR_DEFAULTSIZE, LR_LOADFROMFILE,
+    Win32::{
+        Foundation::{ERROR_RESOURCE_NAME_NOT_FOUND, ERROR_RESOURCE_TYPE_NOT_FOUND, TRUE},
+        Graphics::Gdi::{
+            CreateBitmap, DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP,
+            BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HBITMAP,
+        },
+        Storage::FileSystem::FILE_ATTRIBUTE_NORMAL,
+        UI::Shell::{
+            SHDefExtractIconW, SHGetFileInfoW, SHGetStockIconInfo, SHFILEINFOW, SHGFI_ICON,
+            SHGFI_SMALLICON, SHGFI_USEFILEATTRIBUTES, SHGSI_ICONLOCATION, SHSTOCKICONID,
+            SHSTOCKICONINFO, SIID_APPLICATION, SIID_DELETE, SIID_DOCNOASSOC, SIID_ERROR, SIID_FIND,
+            SIID_FOLDER, SIID_HELP, SIID_INFO, SIID_KEY, SIID_LOCK, SIID_RECYCLER, SIID_SETTINGS,
+            SIID_SHIELD, SIID_USERS, SIID_WARNING, SIID_WORLD,
+        },
+        UI::WindowsAndMessaging::{
+            CopyImage, CreateIcon, CreateIconFromResourceEx, CreateIconIndirect, DestroyIcon,
+            GetIconInfo, LoadImageW, HICON, ICONINFO, IMAGE_ICON, LR_DEFAULTSIZE, LR_LOADFROMFILE,
+        },
     },
 };
 
@@ -17,18 +41,64 @@ impl Pixel {
     }
 }
 
+/// Icons created from pixel data, keyed by their final premultiplied pixels so that
+/// identical icons share a single `HICON`.
+static ICON_CACHE: LazyLock<Mutex<HashMap<RgbaIcon, CachedIcon>>> = LazyLock::new(Default::default);
+
+/// The cache doesn't keep icons alive, the handle is destroyed once the last
+/// [`WinIcon`] sharing it is dropped.
+struct CachedIcon(Weak<RaiiIcon>);
+
+unsafe impl Send for CachedIcon {}
+
 impl RgbaIcon {
-    fn into_windows_icon(self) -> Result<WinIcon, BadIcon> {
+    /// Creates an icon from straight-alpha pixels, these are premultiplied first
+    /// since that's what the shell expects when alpha blending the icon.
+    fn into_windows_icon(mut self, options: IconOptions) -> Result<WinIcon, BadIcon> {
+        compose::premultiply(&mut self.rgba);
+        self.into_windows_icon_premultiplied(options)
+    }
+
+    fn into_windows_icon_premultiplied(mut self, options: IconOptions) -> Result<WinIcon, BadIcon> {
+        if !options.no_resize && self.width != 0 && self.height != 0 {
+            let size = util::tray_icon_size();
+            if (self.width, self.height) != (size, size) {
+                self.rgba = compose::resize(&self.rgba, self.width, self.height, size, size);
+                (self.width, self.height) = (size, size);
+            }
+        }
+
+        let mut cache = ICON_CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(inner) = cache.get(&self).and_then(|cached| cached.0.upgrade()) {
+            return Ok(WinIcon {
+                inner,
+                rgba: None,
+                rescale: false,
+            });
+        }
+
+        let icon = self.clone().create_icon()?;
+        cache.retain(|_, cached| cached.0.strong_count() > 0);
+        cache.insert(self, CachedIcon(Arc::downgrade(&icon.inner)));
+        Ok(icon)
+    }
+
+    fn create_icon(self) -> Result<WinIcon, BadIcon> {
         let rgba = self.rgba;
         let pixel_count = rgba.len() / PIXEL_SIZE;
-        let mut and_mask = Vec::with_capacity(pixel_count);
+        let width = self.width as usize;
+        // 1bpp AND mask, rows are padded to a WORD boundary and set bits mark transparent pixels
+        let mask_stride = width.div_ceil(16) * 2;
+        let mut and_mask = vec![0u8; mask_stride * self.height as usize];
         let pixels =
             unsafe { std::slice::from_raw_parts_mut(rgba.as_ptr() as *mut Pixel, pixel_count) };
-        for pixel in pixels {
-            and_mask.push(pixel.a.wrapping_sub(u8::MAX)); // invert alpha channel
+        for (i, pixel) in pixels.iter_mut().enumerate() {
+            if pixel.a == 0 {
+                let (x, y) = (i % width, i / width);
+                and_mask[y * mask_stride + x / 8] |= 0x80 >> (x % 8);
+            }
             pixel.convert_to_bgra();
         }
-        assert_eq!(and_mask.len(), pixel_count);
         let handle = unsafe {
             CreateIcon(
                 std::ptr::null_mut(),
@@ -40,22 +110,128 @@ impl RgbaIcon {
                 rgba.as_ptr(),
             )
         };
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            width = self.width,
+            height = self.height,
+            success = !handle.is_null(),
+            "CreateIcon"
+        );
         if !handle.is_null() {
-            Ok(WinIcon::from_handle(handle))
+            Ok(WinIcon::from_handle_with_size(
+                handle,
+                true,
+                self.width,
+                self.height,
+            ))
         } else {
             Err(BadIcon::OsError(io::Error::last_os_error()))
         }
     }
 }
 
+/// Reads `hbitmap` as top-down 32bpp BGRA pixels.
+unsafe fn bitmap_to_bgra(hbitmap: HBITMAP, width: i32, height: i32) -> Result<Vec<u8>, BadIcon> {
+    let mut bmi: BITMAPINFO = mem::zeroed();
+    bmi.bmiHeader = BITMAPINFOHEADER {
+        biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+        biWidth: width,
+        // negative height makes the rows top-down like our RGBA buffers
+        biHeight: -height,
+        biPlanes: 1,
+        biBitCount: (PIXEL_SIZE * 8) as u16,
+        biCompression: BI_RGB,
+        ..mem::zeroed()
+    };
+
+    let mut bits = vec![0u8; (width * height) as usize * PIXEL_SIZE];
+    let hdc = GetDC(std::ptr::null_mut());
+    let lines = GetDIBits(
+        hdc,
+        hbitmap,
+        0,
+        height as u32,
+        bits.as_mut_ptr() as _,
+        &mut bmi,
+        DIB_RGB_COLORS,
+    );
+    ReleaseDC(std::ptr::null_mut(), hdc);
+
+    if lines != 0 {
+        Ok(bits)
+    } else {
+        Err(BadIcon::OsError(io::Error::last_os_error()))
+    }
+}
+
+/// Deletes the bitmaps created by `GetIconInfo` when dropped.
+struct IconInfo(ICONINFO);
+
+impl IconInfo {
+    unsafe fn new(handle: HICON) -> Result<Self, BadIcon> {
+        let mut info = IconInfo(mem::zeroed());
+        if GetIconInfo(handle, &mut info.0) != 0 {
+            Ok(info)
+        } else {
+            Err(BadIcon::OsError(io::Error::last_os_error()))
+        }
+    }
+
+    /// Returns the width and height of the icon.
+    unsafe fn size(&self) -> Result<(i32, i32), BadIcon> {
+        // monochrome icons store the AND and XOR masks in a single bitmap of double height
+        let (hbitmap, height_factor) = if self.0.hbmColor.is_null() {
+            (self.0.hbmMask, 2)
+        } else {
+            (self.0.hbmColor, 1)
+        };
+
+        let mut bitmap: BITMAP = mem::zeroed();
+        if GetObjectW(
+            hbitmap,
+            mem::size_of::<BITMAP>() as i32,
+            &mut bitmap as *mut _ as _,
+        ) != 0
+        {
+            Ok((bitmap.bmWidth, bitmap.bmHeight / height_factor))
+        } else {
+            Err(BadIcon::OsError(io::Error::last_os_error()))
+        }
+    }
+}
+
+impl Drop for IconInfo {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.0.hbmColor.is_null() {
+                DeleteObject(self.0.hbmColor);
+            }
+            if !self.0.hbmMask.is_null() {
+                DeleteObject(self.0.hbmMask);
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 struct RaiiIcon {
     handle: HICON,
+    /// Whether `DestroyIcon` should be called on the handle when dropped.
+    owned: bool,
+    width: u32,
+    height: u32,
+    /// A hash of the icon's pixels and dimensions, `None` if they can't be read back.
+    content_hash: OnceLock<Option<u64>>,
 }
 
 #[derive(Clone)]
 pub(crate) struct WinIcon {
     inner: Arc<RaiiIcon>,
+    /// The pixels the icon was created from, if any, so it can be serialized losslessly and
+    /// rescaled.
+    rgba: Option<Arc<RgbaIcon>>,
+    /// Whether the icon is scaled to the tray icon size, which changes with the DPI.
+    rescale: bool,
 }
 
 unsafe impl Send for WinIcon {}
@@ -65,26 +241,292 @@ impl WinIcon {
         self.inner.handle
     }
 
-    pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+    pub fn width(&self) -> u32 {
+        self.inner.width
+    }
+
+    /// Returns a hash of the pixels and dimensions of this icon, computed on first use.
+    pub(crate) fn content_hash(&self) -> Option<u64> {
+        *self.inner.content_hash.get_or_init(|| {
+            let mut hasher = DefaultHasher::new();
+            self.to_rgba().ok()?.hash(&mut hasher);
+            Some(hasher.finish())
+        })
+    }
+
+    pub fn height(&self) -> u32 {
+        self.inner.height
+    }
+
+    pub fn from_rgba(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+        options: IconOptions,
+    ) -> Result<Self, BadIcon> {
+        let rgba_icon = RgbaIcon::from_rgba(rgba, width, height)?;
+        let source = Arc::new(rgba_icon.clone());
+        let icon = rgba_icon.into_windows_icon(options)?;
+        Ok(WinIcon {
+            rgba: Some(source),
+            rescale: !options.no_resize,
+            ..icon
+        })
+    }
+
+    /// Returns this icon created again from its pixels for the current tray icon size, or
+    /// `None` if it isn't scaled to it.
+    ///
+    /// Icons of the same size share a cached handle, so this is cheap when nothing changed.
+    pub(crate) fn rescaled(&self) -> Option<Result<Self, BadIcon>> {
+        let source = self.rgba.as_ref().filter(|_| self.rescale)?;
+        let icon = RgbaIcon::clone(source).into_windows_icon(IconOptions::default());
+        Some(icon.map(|icon| WinIcon {
+            rgba: Some(source.clone()),
+            rescale: true,
+            ..icon
+        }))
+    }
+
+    /// Returns the pixels this icon was created from with [`WinIcon::from_rgba`].
+    #[cfg(feature = "serde")]
+    pub(crate) fn source_rgba(&self) -> Option<&RgbaIcon> {
+        self.rgba.as_deref()
+    }
+
+    pub fn from_rgb(rgb: &[u8], width: u32, height: u32) -> Result<Self, BadIcon> {
+        let rgba_icon = RgbaIcon::from_rgb(rgb, width, height)?;
+        rgba_icon.into_windows_icon(IconOptions::default())
+    }
+
+    pub fn from_rgba_premultiplied(
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+    ) -> Result<Self, BadIcon> {
         let rgba_icon = RgbaIcon::from_rgba(rgba, width, height)?;
-        rgba_icon.into_windows_icon()
+        rgba_icon.into_windows_icon_premultiplied(IconOptions::default())
+    }
+
+    pub fn to_rgba(&self) -> Result<(Vec<u8>, u32, u32), BadIcon> {
+        unsafe {
+            let info = IconInfo::new(self.as_raw_handle())?;
+            if info.0.hbmColor.is_null() {
+                return Err(BadIcon::DecodeError(
+                    "monochrome icons can't be converted to RGBA".to_string(),
+                ));
+            }
+            let (width, height) = info.size()?;
+
+            let mut rgba = bitmap_to_bgra(info.0.hbmColor, width, height)?;
+
+            // icons without an alpha channel rely on the AND mask for transparency
+            let has_alpha = rgba.chunks_exact(PIXEL_SIZE).any(|p| p[3] != 0);
+            let mask = if has_alpha {
+                compose::demultiply(&mut rgba);
+                None
+            } else {
+                Some(bitmap_to_bgra(info.0.hbmMask, width, height)?)
+            };
+
+            for (i, pixel) in rgba.chunks_exact_mut(PIXEL_SIZE).enumerate() {
+                pixel.swap(0, 2);
+                if let Some(mask) = &mask {
+                    // white mask pixels are transparent
+                    pixel[3] = if mask[i * PIXEL_SIZE] == 0 {
+                        u8::MAX
+                    } else {
+                        0
+                    };
+                }
+            }
+
+            Ok((rgba, width as u32, height as u32))
+        }
+    }
+
+    pub fn from_png_bytes(bytes: &[u8]) -> Result<Self, BadIcon> {
+        let rgba_icon = RgbaIcon::from_png(bytes)?;
+        rgba_icon.into_windows_icon(IconOptions::default())
+    }
+
+    #[cfg(feature = "svg")]
+    pub fn from_svg_str(svg: &str, size: u32) -> Result<Self, BadIcon> {
+        let rgba_icon = RgbaIcon::from_svg(svg, size)?;
+        rgba_icon.into_windows_icon(IconOptions { no_resize: true })
+    }
+
+    pub fn from_ico_bytes(bytes: &[u8], preferred_size: Option<u32>) -> Result<Self, BadIcon> {
+        let size = preferred_size.unwrap_or_else(util::tray_icon_size);
+        let frames = ico::parse(bytes)?;
+        let frame = ico::closest_frame(&frames, size)
+            .ok_or(BadIcon::MalformedIco("ICONDIR contains no frames"))?;
+
+        let handle = unsafe {
+            CreateIconFromResourceEx(
+                frame.data.as_ptr(),
+                frame.data.len() as u32,
+                TRUE,
+                // resource format version 3.0, the only one supported by Windows
+                0x00030000,
+                frame.width as i32,
+                frame.height as i32,
+                0,
+            )
+        };
+        if !handle.is_null() {
+            Ok(WinIcon::from_handle(handle))
+        } else {
+            Err(BadIcon::OsError(io::Error::last_os_error()))
+        }
+    }
+
+    pub fn from_rgba_with_stride(
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+        stride: usize,
+    ) -> Result<Self, BadIcon> {
+        let rgba_icon = RgbaIcon::from_rgba_with_stride(rgba, width, height, stride)?;
+        rgba_icon.into_windows_icon(IconOptions::default())
     }
 
     pub(crate) fn from_handle(handle: HICON) -> Self {
+        Self::from_raw_handle(handle, true)
+    }
+
+    pub(crate) fn from_raw_handle(handle: HICON, owned: bool) -> Self {
+        let (width, height) =
+            unsafe { IconInfo::new(handle).and_then(|info| info.size()) }.unwrap_or((0, 0));
+        Self::from_handle_with_size(handle, owned, width as u32, height as u32)
+    }
+
+    fn from_handle_with_size(handle: HICON, owned: bool, width: u32, height: u32) -> Self {
         Self {
             #[allow(clippy::arc_with_non_send_sync)]
-            inner: Arc::new(RaiiIcon { handle }),
+            inner: Arc::new(RaiiIcon {
+                handle,
+                owned,
+                width,
+                height,
+                content_hash: OnceLock::new(),
+            }),
+            rgba: None,
+            rescale: false,
+        }
+    }
+
+    /// Returns a copy of this icon scaled to `width` x `height`.
+    fn scaled(&self, width: u32, height: u32) -> Result<Self, BadIcon> {
+        let handle = unsafe {
+            CopyImage(
+                self.as_raw_handle(),
+                IMAGE_ICON,
+                width as i32,
+                height as i32,
+                0,
+            )
+        };
+        if !handle.is_null() {
+            Ok(WinIcon::from_handle(handle as HICON))
+        } else {
+            Err(BadIcon::OsError(io::Error::last_os_error()))
+        }
+    }
+
+    pub(crate) unsafe fn from_hbitmap(
+        hbitmap: HBITMAP,
+        mask: Option<HBITMAP>,
+        owned: bool,
+    ) -> Result<Self, BadIcon> {
+        let is_valid = |hbitmap: HBITMAP| {
+            let mut bitmap: BITMAP = mem::zeroed();
+            let len = GetObjectW(
+                hbitmap,
+                mem::size_of::<BITMAP>() as i32,
+                &mut bitmap as *mut _ as _,
+            );
+            (len != 0).then_some(bitmap)
+        };
+
+        let bitmap = is_valid(hbitmap).ok_or(BadIcon::InvalidHandle)?;
+        if mask.is_some_and(|mask| is_valid(mask).is_none()) {
+            return Err(BadIcon::InvalidHandle);
+        }
+
+        // an all-zero AND mask makes every pixel opaque, which is what bitmaps
+        // without an alpha channel need
+        let generated_mask = match mask {
+            Some(_) => std::ptr::null_mut(),
+            None => {
+                let stride = (bitmap.bmWidth as usize).div_ceil(16) * 2;
+                let bits = vec![0u8; stride * bitmap.bmHeight as usize];
+                CreateBitmap(bitmap.bmWidth, bitmap.bmHeight, 1, 1, bits.as_ptr() as _)
+            }
+        };
+
+        let info = ICONINFO {
+            fIcon: TRUE,
+            xHotspot: 0,
+            yHotspot: 0,
+            hbmMask: mask.unwrap_or(generated_mask),
+            hbmColor: hbitmap,
+        };
+        let handle = CreateIconIndirect(&info);
+        let error = io::Error::last_os_error();
+
+        if !generated_mask.is_null() {
+            DeleteObject(generated_mask);
         }
+
+        if handle.is_null() {
+            return Err(BadIcon::OsError(error));
+        }
+
+        // the icon holds copies of the bitmaps
+        if owned {
+            DeleteObject(hbitmap);
+            if let Some(mask) = mask {
+                DeleteObject(mask);
+            }
+        }
+
+        Ok(WinIcon::from_handle(handle))
     }
 
     pub(crate) fn from_path<P: AsRef<Path>>(
         path: P,
         size: Option<(u32, u32)>,
     ) -> Result<Self, BadIcon> {
-        // width / height of 0 along with LR_DEFAULTSIZE tells windows to load the default icon size
-        let (width, height) = size.unwrap_or((0, 0));
+        let path = path.as_ref();
+        if !path.is_file() {
+            return Err(BadIcon::FileNotFound(path.to_path_buf()));
+        }
+
+        let (width, height) = size.unwrap_or_else(util::small_icon_size);
 
-        let wide_path = util::encode_wide(path.as_ref());
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("ico") => Self::from_ico_path(path, width, height),
+            Some("png") => {
+                let file = File::open(path).map_err(BadIcon::OsError)?;
+                let rgba_icon = RgbaIcon::from_png(io::BufReader::new(file))?;
+                let icon = rgba_icon.into_windows_icon(IconOptions { no_resize: true })?;
+                if (icon.width(), icon.height()) == (width, height) {
+                    Ok(icon)
+                } else {
+                    icon.scaled(width, height)
+                }
+            }
+            _ => Err(BadIcon::UnsupportedFormat(extension)),
+        }
+    }
+
+    fn from_ico_path(path: &Path, width: u32, height: u32) -> Result<Self, BadIcon> {
+        let wide_path = util::encode_wide(path);
 
         let handle = unsafe {
             LoadImageW(
@@ -93,7 +535,7 @@ impl WinIcon {
                 IMAGE_ICON,
                 width as i32,
                 height as i32,
-                LR_DEFAULTSIZE | LR_LOADFROMFILE,
+                LR_LOADFROMFILE,
             )
         };
         if !handle.is_null() {
@@ -130,18 +572,126 @@ impl WinIcon {
         Self::from_resource_inner_name(resource_id as PCWSTR, size)
     }
 
+    pub(crate) fn from_system(icon: StockIcon, size: Option<u32>) -> Result<Self, BadIcon> {
+        let size = size.unwrap_or_else(util::tray_icon_size);
+        unsafe {
+            let mut info = SHSTOCKICONINFO {
+                cbSize: mem::size_of::<SHSTOCKICONINFO>() as u32,
+                ..mem::zeroed()
+            };
+            // the location is used rather than `SHGSI_ICON` to extract the icon at an exact size
+            let hr = SHGetStockIconInfo(icon.id(), SHGSI_ICONLOCATION, &mut info);
+            if hr < 0 {
+                return Err(BadIcon::OsError(io::Error::from_raw_os_error(hr)));
+            }
+
+            let mut handle = std::ptr::null_mut();
+            let hr = SHDefExtractIconW(
+                info.szPath.as_ptr(),
+                info.iIcon,
+                0,
+                &mut handle,
+                std::ptr::null_mut(),
+                size,
+            );
+            if hr < 0 {
+                Err(BadIcon::OsError(io::Error::from_raw_os_error(hr)))
+            } else if handle.is_null() {
+                Err(BadIcon::OsError(io::ErrorKind::NotFound.into()))
+            } else {
+                // the extracted icon is a copy that we own
+                Ok(WinIcon::from_handle(handle))
+            }
+        }
+    }
+
+    pub(crate) fn from_associated_file(path: &Path) -> Result<Self, BadIcon> {
+        // files that don't exist are looked up by their extension
+        let flags = if path.exists() {
+            0
+        } else {
+            SHGFI_USEFILEATTRIBUTES
+        };
+        Self::from_shell_file_info(&util::encode_wide(path), flags)
+    }
+
+    pub(crate) fn from_extension(extension: &str) -> Result<Self, BadIcon> {
+        let extension = format!(".{}", extension.trim_start_matches('.'));
+        Self::from_shell_file_info(&util::encode_wide(extension), SHGFI_USEFILEATTRIBUTES)
+    }
+
+    fn from_shell_file_info(path: &[u16], flags: u32) -> Result<Self, BadIcon> {
+        let handle = unsafe {
+            let mut info: SHFILEINFOW = mem::zeroed();
+            let result = SHGetFileInfoW(
+                path.as_ptr(),
+                FILE_ATTRIBUTE_NORMAL,
+                &mut info,
+                mem::size_of::<SHFILEINFOW>() as u32,
+                SHGFI_ICON | SHGFI_SMALLICON | flags,
+            );
+            if result == 0 {
+                std::ptr::null_mut()
+            } else {
+                info.hIcon
+            }
+        };
+
+        if handle.is_null() {
+            Self::from_system(StockIcon::Document, Some(util::small_icon_size().0))
+        } else {
+            // `SHGetFileInfoW` returns a copy that must be destroyed by the caller
+            Ok(WinIcon::from_handle(handle))
+        }
+    }
+
     pub(crate) fn from_resource_name(
         resource_name: &str,
         size: Option<(u32, u32)>,
     ) -> Result<Self, BadIcon> {
         let wide_name = util::encode_wide(resource_name);
-        Self::from_resource_inner_name(wide_name.as_ptr(), size)
+        Self::from_resource_inner_name(wide_name.as_ptr(), size).map_err(|e| match e {
+            BadIcon::OsError(e)
+                if matches!(
+                    e.raw_os_error().map(|code| code as u32),
+                    Some(ERROR_RESOURCE_NAME_NOT_FOUND | ERROR_RESOURCE_TYPE_NOT_FOUND)
+                ) =>
+            {
+                BadIcon::ResourceNotFound(resource_name.to_string())
+            }
+            e => e,
+        })
+    }
+}
+
+impl StockIcon {
+    fn id(self) -> SHSTOCKICONID {
+        match self {
+            StockIcon::Application => SIID_APPLICATION,
+            StockIcon::Document => SIID_DOCNOASSOC,
+            StockIcon::Folder => SIID_FOLDER,
+            StockIcon::Error => SIID_ERROR,
+            StockIcon::Warning => SIID_WARNING,
+            StockIcon::Info => SIID_INFO,
+            StockIcon::Help => SIID_HELP,
+            StockIcon::Shield => SIID_SHIELD,
+            StockIcon::Lock => SIID_LOCK,
+            StockIcon::Key => SIID_KEY,
+            StockIcon::Users => SIID_USERS,
+            StockIcon::World => SIID_WORLD,
+            StockIcon::Find => SIID_FIND,
+            StockIcon::Settings => SIID_SETTINGS,
+            StockIcon::Delete => SIID_DELETE,
+            StockIcon::Recycler => SIID_RECYCLER,
+        }
     }
 }
 
 impl Drop for RaiiIcon {
     fn drop(&mut self) {
-        unsafe { DestroyIcon(self.handle) };
+        if self.owned {
+            unsafe { DestroyIcon(self.handle) };
+        }
     }
 }
 
@@ -0,0 +1,104 @@
+//! The shell identifies tray icons by their `uID`, so the ids of live icons must never
+//! be handed out again.
+
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::{Mutex, PoisonError},
+};
+
+static ICON_IDS: Mutex<IconIds> = Mutex::new(IconIds::new(1));
+
+/// Returns an id no live tray icon has, `None` once all are taken.
+pub fn allocate() -> Option<u32> {
+    ICON_IDS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .allocate()
+}
+
+/// Makes `id` available again, once its icon was deleted from the notification area.
+pub fn release(id: u32) {
+    ICON_IDS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .release(id);
+}
+
+/// Allocates ids counting up, then reuses released ones once the counter ran out.
+pub struct IconIds {
+    /// The next id never handed out, `None` after `u32::MAX` was.
+    next: Option<u32>,
+    /// Released ids, reused oldest first.
+    free: VecDeque<u32>,
+    live: BTreeSet<u32>,
+}
+
+impl IconIds {
+    pub const fn new(first: u32) -> Self {
+        Self {
+            next: Some(first),
+            free: VecDeque::new(),
+            live: BTreeSet::new(),
+        }
+    }
+
+    pub fn allocate(&mut self) -> Option<u32> {
+        let id = match self.next {
+            Some(id) => {
+                self.next = id.checked_add(1);
+                id
+            }
+            None => self.free.pop_front()?,
+        };
+        self.live.insert(id);
+        Some(id)
+    }
+
+    pub fn release(&mut self, id: u32) {
+        // ids released twice or never allocated would be handed out twice
+        if self.live.remove(&id) {
+            self.free.push_back(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_unique_ids() {
+        let mut ids = IconIds::new(1);
+        assert_eq!(ids.allocate(), Some(1));
+        assert_eq!(ids.allocate(), Some(2));
+
+        // released ids aren't reused while fresh ones are left
+        ids.release(1);
+        assert_eq!(ids.allocate(), Some(3));
+
+        // neither are ids released twice or never allocated
+        ids.release(2);
+        ids.release(2);
+        ids.release(10);
+        assert_eq!(ids.free, [1, 2]);
+    }
+
+    #[test]
+    fn reuses_released_ids_after_wraparound() {
+        let mut ids = IconIds::new(u32::MAX - 2);
+        let first = ids.allocate().unwrap();
+        let second = ids.allocate().unwrap();
+        assert_eq!(ids.allocate(), Some(u32::MAX));
+        assert_eq!(ids.allocate(), None);
+
+        ids.release(second);
+        ids.release(first);
+        assert_eq!(ids.allocate(), Some(second));
+        assert_eq!(ids.allocate(), Some(first));
+        assert_eq!(ids.allocate(), None);
+
+        // a reused id may be released and reused again
+        ids.release(first);
+        assert_eq!(ids.allocate(), Some(first));
+    }
+}
@@ -1,25 +1,54 @@
+mod click;
+mod file_drop;
+#[cfg(feature = "hot-reload")]
+mod hot_reload;
+mod hover;
 mod icon;
+mod icon_ids;
+mod notification;
+mod proxy;
+#[cfg(feature = "toast")]
+mod toast;
 mod util;
-
-use std::{ptr, sync::LazyLock};
+mod wheel;
+
+use std::{
+    cell::Cell,
+    path::PathBuf,
+    ptr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock,
+    },
+    time::{Duration, Instant},
+};
 
 use windows_sys::{
     s,
     Win32::{
-        Foundation::{FALSE, HWND, LPARAM, LRESULT, POINT, RECT, S_OK, TRUE, WPARAM},
+        Foundation::{HWND, LPARAM, LRESULT, POINT, RECT, S_OK, WPARAM},
+        System::Threading::GetCurrentThreadId,
         UI::{
+            Input::KeyboardAndMouse::{GetAsyncKeyState, GetDoubleClickTime, VK_XBUTTON2},
             Shell::{
-                Shell_NotifyIconGetRect, Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP,
-                NIM_ADD, NIM_DELETE, NIM_MODIFY, NOTIFYICONDATAW, NOTIFYICONIDENTIFIER,
+                Shell_NotifyIconGetRect, NIF_ICON, NIF_MESSAGE, NIF_SHOWTIP, NIF_TIP, NIM_ADD,
+                NIM_DELETE, NIM_MODIFY, NIM_SETFOCUS, NIM_SETVERSION, NINF_KEY, NIN_BALLOONHIDE,
+                NIN_BALLOONSHOW, NIN_BALLOONTIMEOUT, NIN_BALLOONUSERCLICK, NIN_POPUPOPEN,
+                NIN_SELECT, NOTIFYICONDATAW, NOTIFYICONIDENTIFIER, NOTIFYICON_VERSION_4,
+                NOTIFY_ICON_DATA_FLAGS,
             },
             WindowsAndMessaging::{
-                CreateWindowExW, DefWindowProcW, DestroyWindow, GetCursorPos, KillTimer,
-                RegisterClassW, RegisterWindowMessageA, SendMessageW, SetForegroundWindow,
-                SetTimer, TrackPopupMenu, CREATESTRUCTW, CW_USEDEFAULT, GWL_USERDATA, HICON, HMENU,
-                TPM_BOTTOMALIGN, TPM_LEFTALIGN, WM_CREATE, WM_DESTROY, WM_LBUTTONDBLCLK,
-                WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDBLCLK, WM_MBUTTONDOWN, WM_MBUTTONUP,
-                WM_MOUSEMOVE, WM_NCCREATE, WM_RBUTTONDBLCLK, WM_RBUTTONDOWN, WM_RBUTTONUP,
-                WM_TIMER, WNDCLASSW, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
+                CreateWindowExW, DefWindowProcW, DestroyWindow, GetCursorPos,
+                GetWindowThreadProcessId, KillTimer, RegisterClassW, RegisterWindowMessageA,
+                SendMessageW, SetForegroundWindow, SetTimer, TrackPopupMenuEx, CREATESTRUCTW,
+                CW_USEDEFAULT, GWL_USERDATA, HICON, HMENU, SPI_SETWORKAREA, TPMPARAMS,
+                TPM_BOTTOMALIGN, TPM_LEFTALIGN, TPM_RIGHTALIGN, TPM_TOPALIGN, TPM_VERTICAL,
+                TRACK_POPUP_MENU_FLAGS, USER_DEFAULT_SCREEN_DPI, USER_TIMER_MINIMUM, WHEEL_DELTA,
+                WM_CONTEXTMENU, WM_CREATE, WM_DESTROY, WM_DISPLAYCHANGE, WM_DPICHANGED,
+                WM_LBUTTONDBLCLK, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDBLCLK, WM_MBUTTONDOWN,
+                WM_MBUTTONUP, WM_MOUSEMOVE, WM_NCCREATE, WM_RBUTTONDBLCLK, WM_RBUTTONDOWN,
+                WM_RBUTTONUP, WM_SETTINGCHANGE, WM_TIMER, WM_XBUTTONDBLCLK, WM_XBUTTONDOWN,
+                WM_XBUTTONUP, WNDCLASSW, WS_EX_LAYERED, WS_EX_NOACTIVATE, WS_EX_TOOLWINDOW,
                 WS_EX_TRANSPARENT, WS_OVERLAPPED,
             },
         },
@@ -27,11 +56,19 @@ use windows_sys::{
 };
 
 use crate::{
-    dpi::PhysicalPosition, icon::Icon, menu, MouseButton, MouseButtonState, Rect,
-    TrayIconAttributes, TrayIconEvent, TrayIconId, COUNTER,
+    dpi::PhysicalPosition, icon::Icon, menu, tray_icon_id::IdRegistration, AttentionConfig,
+    ContextMenuSource, IconSet, MouseButton, MouseButtonState, Notification, NotificationId,
+    NotificationOverflow, Rect, SuppressionReason, Theme, TrayIconAttributes, TrayIconEvent,
+    TrayIconId,
 };
 
 pub(crate) use self::icon::WinIcon as PlatformIcon;
+pub(crate) use self::notification::user_notification_state;
+pub(crate) use self::proxy::{ProxyCommand, TrayProxy};
+#[cfg(feature = "test-util")]
+pub(crate) use self::util::current_timestamp;
+#[cfg(test)]
+pub(crate) use self::util::tray_icon_size;
 
 const WM_USER_TRAYICON: u32 = 6002;
 const WM_USER_UPDATE_TRAYMENU: u32 = 6003;
@@ -41,27 +78,576 @@ const WM_USER_HIDE_TRAYICON: u32 = 6006;
 const WM_USER_UPDATE_TRAYTOOLTIP: u32 = 6007;
 const WM_USER_LEAVE_TIMER_ID: u32 = 6008;
 const WM_USER_SHOW_MENU_ON_LEFT_CLICK: u32 = 6009;
+const WM_USER_UPDATE_BADGE: u32 = 6010;
+const WM_USER_UPDATE_ANIMATION: u32 = 6011;
+const WM_USER_ANIMATION_TIMER_ID: u32 = 6012;
+#[cfg(feature = "hot-reload")]
+const WM_USER_WATCH_ICON_PATH: u32 = 6013;
+#[cfg(feature = "hot-reload")]
+const WM_USER_ICON_FILE_CHANGED: u32 = 6014;
+#[cfg(feature = "hot-reload")]
+const WM_USER_HOT_RELOAD_TIMER_ID: u32 = 6015;
+/// How long to wait for more changes to a watched icon file before reloading it,
+/// editors often write files in several steps.
+#[cfg(feature = "hot-reload")]
+const HOT_RELOAD_DEBOUNCE_MS: u32 = 200;
+const WM_USER_SHOW_NOTIFICATION: u32 = 6016;
+const WM_USER_CLEAR_NOTIFICATION: u32 = 6017;
+const WM_USER_NOTIFICATION_QUEUEING: u32 = 6018;
+const WM_USER_NOTIFICATION_QUEUE_LIMIT: u32 = 6019;
+/// Returned for `WM_USER_SHOW_NOTIFICATION` when the notification queue rejected the
+/// notification, OS error codes are never negative.
+const NOTIFICATION_QUEUE_FULL: LRESULT = -2;
+/// Returned by `register_tray_icon` when the shell rejected the GUID of the tray icon.
+const GUID_REJECTED: LRESULT = -3;
+const WM_USER_NOTIFICATION_TIMER_ID: u32 = 6020;
+const WM_USER_SET_STANDARD_TOOLTIP: u32 = 6021;
+const WM_USER_SET_TOOLTIP_PROVIDER: u32 = 6022;
+const WM_USER_MOUSE_WHEEL: u32 = 6023;
+const WM_USER_SET_MENU_SUPPRESSED: u32 = 6024;
+const WM_USER_MOVE_TIMER_ID: u32 = 6025;
+const WM_USER_SET_HOVER_EVENTS: u32 = 6026;
+const WM_USER_DOUBLE_CLICK_TIMER_ID: u32 = 6027;
+const WM_USER_HOVER_DWELL_TIMER_ID: u32 = 6028;
+const WM_USER_SET_FILE_DROP: u32 = 6029;
+/// Sent by the [`file_drop::DropOverlay`] with the dropped paths and point.
+const WM_USER_FILES_DROPPED: u32 = 6030;
+const WM_USER_GET_VISIBLE: u32 = 6031;
+const WM_USER_GET_ICON: u32 = 6032;
+const WM_USER_UPDATE_ATTRIBUTES: u32 = 6033;
+const WM_USER_SET_ATTENTION: u32 = 6034;
+const WM_USER_ATTENTION_TIMER_ID: u32 = 6035;
+/// Posted by a [`TrayProxy`] when it queued commands.
+const WM_USER_PROXY_COMMAND: u32 = 6036;
+const WM_USER_GET_TOOLTIP: u32 = 6037;
+const WM_USER_FORCE_REFRESH: u32 = 6038;
+const WM_USER_SET_MESSAGE_HOOK: u32 = 6039;
+/// Handles messages of the tray window, see [`TrayIcon::set_message_hook`](crate::TrayIcon::set_message_hook).
+type MessageHook = Box<dyn FnMut(u32, WPARAM, LPARAM) -> Option<LRESULT>>;
+/// The minimum time between two calls of the tooltip provider while the cursor hovers the icon.
+const TOOLTIP_PROVIDER_INTERVAL: Duration = Duration::from_secs(1);
+/// How long the shell may take to show a balloon before it is considered suppressed.
+const NOTIFICATION_SHOW_TIMEOUT_MS: u32 = 5000;
+/// Sent instead of `NIN_SELECT` when the icon is selected with the keyboard, missing from windows-sys.
+const NIN_KEYSELECT: u32 = NIN_SELECT | NINF_KEY;
 /// When the taskbar is created, it registers a message with the "TaskbarCreated" string and then broadcasts this message to all top-level windows
 /// When the application receives this message, it should assume that any taskbar icons it added have been removed and add them again.
 static S_U_TASKBAR_RESTART: LazyLock<u32> =
     LazyLock::new(|| unsafe { RegisterWindowMessageA(s!("TaskbarCreated")) });
+/// Whether the shell accepted `NOTIFYICON_VERSION_4` for the last added icon,
+/// large notification icons are only shown with it.
+static VERSION_4_SUPPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Where the displayed tray icon comes from.
+enum IconSource {
+    Icon(Option<Icon>),
+    /// The icon closest to the tray icon size is displayed.
+    Set(IconSet),
+    /// `light` is displayed on light taskbars and `dark` on dark ones.
+    Pair {
+        light: Icon,
+        dark: Icon,
+    },
+}
+
+impl IconSource {
+    fn from_attributes(attrs: &TrayIconAttributes) -> Self {
+        if let Some((light, dark)) = &attrs.icon_pair {
+            IconSource::Pair {
+                light: light.clone(),
+                dark: dark.clone(),
+            }
+        } else if let Some(icon_set) = &attrs.icon_set {
+            IconSource::Set(icon_set.clone())
+        } else {
+            IconSource::Icon(attrs.icon.clone())
+        }
+    }
+}
+
+/// An icon animation played on top of the icon source.
+struct Animation {
+    /// Each frame along with how long it is shown.
+    frames: Vec<(Icon, Duration)>,
+    frame: usize,
+}
+
+impl Animation {
+    /// (Re)starts the timer that advances the animation after the current frame's duration.
+    unsafe fn schedule_next_frame(&self, hwnd: HWND) {
+        let interval = self.frames[self.frame]
+            .1
+            .as_millis()
+            .clamp(USER_TIMER_MINIMUM as _, u32::MAX as _);
+        SetTimer(
+            hwnd,
+            WM_USER_ANIMATION_TIMER_ID as _,
+            interval as _,
+            Some(tray_timer_proc),
+        );
+    }
+}
+
+/// Alternates the icon with another one while drawing attention.
+struct Attention {
+    config: AttentionConfig,
+    /// Whether the alternate icon is shown.
+    alternate: bool,
+    started: Instant,
+}
+
+/// The attributes changed by [`TrayIcon::update`], `None` leaving one as is.
+struct AttributeUpdate {
+    icon_source: Option<IconSource>,
+    tooltip: Option<Option<String>>,
+    visible: Option<bool>,
+}
 
 struct TrayUserData {
     internal_id: u32,
     id: TrayIconId,
     hwnd: HWND,
     hpopupmenu: Option<HMENU>,
+    /// The displayed icon, resolved from `icon_source` and `badge`.
     icon: Option<Icon>,
+    icon_source: IconSource,
+    animation: Option<Animation>,
+    attention: Option<Attention>,
+    badge: Option<u32>,
+    /// The icon file reloaded when it changes.
+    #[cfg(feature = "hot-reload")]
+    watched_icon_path: Option<std::path::PathBuf>,
     tooltip: Option<String>,
+    standard_tooltip: bool,
+    tooltip_provider: Option<Box<dyn FnMut() -> String>>,
+    /// When the tooltip provider was last called.
+    tooltip_provided_at: Option<Instant>,
+    message_hook: Option<MessageHook>,
+    /// Counts the hooks set, so a hook replaced while it runs isn't put back afterwards.
+    message_hook_generation: u32,
+    /// The icon of the last notification, kept alive until the next one is shown.
+    notification_icon: Option<Icon>,
+    notification_queue: notification::NotificationQueue,
+    /// The notification whose balloon is shown or about to be shown.
+    current_notification: Option<NotificationId>,
+    /// Whether the icon is added to the notification area.
+    visible: bool,
+    /// The theme of the taskbar when it last changed.
+    theme: Theme,
     entered: bool,
     last_position: Option<PhysicalPosition<f64>>,
+    move_filter: hover::MoveFilter,
+    hover_events: bool,
+    hover_dwell: Option<Duration>,
+    /// The X button last pressed, as the button up doesn't tell which one was released.
+    x_button: MouseButton,
+    /// Holds back clicks that may become a double click, when enabled.
+    double_click_filter: Option<click::DoubleClickFilter>,
     menu_on_left_click: bool,
+    menu_suppressed: bool,
+    /// Whether a right click requested the menu, so the `WM_CONTEXTMENU` following it
+    /// isn't taken for a keyboard request.
+    right_click_menu: bool,
+    /// The window over the icon that files are dropped on, when enabled.
+    file_drop: Option<file_drop::DropOverlay>,
+    /// The commands of the [`TrayProxy`]s of the icon.
+    proxy_queue: Arc<proxy::ProxyQueue>,
+}
+
+impl TrayUserData {
+    /// Returns the icon that should be displayed for the current icon source, badge and theme.
+    fn resolve_icon(&self) -> Option<Icon> {
+        if let Some(attention) = self.attention.as_ref().filter(|a| a.alternate) {
+            return attention.config.icon.clone();
+        }
+
+        // animation frames are shown as is, so no icon has to be created per frame
+        if let Some(animation) = &self.animation {
+            return animation
+                .frames
+                .get(animation.frame)
+                .map(|(icon, _)| icon.clone());
+        }
+
+        // icons scaled to the tray icon size are scaled again as it changes with the DPI
+        let rescaled = |icon: &Icon| icon.rescaled().unwrap_or_else(|| icon.clone());
+        let icon = match &self.icon_source {
+            IconSource::Icon(icon) => icon.as_ref().map(rescaled),
+            IconSource::Set(icon_set) => icon_set.closest(util::tray_icon_size()).cloned(),
+            IconSource::Pair { light, dark } => Some(if util::system_uses_light_theme() {
+                rescaled(light)
+            } else {
+                rescaled(dark)
+            }),
+        };
+
+        match (icon, self.badge) {
+            // fall back to the plain icon if it can't be badged, e.g. monochrome icons
+            (Some(icon), Some(count)) => Some(icon.with_badge(count).unwrap_or(icon)),
+            (icon, _) => icon,
+        }
+    }
+
+    /// Returns the icon shown in notifications of kind [`NotificationKind::User`](crate::NotificationKind::User),
+    /// the displayed one or, for `large` notifications, a larger one picked from the
+    /// icon set or upscaled.
+    fn notification_icon(&self, large: bool) -> Option<Icon> {
+        if !large {
+            return self.icon.clone();
+        }
+
+        let size = util::large_icon_size();
+        let icon = match &self.icon_source {
+            IconSource::Set(icon_set) if self.animation.is_none() => {
+                icon_set.closest(size).cloned()
+            }
+            _ => self.icon.clone(),
+        }?;
+        Some(icon.resized(size).unwrap_or(icon))
+    }
+
+    /// Adds the tray icon to the notification area, returning the result for `SendMessageW`.
+    unsafe fn register(&self) -> LRESULT {
+        register_tray_icon(
+            self.hwnd,
+            self.internal_id,
+            &self.icon.as_ref().map(|i| i.inner.as_raw_handle()),
+            &self.tooltip,
+            self.standard_tooltip,
+        )
+    }
+
+    /// Shows `notification` right away, adding the tray icon first if it is hidden.
+    unsafe fn show_notification(
+        &mut self,
+        id: NotificationId,
+        notification: &Notification,
+    ) -> LRESULT {
+        // balloons are attached to the icon, so a hidden icon is shown first
+        if !self.visible {
+            let result = self.register();
+            if result != 0 {
+                return result;
+            }
+            self.visible = true;
+        }
+
+        let large_icon = notification.large_icon && VERSION_4_SUPPORTED.load(Ordering::Relaxed);
+        self.notification_icon = match notification.icon.clone() {
+            Some(icon) if large_icon => Some(icon.resized(util::large_icon_size()).unwrap_or(icon)),
+            Some(icon) => Some(icon),
+            None => self.notification_icon(large_icon),
+        };
+
+        let shown = notification::show(
+            self.hwnd,
+            self.internal_id,
+            notification,
+            self.notification_icon
+                .as_ref()
+                .map(|i| i.inner.as_raw_handle()),
+            large_icon,
+        );
+        if shown {
+            self.current_notification = Some(id);
+            self.notification_queue.showing = true;
+
+            // the shell holds balloons back while the user is busy, which is reported right away,
+            // otherwise the notification was suppressed if the shell doesn't show it in time
+            match notification::user_notification_state() {
+                Ok(state) if notification::suppresses(state, notification) => {
+                    TrayIconEvent::send(TrayIconEvent::NotificationSuppressed {
+                        id: self.id.clone(),
+                        notification: id,
+                        reason: SuppressionReason::UserState(state),
+                        timestamp: util::current_timestamp(),
+                    });
+                }
+                _ => {
+                    SetTimer(
+                        self.hwnd,
+                        WM_USER_NOTIFICATION_TIMER_ID as _,
+                        NOTIFICATION_SHOW_TIMEOUT_MS,
+                        Some(tray_timer_proc),
+                    );
+                }
+            }
+        }
+        os_result(shown)
+    }
+
+    /// Marks the current balloon as closed and shows the next queued notification.
+    unsafe fn show_next_notification(&mut self) {
+        self.current_notification = None;
+        self.notification_queue.showing = false;
+        if let Some((id, notification)) = self.notification_queue.pop() {
+            self.show_notification(id, &notification);
+        }
+    }
+
+    /// Sends [`TrayIconEvent::ContextMenuRequested`] and shows the menu at `x`, `y`
+    /// unless it is suppressed.
+    unsafe fn request_menu(&self, source: ContextMenuSource, x: i32, y: i32, rect: Rect) {
+        TrayIconEvent::send(TrayIconEvent::ContextMenuRequested {
+            id: self.id.clone(),
+            position: PhysicalPosition::new(x as f64, y as f64),
+            scale_factor: util::scale_factor_at(PhysicalPosition::new(x as f64, y as f64)),
+            rect,
+            source,
+            monitor: util::monitor_of(&rect),
+            timestamp: util::message_timestamp(),
+        });
+
+        if !self.menu_suppressed {
+            if let Some(menu) = self.hpopupmenu {
+                show_tray_menu(self.hwnd, menu, x, y, TPM_BOTTOMALIGN | TPM_LEFTALIGN, None);
+            }
+        }
+    }
+
+    /// Sends the move held back by the [`MoveEventPolicy`](crate::MoveEventPolicy), if any.
+    fn send_pending_move(&mut self) {
+        if let Some((position, rect)) = self.move_filter.take_pending(Instant::now()) {
+            TrayIconEvent::send(TrayIconEvent::Move {
+                id: self.id.clone(),
+                position,
+                scale_factor: util::scale_factor_at(position),
+                rect,
+                timestamp: util::current_timestamp(),
+            });
+        }
+    }
+
+    /// Calls the tooltip provider and sends its tooltip to the shell, at most once per
+    /// [`TOOLTIP_PROVIDER_INTERVAL`].
+    unsafe fn provide_tooltip(&mut self) {
+        let now = Instant::now();
+        if !provider_due(self.tooltip_provided_at, now) {
+            return;
+        }
+        let Some(provider) = &mut self.tooltip_provider else {
+            return;
+        };
+        self.tooltip_provided_at = Some(now);
+
+        let tooltip = provider();
+        let tooltip = crate::tooltip::normalize(&tooltip);
+        let tooltip = crate::tooltip::truncate(&tooltip);
+        if self.tooltip.as_deref() == Some(tooltip) {
+            return;
+        }
+
+        let mut nid = NOTIFYICONDATAW {
+            uFlags: tooltip_flags(self.standard_tooltip),
+            hWnd: self.hwnd,
+            uID: self.internal_id,
+            szTip: encode_tooltip(tooltip),
+            ..std::mem::zeroed()
+        };
+        if util::notify_icon(NIM_MODIFY, &mut nid) {
+            self.tooltip = Some(tooltip.to_string());
+        }
+    }
+
+    /// Resolves the displayed icon again and sends it to the shell.
+    unsafe fn update_icon(&mut self) -> LRESULT {
+        let icon = self.resolve_icon();
+        // identical icons share a cached handle, so there's nothing to update
+        if icon_handle(&icon) == icon_handle(&self.icon) {
+            return os_result(true);
+        }
+        self.icon = icon;
+        // the shell doesn't know hidden icons, they are added with the current icon once shown
+        if !self.visible {
+            return os_result(true);
+        }
+
+        let mut nid = NOTIFYICONDATAW {
+            uFlags: NIF_ICON,
+            hWnd: self.hwnd,
+            uID: self.internal_id,
+            ..std::mem::zeroed()
+        };
+
+        if let Some(hicon) = self.icon.as_ref().map(|i| i.inner.as_raw_handle()) {
+            nid.hIcon = hicon;
+        }
+
+        os_result(util::notify_icon(NIM_MODIFY, &mut nid))
+    }
+
+    /// Stops drawing attention, showing the current icon again.
+    unsafe fn stop_attention(&mut self) {
+        if self.attention.take().is_some() {
+            KillTimer(self.hwnd, WM_USER_ATTENTION_TIMER_ID as _);
+            self.update_icon();
+        }
+    }
+
+    /// Shows `notification`, or queues it while another one is shown.
+    unsafe fn queue_notification(
+        &mut self,
+        id: NotificationId,
+        notification: Notification,
+    ) -> LRESULT {
+        let queue = &mut self.notification_queue;
+        // realtime notifications are only meaningful right away, so they skip the queue
+        if queue.enabled && queue.showing && !notification.realtime {
+            return if queue.push(id, notification) {
+                0
+            } else {
+                NOTIFICATION_QUEUE_FULL
+            };
+        }
+
+        self.show_notification(id, &notification)
+    }
+
+    /// Runs the commands queued by the [`TrayProxy`]s of the icon.
+    unsafe fn run_proxy_commands(&mut self) {
+        for command in self.proxy_queue.take() {
+            match command {
+                proxy::ProxyCommand::Update {
+                    icon,
+                    tooltip,
+                    visible,
+                    reply,
+                } => {
+                    let update = AttributeUpdate {
+                        icon_source: icon.map(IconSource::Icon),
+                        tooltip,
+                        visible,
+                    };
+                    let _ = reply.send(lresult_to_result(self.apply_update(update)));
+                }
+                proxy::ProxyCommand::ShowNotification {
+                    id,
+                    notification,
+                    reply,
+                } => {
+                    let result = match self.queue_notification(id, notification) {
+                        NOTIFICATION_QUEUE_FULL => Err(crate::Error::NotificationQueueFull),
+                        result => lresult_to_result(result).map(|()| id),
+                    };
+                    let _ = reply.send(result);
+                }
+            }
+        }
+    }
+
+    /// Applies `update` with a single shell call, restoring the previous attributes if it fails.
+    unsafe fn apply_update(&mut self, update: AttributeUpdate) -> LRESULT {
+        let visible = update.visible.unwrap_or(self.visible);
+        let icon_changed = update.icon_source.is_some();
+        let tooltip_changed = update.tooltip.is_some();
+
+        let previous_source = update
+            .icon_source
+            .map(|source| std::mem::replace(&mut self.icon_source, source));
+        let previous_animation = if icon_changed {
+            self.animation.take()
+        } else {
+            None
+        };
+        let previous_icon = icon_changed.then(|| {
+            let icon = self.resolve_icon();
+            std::mem::replace(&mut self.icon, icon)
+        });
+        let previous_tooltip = update
+            .tooltip
+            .map(|tooltip| std::mem::replace(&mut self.tooltip, tooltip));
+
+        // values that didn't change aren't sent again, which may make the tooltip flicker
+        let icon_differs = previous_icon
+            .as_ref()
+            .is_some_and(|icon| icon_handle(icon) != icon_handle(&self.icon));
+        let tooltip_differs = previous_tooltip
+            .as_ref()
+            .is_some_and(|tooltip| *tooltip != self.tooltip);
+
+        let result = match (self.visible, visible) {
+            (true, true) => os_result(self.modify(icon_differs, tooltip_differs)),
+            (false, true) => self.register(),
+            (true, false) => {
+                remove_tray_icon(self.hwnd, self.internal_id);
+                0
+            }
+            (false, false) => 0,
+        };
+        if result != 0 {
+            if let Some(source) = previous_source {
+                self.icon_source = source;
+                self.animation = previous_animation;
+            }
+            if let Some(icon) = previous_icon {
+                self.icon = icon;
+            }
+            if let Some(tooltip) = previous_tooltip {
+                self.tooltip = tooltip;
+            }
+            return result;
+        }
+
+        // setting an icon cancels the animation, setting a tooltip the tooltip provider
+        if previous_animation.is_some() {
+            KillTimer(self.hwnd, WM_USER_ANIMATION_TIMER_ID as _);
+        }
+        if tooltip_changed {
+            self.tooltip_provider = None;
+        }
+
+        self.visible = visible;
+        if let Some(overlay) = &self.file_drop {
+            if visible {
+                overlay.track();
+            } else {
+                overlay.hide();
+            }
+        }
+        os_result(true)
+    }
+
+    /// Sends the icon and tooltip, when `icon` and `tooltip` changed, in one modify.
+    unsafe fn modify(&self, icon: bool, tooltip: bool) -> bool {
+        let mut nid = NOTIFYICONDATAW {
+            hWnd: self.hwnd,
+            uID: self.internal_id,
+            ..std::mem::zeroed()
+        };
+        if icon {
+            nid.uFlags |= NIF_ICON;
+            if let Some(hicon) = self.icon.as_ref().map(|i| i.inner.as_raw_handle()) {
+                nid.hIcon = hicon;
+            }
+        }
+        if tooltip {
+            nid.uFlags |= tooltip_flags(self.standard_tooltip);
+            if let Some(tooltip) = &self.tooltip {
+                nid.szTip = encode_tooltip(tooltip);
+            }
+        }
+
+        nid.uFlags == 0 || util::notify_icon(NIM_MODIFY, &mut nid)
+    }
 }
 
 pub struct TrayIcon {
     hwnd: HWND,
     menu: Option<Box<dyn menu::ContextMenu>>,
     internal_id: u32,
+    proxy_queue: Arc<proxy::ProxyQueue>,
+    /// Keeps the id taken while the icon is alive.
+    _registration: IdRegistration,
+    #[cfg(feature = "hot-reload")]
+    icon_watcher: Option<hot_reload::IconWatcher>,
+    /// Created on the first toast.
+    #[cfg(feature = "toast")]
+    toaster: Option<toast::Toaster>,
+    /// Set once toasts turned out to be unavailable, they're shown as balloons from then on.
+    #[cfg(feature = "toast")]
+    toasts_unavailable: bool,
+    /// Whether the icon was already deleted from the notification area by [`TrayIcon::remove`].
+    removed: bool,
 }
 
 impl core::fmt::Debug for TrayIcon {
@@ -82,9 +668,33 @@ impl core::fmt::Debug for TrayIcon {
 }
 
 impl TrayIcon {
-    pub fn new(id: TrayIconId, attrs: TrayIconAttributes) -> crate::Result<Self> {
-        let internal_id = COUNTER.next();
+    pub fn new(id: TrayIconId, mut attrs: TrayIconAttributes) -> crate::Result<Self> {
+        if let Some(tooltip) = &mut attrs.tooltip {
+            *tooltip = crate::tooltip::normalize(tooltip).into_owned();
+            crate::tooltip::validate(tooltip)?;
+        }
 
+        let registration = IdRegistration::new(id.clone(), attrs.allow_duplicate_ids)?;
+        let internal_id = icon_ids::allocate().ok_or_else(|| {
+            crate::Error::OsError(std::io::Error::new(
+                std::io::ErrorKind::OutOfMemory,
+                "no tray icon ids left",
+            ))
+        })?;
+        let result = Self::create(id, attrs, internal_id, registration);
+        // the icon wasn't added, so its id is free
+        if result.is_err() {
+            icon_ids::release(internal_id);
+        }
+        result
+    }
+
+    fn create(
+        id: TrayIconId,
+        attrs: TrayIconAttributes,
+        internal_id: u32,
+        registration: IdRegistration,
+    ) -> crate::Result<Self> {
         let class_name = util::encode_wide("tray_icon_app");
         unsafe {
             let hinstance = util::get_instance_handle();
@@ -98,17 +708,47 @@ impl TrayIcon {
 
             RegisterClassW(&wnd_class);
 
-            let traydata = TrayUserData {
+            let mut traydata = TrayUserData {
                 id,
                 internal_id,
                 hwnd: std::ptr::null_mut(),
                 hpopupmenu: attrs.menu.as_ref().map(|m| m.hpopupmenu() as _),
-                icon: attrs.icon.clone(),
+                icon: None,
+                icon_source: IconSource::from_attributes(&attrs),
+                animation: None,
+                attention: None,
+                badge: None,
+                #[cfg(feature = "hot-reload")]
+                watched_icon_path: None,
                 tooltip: attrs.tooltip.clone(),
+                standard_tooltip: attrs.standard_tooltip,
+                tooltip_provider: None,
+                tooltip_provided_at: None,
+                message_hook: None,
+                message_hook_generation: 0,
+                notification_icon: None,
+                notification_queue: Default::default(),
+                current_notification: None,
+                visible: attrs.visible,
+                theme: system_theme(),
                 entered: false,
                 last_position: None,
+                move_filter: hover::MoveFilter::new(attrs.move_events),
+                hover_events: attrs.hover_events,
+                hover_dwell: attrs.hover_dwell,
+                x_button: MouseButton::Back,
+                double_click_filter: attrs
+                    .double_click_detection
+                    .then(click::DoubleClickFilter::default),
                 menu_on_left_click: attrs.menu_on_left_click,
+                menu_suppressed: false,
+                right_click_menu: false,
+                file_drop: None,
+                proxy_queue: Arc::new(proxy::ProxyQueue::new()),
             };
+            let proxy_queue = traydata.proxy_queue.clone();
+            traydata.icon = traydata.resolve_icon();
+            let hicon = traydata.icon.as_ref().map(|i| i.inner.as_raw_handle());
 
             let hwnd = CreateWindowExW(
                 WS_EX_NOACTIVATE | WS_EX_TRANSPARENT | WS_EX_LAYERED |
@@ -136,10 +776,32 @@ impl TrayIcon {
                 return Err(crate::Error::OsError(std::io::Error::last_os_error()));
             }
 
-            let hicon = attrs.icon.as_ref().map(|i| i.inner.as_raw_handle());
+            util::set_icon_guid(internal_id, attrs.guid);
+            // hidden icons are added once shown
+            if attrs.visible {
+                let result = register_tray_icon(
+                    hwnd,
+                    internal_id,
+                    &hicon,
+                    &attrs.tooltip,
+                    attrs.standard_tooltip,
+                );
+                if let Err(error) = lresult_to_result(result) {
+                    util::set_icon_guid(internal_id, None);
+                    DestroyWindow(hwnd);
+                    return Err(error);
+                }
+            }
 
-            if !register_tray_icon(hwnd, internal_id, &hicon, &attrs.tooltip) {
-                return Err(crate::Error::OsError(std::io::Error::last_os_error()));
+            // registered last, so no error path has to remove the hook again
+            if attrs.wheel_events && !wheel::register(hwnd) {
+                let error = std::io::Error::last_os_error();
+                util::set_icon_guid(internal_id, None);
+                if attrs.visible {
+                    remove_tray_icon(hwnd, internal_id);
+                }
+                DestroyWindow(hwnd);
+                return Err(crate::Error::OsError(error));
             }
 
             if let Some(menu) = &attrs.menu {
@@ -150,37 +812,201 @@ impl TrayIcon {
                 hwnd,
                 internal_id,
                 menu: attrs.menu,
+                proxy_queue,
+                _registration: registration,
+                #[cfg(feature = "hot-reload")]
+                icon_watcher: None,
+                #[cfg(feature = "toast")]
+                toaster: None,
+                #[cfg(feature = "toast")]
+                toasts_unavailable: false,
+                removed: false,
             })
         }
     }
 
     pub fn set_icon(&mut self, icon: Option<Icon>) -> crate::Result<()> {
+        self.set_icon_source(IconSource::Icon(icon))
+    }
+
+    pub fn set_icon_set(&mut self, icon_set: Option<IconSet>) -> crate::Result<()> {
+        self.set_icon_source(match icon_set {
+            Some(icon_set) => IconSource::Set(icon_set),
+            None => IconSource::Icon(None),
+        })
+    }
+
+    pub fn set_icon_pair(&mut self, light: Icon, dark: Icon) -> crate::Result<()> {
+        self.set_icon_source(IconSource::Pair { light, dark })
+    }
+
+    pub fn icon(&self) -> Option<Icon> {
+        let mut icon = None;
         unsafe {
-            let mut nid = NOTIFYICONDATAW {
-                uFlags: NIF_ICON,
-                hWnd: self.hwnd,
-                uID: self.internal_id,
-                ..std::mem::zeroed()
-            };
+            SendMessageW(self.hwnd, WM_USER_GET_ICON, &mut icon as *mut _ as _, 0);
+        }
+        icon
+    }
 
-            if let Some(hicon) = icon.as_ref().map(|i| i.inner.as_raw_handle()) {
-                nid.hIcon = hicon;
+    fn set_icon_source(&mut self, icon_source: IconSource) -> crate::Result<()> {
+        // send the new icon source to the subclass proc to store it in the tray data
+        // and update the displayed icon
+        let result = unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_UPDATE_TRAYICON,
+                Box::into_raw(Box::new(icon_source)) as _,
+                0,
+            )
+        };
+        lresult_to_result(result)
+    }
+
+    pub fn set_icon_animation(
+        &mut self,
+        animation: Option<Vec<(Icon, Duration)>>,
+    ) -> crate::Result<()> {
+        let result = unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_UPDATE_ANIMATION,
+                Box::into_raw(Box::new(animation)) as _,
+                0,
+            )
+        };
+        lresult_to_result(result)
+    }
+
+    #[cfg(feature = "hot-reload")]
+    pub fn watch_icon_path(&mut self, path: &std::path::Path) -> crate::Result<()> {
+        self.unwatch_icon_path();
+
+        let path = std::path::absolute(path).map_err(crate::Error::OsError)?;
+        self.icon_watcher =
+            Some(hot_reload::IconWatcher::new(self.hwnd, &path).map_err(crate::Error::OsError)?);
+        unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_WATCH_ICON_PATH,
+                Box::into_raw(Box::new(Some(path))) as _,
+                0,
+            );
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "hot-reload")]
+    pub fn unwatch_icon_path(&mut self) {
+        if self.icon_watcher.take().is_some() {
+            unsafe {
+                SendMessageW(
+                    self.hwnd,
+                    WM_USER_WATCH_ICON_PATH,
+                    Box::into_raw(Box::new(None::<std::path::PathBuf>)) as _,
+                    0,
+                );
             }
+        }
+    }
 
-            if Shell_NotifyIconW(NIM_MODIFY, &mut nid as _) == 0 {
-                return Err(crate::Error::OsError(std::io::Error::last_os_error()));
+    pub fn set_attention(&mut self, config: Option<AttentionConfig>) -> crate::Result<()> {
+        let result = unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_SET_ATTENTION,
+                Box::into_raw(Box::new(config)) as _,
+                0,
+            )
+        };
+        lresult_to_result(result)
+    }
+
+    pub fn set_badge(&mut self, count: Option<u32>) -> crate::Result<()> {
+        let result = unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_UPDATE_BADGE,
+                Box::into_raw(Box::new(count)) as _,
+                0,
+            )
+        };
+        lresult_to_result(result)
+    }
+
+    pub fn show_notification(
+        &mut self,
+        id: NotificationId,
+        notification: Notification,
+    ) -> crate::Result<()> {
+        let result = unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_SHOW_NOTIFICATION,
+                Box::into_raw(Box::new((id, notification))) as _,
+                0,
+            )
+        };
+        if result == NOTIFICATION_QUEUE_FULL {
+            return Err(crate::Error::NotificationQueueFull);
+        }
+        lresult_to_result(result)
+    }
+
+    #[cfg(feature = "toast")]
+    pub fn show_toast(
+        &mut self,
+        tray_id: &TrayIconId,
+        id: NotificationId,
+        toast: &crate::Toast,
+    ) -> crate::Result<crate::ShownToast> {
+        if !self.toasts_unavailable {
+            match self.show_winrt_toast(tray_id, id, toast) {
+                Ok(()) => return Ok(crate::ShownToast::Toast(id)),
+                Err(err) if toast::is_unavailable(&err) => self.toasts_unavailable = true,
+                Err(err) => return Err(crate::Error::OsError(err.into())),
             }
+        }
+
+        let notification = toast.to_notification();
+        notification.validate()?;
+        self.show_notification(id, notification)?;
+        Ok(crate::ShownToast::Balloon(id))
+    }
 
-            // send the new icon to the subclass proc to store it in the tray data
+    #[cfg(feature = "toast")]
+    fn show_winrt_toast(
+        &mut self,
+        tray_id: &TrayIconId,
+        id: NotificationId,
+        toast: &crate::Toast,
+    ) -> windows::core::Result<()> {
+        let toaster = match &mut self.toaster {
+            Some(toaster) => toaster,
+            None => self.toaster.insert(toast::Toaster::new(tray_id)?),
+        };
+        toaster.show(id, toast)
+    }
+
+    pub fn clear_notification(&mut self) -> crate::Result<()> {
+        let result = unsafe { SendMessageW(self.hwnd, WM_USER_CLEAR_NOTIFICATION, 0, 0) };
+        lresult_to_result(result)
+    }
+
+    pub fn set_notification_queueing(&mut self, enable: bool) {
+        unsafe {
+            SendMessageW(self.hwnd, WM_USER_NOTIFICATION_QUEUEING, enable as _, 0);
+        }
+    }
+
+    pub fn set_notification_queue_limit(&mut self, max_len: usize, overflow: NotificationOverflow) {
+        unsafe {
             SendMessageW(
                 self.hwnd,
-                WM_USER_UPDATE_TRAYICON,
-                Box::into_raw(Box::new(icon)) as _,
+                WM_USER_NOTIFICATION_QUEUE_LIMIT,
+                Box::into_raw(Box::new((max_len, overflow))) as _,
                 0,
             );
         }
-
-        Ok(())
     }
 
     pub fn set_menu(&mut self, menu: Option<Box<dyn menu::ContextMenu>>) {
@@ -206,35 +1032,71 @@ impl TrayIcon {
     }
 
     pub fn set_tooltip<S: AsRef<str>>(&mut self, tooltip: Option<S>) -> crate::Result<()> {
+        // send the new tooltip to the subclass proc to store it in the tray data
+        // and update the displayed tooltip
+        let result = unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_UPDATE_TRAYTOOLTIP,
+                Box::into_raw(Box::new(tooltip.map(|t| t.as_ref().to_string()))) as _,
+                0,
+            )
+        };
+        lresult_to_result(result)
+    }
+
+    pub fn tooltip(&self) -> Option<String> {
+        let mut tooltip = None;
         unsafe {
-            let mut nid = NOTIFYICONDATAW {
-                uFlags: NIF_TIP,
-                hWnd: self.hwnd,
-                uID: self.internal_id,
-                ..std::mem::zeroed()
-            };
-            if let Some(tooltip) = &tooltip {
-                let tip = util::encode_wide(tooltip.as_ref());
-                #[allow(clippy::manual_memcpy)]
-                for i in 0..tip.len().min(128) {
-                    nid.szTip[i] = tip[i];
-                }
-            }
+            SendMessageW(
+                self.hwnd,
+                WM_USER_GET_TOOLTIP,
+                &mut tooltip as *mut _ as _,
+                0,
+            );
+        }
+        tooltip
+    }
 
-            if Shell_NotifyIconW(NIM_MODIFY, &mut nid as _) == 0 {
-                return Err(crate::Error::OsError(std::io::Error::last_os_error()));
-            }
+    pub fn window_handle(&self) -> isize {
+        self.hwnd as isize
+    }
+
+    pub fn proxy(&self) -> TrayProxy {
+        TrayProxy::new(self.hwnd, self.proxy_queue.clone())
+    }
 
-            // send the new tooltip to the subclass proc to store it in the tray data
+    pub fn set_tooltip_provider(&mut self, provider: Option<Box<dyn FnMut() -> String>>) {
+        unsafe {
             SendMessageW(
                 self.hwnd,
-                WM_USER_UPDATE_TRAYTOOLTIP,
-                Box::into_raw(Box::new(tooltip.map(|t| t.as_ref().to_string()))) as _,
+                WM_USER_SET_TOOLTIP_PROVIDER,
+                Box::into_raw(Box::new(provider)) as _,
                 0,
             );
         }
+    }
 
-        Ok(())
+    pub fn set_message_hook(&mut self, hook: Option<MessageHook>) {
+        unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_SET_MESSAGE_HOOK,
+                Box::into_raw(Box::new(hook)) as _,
+                0,
+            );
+        }
+    }
+
+    pub fn set_standard_tooltip(&mut self, enable: bool) -> crate::Result<()> {
+        let result =
+            unsafe { SendMessageW(self.hwnd, WM_USER_SET_STANDARD_TOOLTIP, enable as _, 0) };
+        lresult_to_result(result)
+    }
+
+    pub fn force_refresh(&mut self) -> crate::Result<()> {
+        let result = unsafe { SendMessageW(self.hwnd, WM_USER_FORCE_REFRESH, 0, 0) };
+        lresult_to_result(result)
     }
 
     pub fn set_show_menu_on_left_click(&mut self, enable: bool) {
@@ -248,8 +1110,127 @@ impl TrayIcon {
         }
     }
 
-    pub fn set_visible(&mut self, visible: bool) -> crate::Result<()> {
+    pub fn set_hover_events(&mut self, enable: bool) {
+        unsafe {
+            SendMessageW(self.hwnd, WM_USER_SET_HOVER_EVENTS, enable as usize, 0);
+        }
+    }
+
+    pub fn enable_file_drop(&mut self, enable: bool) -> crate::Result<()> {
+        let result = unsafe { SendMessageW(self.hwnd, WM_USER_SET_FILE_DROP, enable as _, 0) };
+        lresult_to_result(result)
+    }
+
+    /// Fails unless called on the thread the tray icon was created on.
+    fn check_thread(&self) -> crate::Result<()> {
+        if unsafe { GetWindowThreadProcessId(self.hwnd, ptr::null_mut()) != GetCurrentThreadId() } {
+            return Err(crate::Error::NotMainThread);
+        }
+        Ok(())
+    }
+
+    pub fn focus(&self) -> crate::Result<()> {
+        self.check_thread()?;
+        if !self.visible() {
+            return Err(crate::Error::OsError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "the tray icon is hidden",
+            )));
+        }
+
+        let mut nid = NOTIFYICONDATAW {
+            hWnd: self.hwnd,
+            uID: self.internal_id,
+            ..unsafe { std::mem::zeroed() }
+        };
+        if unsafe { util::notify_icon(NIM_SETFOCUS, &mut nid) } {
+            Ok(())
+        } else {
+            Err(crate::Error::OsError(std::io::Error::last_os_error()))
+        }
+    }
+
+    pub fn context_menu_at(&self, position: crate::dpi::Position) -> crate::Result<ContextMenu> {
+        self.check_thread()?;
+        let menu = self.menu.as_ref().ok_or(crate::Error::NoMenu)?;
+
+        // logical positions are taken to be on the monitor of the taskbar
+        let scale_factor = util::tray_dpi() as f64 / USER_DEFAULT_SCREEN_DPI as f64;
+        let position = position.to_physical::<i32>(scale_factor);
+        Ok(ContextMenu {
+            hwnd: self.hwnd,
+            menu: menu.hpopupmenu() as _,
+            x: position.x,
+            y: position.y,
+            flags: TPM_BOTTOMALIGN | TPM_LEFTALIGN,
+            exclude: None,
+        })
+    }
+
+    pub fn context_menu(&self) -> crate::Result<ContextMenu> {
+        self.check_thread()?;
+        let menu = self.menu.as_ref().ok_or(crate::Error::NoMenu)?;
+
+        let icon = get_tray_rect(self.internal_id, self.hwnd).ok_or_else(|| {
+            crate::Error::OsError(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "the tray icon has no position",
+            ))
+        })?;
+        let monitor = util::monitor_rect(&icon).unwrap_or(icon);
+        let anchor = menu_anchor(&icon, &monitor);
+        Ok(ContextMenu {
+            hwnd: self.hwnd,
+            menu: menu.hpopupmenu() as _,
+            x: anchor.x,
+            y: anchor.y,
+            flags: anchor.flags,
+            exclude: Some(icon),
+        })
+    }
+
+    pub fn set_menu_suppressed(&mut self, suppressed: bool) {
         unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_SET_MENU_SUPPRESSED,
+                suppressed as usize,
+                0,
+            );
+        }
+    }
+
+    pub fn update(&mut self, update: crate::TrayIconUpdate) -> crate::Result<()> {
+        let crate::TrayIconUpdate {
+            icon,
+            tooltip,
+            visible,
+            menu,
+        } = update;
+
+        let update = AttributeUpdate {
+            icon_source: icon.map(IconSource::Icon),
+            tooltip,
+            visible,
+        };
+        let result = unsafe {
+            SendMessageW(
+                self.hwnd,
+                WM_USER_UPDATE_ATTRIBUTES,
+                Box::into_raw(Box::new(update)) as _,
+                0,
+            )
+        };
+        lresult_to_result(result)?;
+
+        if let Some(menu) = menu {
+            self.set_menu(menu);
+        }
+        Ok(())
+    }
+
+    pub fn set_visible(&mut self, visible: bool) -> crate::Result<()> {
+        let result = unsafe {
             SendMessageW(
                 self.hwnd,
                 if visible {
@@ -259,21 +1240,62 @@ impl TrayIcon {
                 },
                 0,
                 0,
-            );
-        }
+            )
+        };
+        lresult_to_result(result)
+    }
 
-        Ok(())
+    pub fn visible(&self) -> bool {
+        unsafe { SendMessageW(self.hwnd, WM_USER_GET_VISIBLE, 0, 0) != 0 }
+    }
+
+    /// Removes the icon from the notification area, the window is destroyed once `self` is
+    /// dropped.
+    pub fn remove(mut self) -> crate::Result<()> {
+        // hidden icons aren't in the notification area
+        self.removed = !self.visible() || unsafe { remove_tray_icon(self.hwnd, self.internal_id) };
+        if self.removed {
+            Ok(())
+        } else {
+            Err(crate::Error::OsError(std::io::Error::last_os_error()))
+        }
     }
 
     pub fn rect(&self) -> Option<Rect> {
         get_tray_rect(self.internal_id, self.hwnd).map(Into::into)
     }
+
+    pub fn is_in_overflow(&self) -> Option<bool> {
+        let rect = get_tray_rect(self.internal_id, self.hwnd)?;
+        let (tray, overflow) = util::notification_area_rects();
+        in_overflow(&rect, tray.as_ref(), overflow.as_ref())
+    }
+
+    pub fn scale_factor(&self) -> Option<f64> {
+        let rect = get_tray_rect(self.internal_id, self.hwnd)?;
+        let center = PhysicalPosition::new(
+            (rect.left + rect.right) as f64 / 2.,
+            (rect.top + rect.bottom) as f64 / 2.,
+        );
+        Some(util::scale_factor_at(center))
+    }
 }
 
 impl Drop for TrayIcon {
     fn drop(&mut self) {
+        // stop the watcher thread before the window it posts to is destroyed
+        #[cfg(feature = "hot-reload")]
+        self.icon_watcher.take();
+
         unsafe {
-            remove_tray_icon(self.hwnd, self.internal_id);
+            wheel::unregister(self.hwnd);
+            let removed =
+                self.removed || !self.visible() || remove_tray_icon(self.hwnd, self.internal_id);
+            util::set_icon_guid(self.internal_id, None);
+            // the shell may still know the id of an icon it failed to delete
+            if removed {
+                icon_ids::release(self.internal_id);
+            }
 
             if let Some(menu) = &self.menu {
                 menu.detach_menu_subclass_from_hwnd(self.hwnd as _);
@@ -310,50 +1332,398 @@ unsafe extern "system" fn tray_proc(
 
     let userdata = &mut *(userdata_ptr);
 
-    match msg {
-        WM_DESTROY => {
-            drop(Box::from_raw(userdata_ptr));
-            return 0;
-        }
-        WM_USER_UPDATE_TRAYMENU => {
-            let hpopupmenu = Box::from_raw(wparam as *mut Option<isize>);
-            userdata.hpopupmenu = (*hpopupmenu).map(|h| h as *mut _);
-        }
-        WM_USER_UPDATE_TRAYICON => {
-            let icon = Box::from_raw(wparam as *mut Option<Icon>);
-            userdata.icon = *icon;
-        }
-        WM_USER_SHOW_TRAYICON => {
-            register_tray_icon(
-                userdata.hwnd,
-                userdata.internal_id,
-                &userdata.icon.as_ref().map(|i| i.inner.as_raw_handle()),
-                &userdata.tooltip,
-            );
+    #[cfg(feature = "tracing")]
+    let _span = tracing::trace_span!("tray_proc", uid = userdata.internal_id, msg, wparam, lparam)
+        .entered();
+
+    match msg {
+        WM_DESTROY => {
+            // proxies fail from now on, instead of posting to a window that is gone
+            userdata.proxy_queue.close();
+            // later messages like WM_NCDESTROY must not reach the freed tray data or the hook
+            util::set_window_long(hwnd, GWL_USERDATA, 0);
+            drop(Box::from_raw(userdata_ptr));
+            return 0;
+        }
+        WM_USER_UPDATE_TRAYMENU => {
+            let hpopupmenu = Box::from_raw(wparam as *mut Option<isize>);
+            userdata.hpopupmenu = (*hpopupmenu).map(|h| h as *mut _);
+        }
+        WM_USER_UPDATE_TRAYICON => {
+            let icon_source = Box::from_raw(wparam as *mut IconSource);
+            userdata.icon_source = *icon_source;
+
+            // setting an icon cancels the animation
+            if userdata.animation.take().is_some() {
+                KillTimer(hwnd, WM_USER_ANIMATION_TIMER_ID as _);
+            }
+
+            return userdata.update_icon();
+        }
+        WM_USER_UPDATE_ANIMATION => {
+            let animation = Box::from_raw(wparam as *mut Option<Vec<(Icon, Duration)>>);
+
+            KillTimer(hwnd, WM_USER_ANIMATION_TIMER_ID as _);
+            userdata.animation = match *animation {
+                Some(frames) if !frames.is_empty() => {
+                    let animation = Animation { frames, frame: 0 };
+                    animation.schedule_next_frame(hwnd);
+                    Some(animation)
+                }
+                _ => None,
+            };
+
+            return userdata.update_icon();
+        }
+        #[cfg(feature = "hot-reload")]
+        WM_USER_WATCH_ICON_PATH => {
+            let path = Box::from_raw(wparam as *mut Option<std::path::PathBuf>);
+            userdata.watched_icon_path = *path;
+            KillTimer(hwnd, WM_USER_HOT_RELOAD_TIMER_ID as _);
+        }
+        #[cfg(feature = "hot-reload")]
+        WM_USER_ICON_FILE_CHANGED => {
+            // restarting the timer on every change debounces bursts of writes
+            SetTimer(
+                hwnd,
+                WM_USER_HOT_RELOAD_TIMER_ID as _,
+                HOT_RELOAD_DEBOUNCE_MS,
+                Some(tray_timer_proc),
+            );
+        }
+        WM_USER_SET_ATTENTION => {
+            let config = Box::from_raw(wparam as *mut Option<AttentionConfig>);
+
+            KillTimer(hwnd, WM_USER_ATTENTION_TIMER_ID as _);
+            userdata.attention = (*config).map(|config| {
+                let interval = config
+                    .interval
+                    .as_millis()
+                    .clamp(USER_TIMER_MINIMUM as _, u32::MAX as _);
+                SetTimer(
+                    hwnd,
+                    WM_USER_ATTENTION_TIMER_ID as _,
+                    interval as _,
+                    Some(tray_timer_proc),
+                );
+                Attention {
+                    config,
+                    alternate: true,
+                    started: Instant::now(),
+                }
+            });
+
+            return userdata.update_icon();
+        }
+        WM_USER_UPDATE_BADGE => {
+            let badge = Box::from_raw(wparam as *mut Option<u32>);
+            userdata.badge = *badge;
+            return userdata.update_icon();
+        }
+        WM_SETTINGCHANGE if util::pcwstr_eq(lparam as _, "ImmersiveColorSet") => {
+            // also sent for other color changes, like the accent color
+            let theme = system_theme();
+            if theme != userdata.theme {
+                userdata.theme = theme;
+
+                // the taskbar switched between light and dark, swap the icon in place
+                if matches!(userdata.icon_source, IconSource::Pair { .. }) {
+                    userdata.update_icon();
+                }
+                TrayIconEvent::send(TrayIconEvent::ThemeChanged {
+                    id: userdata.id.clone(),
+                    theme,
+                    timestamp: util::message_timestamp(),
+                });
+            }
+        }
+        // the tray icon size changes with the DPI of the taskbar's monitor, which changes when
+        // its scaling does or the taskbar is moved to another monitor
+        WM_DPICHANGED | WM_DISPLAYCHANGE => {
+            userdata.update_icon();
+        }
+        WM_SETTINGCHANGE if wparam == SPI_SETWORKAREA as usize => {
+            userdata.update_icon();
+        }
+        WM_USER_SHOW_TRAYICON => {
+            if !userdata.visible {
+                let result = userdata.register();
+                if result != 0 {
+                    return result;
+                }
+                userdata.visible = true;
+            }
+            if let Some(overlay) = &userdata.file_drop {
+                overlay.track();
+            }
+        }
+        WM_USER_HIDE_TRAYICON => {
+            if userdata.visible {
+                remove_tray_icon(userdata.hwnd, userdata.internal_id);
+            }
+            userdata.visible = false;
+            if let Some(overlay) = &userdata.file_drop {
+                overlay.hide();
+            }
+        }
+        WM_USER_SET_FILE_DROP => {
+            if wparam == 0 {
+                userdata.file_drop = None;
+            } else if userdata.file_drop.is_none() {
+                userdata.file_drop = file_drop::DropOverlay::new(hwnd, userdata.internal_id);
+                return os_result(userdata.file_drop.is_some());
+            }
+        }
+        WM_USER_FILES_DROPPED => {
+            let (paths, point) = *Box::from_raw(wparam as *mut (Vec<PathBuf>, POINT));
+            let position = PhysicalPosition::new(point.x as f64, point.y as f64);
+            TrayIconEvent::send(TrayIconEvent::FilesDropped {
+                id: userdata.id.clone(),
+                paths,
+                position,
+                scale_factor: util::scale_factor_at(position),
+                timestamp: util::message_timestamp(),
+            });
+        }
+        WM_USER_UPDATE_TRAYTOOLTIP => {
+            let tooltip = Box::from_raw(wparam as *mut Option<String>);
+            return userdata.apply_update(AttributeUpdate {
+                icon_source: None,
+                tooltip: Some(*tooltip),
+                visible: None,
+            });
+        }
+        WM_USER_SET_MESSAGE_HOOK => {
+            let hook = Box::from_raw(wparam as *mut Option<MessageHook>);
+            userdata.message_hook = *hook;
+            userdata.message_hook_generation = userdata.message_hook_generation.wrapping_add(1);
+        }
+        WM_USER_SET_TOOLTIP_PROVIDER => {
+            let provider = Box::from_raw(wparam as *mut Option<Box<dyn FnMut() -> String>>);
+            userdata.tooltip_provider = *provider;
+            userdata.tooltip_provided_at = None;
+        }
+        WM_USER_SET_STANDARD_TOOLTIP => {
+            if userdata.standard_tooltip == (wparam != 0) {
+                return os_result(true);
+            }
+            userdata.standard_tooltip = wparam != 0;
+            if !userdata.visible {
+                return os_result(true);
+            }
+
+            let mut nid = NOTIFYICONDATAW {
+                uFlags: tooltip_flags(userdata.standard_tooltip),
+                hWnd: hwnd,
+                uID: userdata.internal_id,
+                ..std::mem::zeroed()
+            };
+            if let Some(tooltip) = &userdata.tooltip {
+                nid.szTip = encode_tooltip(tooltip);
+            }
+            return os_result(util::notify_icon(NIM_MODIFY, &mut nid));
+        }
+        _ if msg == *S_U_TASKBAR_RESTART => {
+            // the taskbar may now live on a monitor with a different DPI or theme
+            userdata.icon = userdata.resolve_icon();
+
+            // hidden icons stay hidden, icons the new taskbar rejects are reported as hidden
+            if userdata.visible {
+                remove_tray_icon(userdata.hwnd, userdata.internal_id);
+                userdata.visible = userdata.register() == 0;
+            }
+            if let Some(overlay) = &userdata.file_drop {
+                overlay.track();
+            }
+
+            TrayIconEvent::send(TrayIconEvent::Recreated {
+                id: userdata.id.clone(),
+                timestamp: util::message_timestamp(),
+            });
+        }
+        WM_USER_UPDATE_ATTRIBUTES => {
+            let update = Box::from_raw(wparam as *mut AttributeUpdate);
+            return userdata.apply_update(*update);
+        }
+        WM_USER_GET_VISIBLE => return userdata.visible as LRESULT,
+        // sends the icon and tooltip again, even though they didn't change
+        WM_USER_FORCE_REFRESH => {
+            return os_result(!userdata.visible || userdata.modify(true, true));
+        }
+        WM_USER_GET_TOOLTIP => {
+            let tooltip = &mut *(wparam as *mut Option<String>);
+            // tooltips of the tooltip provider aren't reported
+            if userdata.tooltip_provider.is_none() {
+                tooltip.clone_from(&userdata.tooltip);
+            }
+        }
+        WM_USER_GET_ICON => {
+            let icon = &mut *(wparam as *mut Option<Icon>);
+            icon.clone_from(&userdata.icon);
+        }
+        WM_USER_SHOW_MENU_ON_LEFT_CLICK => {
+            userdata.menu_on_left_click = wparam != 0;
+        }
+        WM_USER_SET_MENU_SUPPRESSED => {
+            userdata.menu_suppressed = wparam != 0;
+        }
+        WM_USER_SET_HOVER_EVENTS => {
+            userdata.hover_events = wparam != 0;
+            if !userdata.hover_events {
+                // stop tracking the cursor, hovering starts over with an Enter when enabled again
+                KillTimer(hwnd, WM_USER_LEAVE_TIMER_ID as _);
+                KillTimer(hwnd, WM_USER_MOVE_TIMER_ID as _);
+                KillTimer(hwnd, WM_USER_HOVER_DWELL_TIMER_ID as _);
+                userdata.entered = false;
+                userdata.last_position = None;
+                userdata.move_filter.take_pending(Instant::now());
+            }
+        }
+        WM_USER_SHOW_NOTIFICATION => {
+            let notification = Box::from_raw(wparam as *mut (NotificationId, Notification));
+            let (id, notification) = *notification;
+            return userdata.queue_notification(id, notification);
+        }
+        WM_USER_PROXY_COMMAND => {
+            userdata.run_proxy_commands();
+            return 0;
+        }
+        WM_USER_CLEAR_NOTIFICATION => {
+            KillTimer(hwnd, WM_USER_NOTIFICATION_TIMER_ID as _);
+            userdata.notification_queue.clear();
+            userdata.notification_queue.showing = false;
+            userdata.current_notification = None;
+            return os_result(notification::clear(hwnd, userdata.internal_id));
+        }
+        WM_USER_NOTIFICATION_QUEUEING => {
+            let queue = &mut userdata.notification_queue;
+            queue.enabled = wparam != 0;
+            if !queue.enabled {
+                queue.clear();
+            }
+        }
+        WM_USER_NOTIFICATION_QUEUE_LIMIT => {
+            let limit = Box::from_raw(wparam as *mut (usize, NotificationOverflow));
+            let (max_len, overflow) = *limit;
+            userdata.notification_queue.set_limit(max_len, overflow);
+        }
+
+        WM_USER_TRAYICON
+            if matches!(
+                tray_event(lparam),
+                NIN_BALLOONSHOW | NIN_BALLOONUSERCLICK | NIN_BALLOONHIDE | NIN_BALLOONTIMEOUT
+            ) =>
+        {
+            // balloons of cleared notifications aren't reported
+            let Some(notification) = userdata.current_notification else {
+                return 0;
+            };
+
+            let id = userdata.id.clone();
+            let timestamp = util::message_timestamp();
+            let event = match tray_event(lparam) {
+                NIN_BALLOONSHOW => TrayIconEvent::NotificationShown {
+                    id,
+                    notification,
+                    timestamp,
+                },
+                NIN_BALLOONUSERCLICK => TrayIconEvent::NotificationClicked {
+                    id,
+                    notification,
+                    timestamp,
+                },
+                NIN_BALLOONHIDE => TrayIconEvent::NotificationDismissed {
+                    id,
+                    notification,
+                    timestamp,
+                },
+                NIN_BALLOONTIMEOUT => TrayIconEvent::NotificationTimedOut {
+                    id,
+                    notification,
+                    timestamp,
+                },
+                _ => unreachable!(),
+            };
+
+            TrayIconEvent::send(event);
+
+            KillTimer(hwnd, WM_USER_NOTIFICATION_TIMER_ID as _);
+            if tray_event(lparam) != NIN_BALLOONSHOW {
+                userdata.show_next_notification();
+            }
         }
-        WM_USER_HIDE_TRAYICON => {
-            remove_tray_icon(userdata.hwnd, userdata.internal_id);
+
+        WM_USER_MOUSE_WHEEL => {
+            let (x, y) = wheel::unpack_point(lparam);
+            let Some(rect) = get_tray_rect(userdata.internal_id, hwnd) else {
+                return 0;
+            };
+            if x < rect.left || x >= rect.right || y < rect.top || y >= rect.bottom {
+                return 0;
+            }
+
+            let position = PhysicalPosition::new(x as f64, y as f64);
+            TrayIconEvent::send(TrayIconEvent::Wheel {
+                id: userdata.id.clone(),
+                delta: wparam as i16 as f64 / WHEEL_DELTA as f64,
+                position,
+                scale_factor: util::scale_factor_at(position),
+                rect: Rect::from(rect),
+                timestamp: util::message_timestamp(),
+            });
         }
-        WM_USER_UPDATE_TRAYTOOLTIP => {
-            let tooltip = Box::from_raw(wparam as *mut Option<String>);
-            userdata.tooltip = *tooltip;
+
+        WM_USER_TRAYICON if matches!(tray_event(lparam), NIN_SELECT | NIN_KEYSELECT) => {
+            let Some(rect) = get_tray_rect(userdata.internal_id, hwnd) else {
+                return 0;
+            };
+
+            userdata.stop_attention();
+
+            let keyboard = tray_event(lparam) == NIN_KEYSELECT;
+            TrayIconEvent::send(TrayIconEvent::Select {
+                id: userdata.id.clone(),
+                rect: Rect::from(rect),
+                keyboard,
+                timestamp: util::message_timestamp(),
+            });
+
+            // mouse clicks already requested the menu on WM_LBUTTONUP, for the keyboard
+            // it is anchored to the icon since the cursor may be anywhere
+            if keyboard && userdata.menu_on_left_click {
+                userdata.request_menu(
+                    ContextMenuSource::Keyboard,
+                    rect.left,
+                    rect.top,
+                    Rect::from(rect),
+                );
+            }
         }
-        _ if msg == *S_U_TASKBAR_RESTART => {
-            remove_tray_icon(userdata.hwnd, userdata.internal_id);
-            register_tray_icon(
-                userdata.hwnd,
-                userdata.internal_id,
-                &userdata.icon.as_ref().map(|i| i.inner.as_raw_handle()),
-                &userdata.tooltip,
+
+        // sent after right clicks and when the menu key or Shift+F10 is pressed on the icon
+        WM_USER_TRAYICON if tray_event(lparam) == WM_CONTEXTMENU => {
+            if std::mem::take(&mut userdata.right_click_menu) {
+                return 0;
+            }
+            let Some(rect) = get_tray_rect(userdata.internal_id, hwnd) else {
+                return 0;
+            };
+            userdata.request_menu(
+                ContextMenuSource::Keyboard,
+                rect.left,
+                rect.top,
+                Rect::from(rect),
             );
         }
-        WM_USER_SHOW_MENU_ON_LEFT_CLICK => {
-            userdata.menu_on_left_click = wparam != 0;
+
+        // only sent when the standard tooltip is turned off
+        WM_USER_TRAYICON if tray_event(lparam) == NIN_POPUPOPEN => {
+            userdata.provide_tooltip();
         }
 
         WM_USER_TRAYICON
             if matches!(
-                lparam as u32,
+                tray_event(lparam),
                 WM_LBUTTONDOWN
                     | WM_RBUTTONDOWN
                     | WM_MBUTTONDOWN
@@ -363,9 +1733,20 @@ unsafe extern "system" fn tray_proc(
                     | WM_LBUTTONDBLCLK
                     | WM_RBUTTONDBLCLK
                     | WM_MBUTTONDBLCLK
+                    | WM_XBUTTONDOWN
+                    | WM_XBUTTONUP
+                    | WM_XBUTTONDBLCLK
                     | WM_MOUSEMOVE
             ) =>
         {
+            // the standard tooltip shows after hovering for a while, so there's time to update it
+            if tray_event(lparam) == WM_MOUSEMOVE {
+                userdata.provide_tooltip();
+            }
+            if hover::skips(userdata.hover_events, tray_event(lparam)) {
+                return 0;
+            }
+
             let mut cursor = POINT { x: 0, y: 0 };
             if GetCursorPos(&mut cursor as _) == 0 {
                 return 0;
@@ -373,17 +1754,28 @@ unsafe extern "system" fn tray_proc(
 
             let id = userdata.id.clone();
             let position = PhysicalPosition::new(cursor.x as f64, cursor.y as f64);
+            let scale_factor = util::scale_factor_at(position);
+            let timestamp = util::message_timestamp();
 
             let rect = match get_tray_rect(userdata.internal_id, hwnd) {
                 Some(rect) => Rect::from(rect),
                 None => return 0,
             };
 
-            let event = match lparam as u32 {
+            // with NOTIFYICON_VERSION_4 `wparam` holds the anchor point rather than the
+            // pressed X button, so it is read from the button state and kept for the button up
+            if matches!(tray_event(lparam), WM_XBUTTONDOWN | WM_XBUTTONDBLCLK) {
+                userdata.x_button = pressed_x_button();
+            }
+
+            let event = match tray_event(lparam) {
                 WM_LBUTTONDOWN => TrayIconEvent::Click {
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Left,
                     button_state: MouseButtonState::Down,
                 },
@@ -391,6 +1783,9 @@ unsafe extern "system" fn tray_proc(
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Right,
                     button_state: MouseButtonState::Down,
                 },
@@ -398,13 +1793,29 @@ unsafe extern "system" fn tray_proc(
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Middle,
                     button_state: MouseButtonState::Down,
                 },
+                WM_XBUTTONDOWN => TrayIconEvent::Click {
+                    id,
+                    rect,
+                    position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
+                    button: userdata.x_button,
+                    button_state: MouseButtonState::Down,
+                },
                 WM_LBUTTONUP => TrayIconEvent::Click {
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Left,
                     button_state: MouseButtonState::Up,
                 },
@@ -412,6 +1823,9 @@ unsafe extern "system" fn tray_proc(
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Right,
                     button_state: MouseButtonState::Up,
                 },
@@ -419,30 +1833,75 @@ unsafe extern "system" fn tray_proc(
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Middle,
                     button_state: MouseButtonState::Up,
                 },
+                WM_XBUTTONUP => TrayIconEvent::Click {
+                    id,
+                    rect,
+                    position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
+                    button: userdata.x_button,
+                    button_state: MouseButtonState::Up,
+                },
                 WM_LBUTTONDBLCLK => TrayIconEvent::DoubleClick {
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Left,
                 },
                 WM_RBUTTONDBLCLK => TrayIconEvent::DoubleClick {
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Right,
                 },
                 WM_MBUTTONDBLCLK => TrayIconEvent::DoubleClick {
                     id,
                     rect,
                     position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
                     button: MouseButton::Middle,
                 },
+                WM_XBUTTONDBLCLK => TrayIconEvent::DoubleClick {
+                    id,
+                    rect,
+                    position,
+                    scale_factor,
+                    timestamp,
+                    monitor: util::monitor_of(&rect),
+                    button: userdata.x_button,
+                },
                 WM_MOUSEMOVE if !userdata.entered => {
                     userdata.entered = true;
-                    TrayIconEvent::Enter { id, rect, position }
+                    if let Some(dwell) = userdata.hover_dwell {
+                        SetTimer(
+                            hwnd,
+                            WM_USER_HOVER_DWELL_TIMER_ID as _,
+                            dwell.as_millis() as u32,
+                            Some(tray_timer_proc),
+                        );
+                    }
+                    TrayIconEvent::Enter {
+                        id,
+                        rect,
+                        position,
+                        scale_factor,
+                        timestamp,
+                    }
                 }
                 WM_MOUSEMOVE if userdata.entered => {
                     // handle extra WM_MOUSEMOVE events, ignore if position hasn't changed
@@ -452,7 +1911,27 @@ unsafe extern "system" fn tray_proc(
                         // Set or update existing timer, where we check if cursor left
                         SetTimer(hwnd, WM_USER_LEAVE_TIMER_ID as _, 15, Some(tray_timer_proc));
 
-                        TrayIconEvent::Move { id, rect, position }
+                        match userdata.move_filter.on_move(Instant::now(), position, rect) {
+                            hover::MoveAction::Send => {}
+                            hover::MoveAction::Delay(delay) => {
+                                SetTimer(
+                                    hwnd,
+                                    WM_USER_MOVE_TIMER_ID as _,
+                                    delay.as_millis() as u32,
+                                    Some(tray_timer_proc),
+                                );
+                                return 0;
+                            }
+                            hover::MoveAction::Drop => return 0,
+                        }
+
+                        TrayIconEvent::Move {
+                            id,
+                            rect,
+                            position,
+                            scale_factor,
+                            timestamp,
+                        }
                     } else {
                         return 0;
                     }
@@ -461,15 +1940,150 @@ unsafe extern "system" fn tray_proc(
                 _ => unreachable!(),
             };
 
-            TrayIconEvent::send(event);
+            if matches!(
+                event,
+                TrayIconEvent::Click { .. } | TrayIconEvent::DoubleClick { .. }
+            ) {
+                userdata.stop_attention();
+            }
 
-            if lparam as u32 == WM_RBUTTONDOWN
-                || (userdata.menu_on_left_click && lparam as u32 == WM_LBUTTONDOWN)
+            match &mut userdata.double_click_filter {
+                Some(filter) => {
+                    let down = matches!(
+                        event,
+                        TrayIconEvent::Click {
+                            button_state: MouseButtonState::Down,
+                            ..
+                        }
+                    );
+                    let events = filter.on_click(event);
+                    if !filter.holding() {
+                        KillTimer(hwnd, WM_USER_DOUBLE_CLICK_TIMER_ID as _);
+                    } else if down {
+                        SetTimer(
+                            hwnd,
+                            WM_USER_DOUBLE_CLICK_TIMER_ID as _,
+                            GetDoubleClickTime(),
+                            Some(tray_timer_proc),
+                        );
+                    }
+                    for event in events {
+                        TrayIconEvent::send(event);
+                    }
+                }
+                None => TrayIconEvent::send(event),
+            }
+
+            // the click was sent above, so it comes before the menu
+            if let Some(source) = menu_source(tray_event(lparam), userdata.menu_on_left_click) {
+                userdata.right_click_menu = source == ContextMenuSource::RightClick;
+                userdata.request_menu(source, cursor.x, cursor.y, rect);
+            }
+        }
+
+        #[cfg(feature = "hot-reload")]
+        WM_TIMER if wparam as u32 == WM_USER_HOT_RELOAD_TIMER_ID => {
+            KillTimer(hwnd, WM_USER_HOT_RELOAD_TIMER_ID as _);
+
+            // keep the current icon if the new file can't be loaded, e.g. while it is being written
+            if let Some(Ok(icon)) = userdata
+                .watched_icon_path
+                .as_ref()
+                .map(|path| Icon::from_path(path, None))
             {
-                if let Some(menu) = userdata.hpopupmenu {
-                    show_tray_menu(hwnd, menu, cursor.x, cursor.y);
+                userdata.icon_source = IconSource::Icon(Some(icon));
+                if userdata.animation.take().is_some() {
+                    KillTimer(hwnd, WM_USER_ANIMATION_TIMER_ID as _);
+                }
+                userdata.update_icon();
+            }
+
+            return 0;
+        }
+
+        WM_TIMER if wparam as u32 == WM_USER_NOTIFICATION_TIMER_ID => {
+            KillTimer(hwnd, WM_USER_NOTIFICATION_TIMER_ID as _);
+
+            // the shell held the balloon back, e.g. during quiet time or because of Focus Assist
+            if let Some(notification) = userdata.current_notification {
+                TrayIconEvent::send(TrayIconEvent::NotificationSuppressed {
+                    id: userdata.id.clone(),
+                    notification,
+                    reason: SuppressionReason::NotShown,
+                    timestamp: util::message_timestamp(),
+                });
+            }
+            userdata.show_next_notification();
+
+            return 0;
+        }
+
+        WM_TIMER if wparam as u32 == WM_USER_ANIMATION_TIMER_ID => {
+            if let Some(animation) = &mut userdata.animation {
+                animation.frame = (animation.frame + 1) % animation.frames.len();
+                animation.schedule_next_frame(hwnd);
+                userdata.update_icon();
+            }
+
+            return 0;
+        }
+
+        WM_TIMER if wparam as u32 == WM_USER_ATTENTION_TIMER_ID => {
+            if let Some(attention) = &mut userdata.attention {
+                let expired = attention
+                    .config
+                    .max_duration
+                    .is_some_and(|max| attention.started.elapsed() >= max);
+                if expired {
+                    userdata.stop_attention();
+                } else {
+                    attention.alternate = !attention.alternate;
+                    userdata.update_icon();
+                }
+            }
+
+            return 0;
+        }
+
+        WM_TIMER if wparam as u32 == WM_USER_MOVE_TIMER_ID => {
+            KillTimer(hwnd, WM_USER_MOVE_TIMER_ID as _);
+            userdata.send_pending_move();
+
+            return 0;
+        }
+
+        WM_TIMER if wparam as u32 == WM_USER_HOVER_DWELL_TIMER_ID => {
+            KillTimer(hwnd, WM_USER_HOVER_DWELL_TIMER_ID as _);
+
+            let mut cursor = POINT { x: 0, y: 0 };
+            if !userdata.entered || GetCursorPos(&mut cursor as _) == 0 {
+                return 0;
+            }
+            let Some(rect) = get_tray_rect(userdata.internal_id, hwnd) else {
+                return 0;
+            };
+
+            let position = PhysicalPosition::new(cursor.x as f64, cursor.y as f64);
+            TrayIconEvent::send(TrayIconEvent::HoverDwell {
+                id: userdata.id.clone(),
+                position,
+                scale_factor: util::scale_factor_at(position),
+                rect: rect.into(),
+                timestamp: util::message_timestamp(),
+            });
+
+            return 0;
+        }
+
+        WM_TIMER if wparam as u32 == WM_USER_DOUBLE_CLICK_TIMER_ID => {
+            KillTimer(hwnd, WM_USER_DOUBLE_CLICK_TIMER_ID as _);
+            if let Some(filter) = &mut userdata.double_click_filter {
+                for event in filter.take_held() {
+                    TrayIconEvent::send(event);
                 }
             }
+
+            return 0;
         }
 
         WM_TIMER if wparam as u32 == WM_USER_LEAVE_TIMER_ID => {
@@ -489,12 +2103,19 @@ unsafe extern "system" fn tray_proc(
 
                 if !in_x || !in_y {
                     KillTimer(hwnd, WM_USER_LEAVE_TIMER_ID as _);
+                    KillTimer(hwnd, WM_USER_HOVER_DWELL_TIMER_ID as _);
                     userdata.entered = false;
 
+                    // throttled moves still deliver the final position before leaving
+                    KillTimer(hwnd, WM_USER_MOVE_TIMER_ID as _);
+                    userdata.send_pending_move();
+
                     TrayIconEvent::send(TrayIconEvent::Leave {
                         id: userdata.id.clone(),
                         rect: rect.into(),
                         position,
+                        scale_factor: util::scale_factor_at(position),
+                        timestamp: util::message_timestamp(),
                     });
                 }
             }
@@ -502,7 +2123,14 @@ unsafe extern "system" fn tray_proc(
             return 0;
         }
 
-        _ => {}
+        // tray icon callbacks are the tray icon's own, even those it ignores
+        WM_USER_TRAYICON => {}
+
+        _ => {
+            if let Some(result) = call_message_hook(hwnd, msg, wparam, lparam) {
+                return result;
+            }
+        }
     }
 
     DefWindowProcW(hwnd, msg, wparam, lparam)
@@ -512,33 +2140,88 @@ unsafe extern "system" fn tray_timer_proc(hwnd: HWND, msg: u32, wparam: WPARAM,
     tray_proc(hwnd, msg, wparam, lparam as _);
 }
 
+/// A tray menu ready to be shown, holding no borrow of the tray icon so handlers called
+/// while the menu is open can still change it.
+pub struct ContextMenu {
+    hwnd: HWND,
+    menu: HMENU,
+    x: i32,
+    y: i32,
+    flags: TRACK_POPUP_MENU_FLAGS,
+    exclude: Option<RECT>,
+}
+
+impl ContextMenu {
+    /// Shows the menu, returning once it is closed.
+    pub fn show(self) {
+        unsafe {
+            show_tray_menu(
+                self.hwnd,
+                self.menu,
+                self.x,
+                self.y,
+                self.flags,
+                self.exclude,
+            )
+        };
+    }
+}
+
+thread_local! {
+    /// Whether a tray menu is open on this thread, menus are modal so showing another one
+    /// from a handler running meanwhile would nest them.
+    static MENU_OPEN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Shows `menu` at `x`, `y`, keeping it clear of `exclude` when it has to be moved to fit
+/// on the screen.
 #[inline]
-unsafe fn show_tray_menu(hwnd: HWND, menu: HMENU, x: i32, y: i32) {
+unsafe fn show_tray_menu(
+    hwnd: HWND,
+    menu: HMENU,
+    x: i32,
+    y: i32,
+    flags: TRACK_POPUP_MENU_FLAGS,
+    exclude: Option<RECT>,
+) {
+    if MENU_OPEN.replace(true) {
+        return;
+    }
+
+    let params = exclude.map(|rect| TPMPARAMS {
+        cbSize: std::mem::size_of::<TPMPARAMS>() as u32,
+        rcExclude: rect,
+    });
+
     // bring the hidden window to the foreground so the pop up menu
     // would automatically hide on click outside
     SetForegroundWindow(hwnd);
-    TrackPopupMenu(
+    TrackPopupMenuEx(
         menu,
-        // align bottom / right, maybe we could expose this later..
-        TPM_BOTTOMALIGN | TPM_LEFTALIGN,
+        flags,
         x,
         y,
-        0,
         hwnd,
-        std::ptr::null_mut(),
+        params
+            .as_ref()
+            .map_or(ptr::null(), |params| params as *const _),
     );
+
+    MENU_OPEN.set(false);
 }
 
+/// Adds the tray icon to the notification area, returning the result for `SendMessageW`.
 #[inline]
 unsafe fn register_tray_icon(
     hwnd: HWND,
     tray_id: u32,
     hicon: &Option<HICON>,
     tooltip: &Option<String>,
-) -> bool {
+    standard_tooltip: bool,
+) -> LRESULT {
     let mut h_icon = std::ptr::null_mut();
     let mut flags = NIF_MESSAGE;
-    let mut sz_tip: [u16; 128] = [0; 128];
+    let mut sz_tip = [0; 128];
 
     if let Some(hicon) = hicon {
         flags |= NIF_ICON;
@@ -546,12 +2229,8 @@ unsafe fn register_tray_icon(
     }
 
     if let Some(tooltip) = tooltip {
-        flags |= NIF_TIP;
-        let tip = util::encode_wide(tooltip);
-        #[allow(clippy::manual_memcpy)]
-        for i in 0..tip.len().min(128) {
-            sz_tip[i] = tip[i];
-        }
+        flags |= tooltip_flags(standard_tooltip);
+        sz_tip = encode_tooltip(tooltip);
     }
 
     let mut nid = NOTIFYICONDATAW {
@@ -564,11 +2243,122 @@ unsafe fn register_tray_icon(
         ..std::mem::zeroed()
     };
 
-    Shell_NotifyIconW(NIM_ADD, &mut nid as _) == TRUE
+    if !util::notify_icon(NIM_ADD, &mut nid) {
+        let result = os_result(false);
+        // a GUID used by another executable path makes the shell reject the icon
+        if util::icon_guid(tray_id).is_some() && util::accepts_without_guid(&nid) {
+            return GUID_REJECTED;
+        }
+        return result;
+    }
+
+    // shells without version 4 keep the default behavior, which only lacks large notification icons
+    nid.Anonymous.uVersion = NOTIFYICON_VERSION_4;
+    VERSION_4_SUPPORTED.store(
+        util::notify_icon(NIM_SETVERSION, &mut nid),
+        Ordering::Relaxed,
+    );
+
+    0
+}
+
+/// Returns the handle of `icon`, which identical icons share.
+fn icon_handle(icon: &Option<Icon>) -> Option<HICON> {
+    icon.as_ref().map(|icon| icon.inner.as_raw_handle())
+}
+
+/// Returns the flags for sending a tooltip, version 4 icons only show the standard tooltip
+/// with `NIF_SHOWTIP`.
+#[inline]
+fn tooltip_flags(standard_tooltip: bool) -> NOTIFY_ICON_DATA_FLAGS {
+    if standard_tooltip {
+        NIF_TIP | NIF_SHOWTIP
+    } else {
+        NIF_TIP
+    }
+}
+
+/// Returns whether the tooltip provider last called at `provided_at` may be called again.
+#[inline]
+fn provider_due(provided_at: Option<Instant>, now: Instant) -> bool {
+    provided_at.is_none_or(|at| now.duration_since(at) >= TOOLTIP_PROVIDER_INTERVAL)
+}
+
+/// Encodes `tooltip` for `NOTIFYICONDATAW::szTip`, truncating it without splitting
+/// surrogate pairs or emoji sequences when it is too long.
+fn encode_tooltip(tooltip: &str) -> [u16; 128] {
+    let mut sz_tip = [0; 128];
+    let tooltip = crate::tooltip::truncate(tooltip);
+    for (unit, c) in sz_tip.iter_mut().zip(tooltip.encode_utf16()) {
+        *unit = c;
+    }
+    sz_tip
+}
+
+/// Returns which X button is held down, [`MouseButton::Back`] unless the second one is.
+#[inline]
+unsafe fn pressed_x_button() -> MouseButton {
+    // the high bit is set while the button is down
+    if GetAsyncKeyState(VK_XBUTTON2 as i32) < 0 {
+        MouseButton::Forward
+    } else {
+        MouseButton::Back
+    }
+}
+
+pub(crate) fn system_theme() -> Theme {
+    if util::system_uses_light_theme() {
+        Theme::Light
+    } else {
+        Theme::Dark
+    }
+}
+
+/// Returns the event of a tray icon callback message, with `NOTIFYICON_VERSION_4`
+/// the high word of `lparam` holds the icon id.
+#[inline]
+fn tray_event(lparam: LPARAM) -> u32 {
+    (lparam & 0xFFFF) as u32
+}
+
+/// Calls the message hook of the tray window `hwnd` with a message it doesn't handle itself.
+///
+/// The hook may destroy the window, which frees the tray data, so no reference to it is held
+/// while the hook runs.
+unsafe fn call_message_hook(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> Option<LRESULT> {
+    let userdata = util::get_window_long(hwnd, GWL_USERDATA) as *mut TrayUserData;
+    // taken out while it runs, since it may call back into the tray icon
+    let mut hook = (*userdata).message_hook.take()?;
+    let generation = (*userdata).message_hook_generation;
+
+    let result =
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| hook(msg, wparam, lparam)));
+
+    // the tray data is gone once the window was destroyed
+    let userdata = util::get_window_long(hwnd, GWL_USERDATA) as *mut TrayUserData;
+    match result {
+        Ok(result) => {
+            if !userdata.is_null() && (*userdata).message_hook_generation == generation {
+                (*userdata).message_hook = Some(hook);
+            }
+            result
+        }
+        // unwinding into the system is undefined behavior, a panicking hook is dropped
+        Err(_) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(msg, "tray icon message hook panicked and was removed");
+            None
+        }
+    }
 }
 
 #[inline]
-unsafe fn remove_tray_icon(hwnd: HWND, id: u32) {
+unsafe fn remove_tray_icon(hwnd: HWND, id: u32) -> bool {
     let mut nid = NOTIFYICONDATAW {
         uFlags: NIF_ICON,
         hWnd: hwnd,
@@ -576,9 +2366,94 @@ unsafe fn remove_tray_icon(hwnd: HWND, id: u32) {
         ..std::mem::zeroed()
     };
 
-    if Shell_NotifyIconW(NIM_DELETE, &mut nid as _) == FALSE {
+    let removed = util::notify_icon(NIM_DELETE, &mut nid);
+    if !removed {
         eprintln!("Error removing system tray icon");
     }
+    removed
+}
+
+/// Returns whether the tray icon at `icon` is in the `overflow` flyout rather than the
+/// notification area on the taskbar at `tray`, or `None` if it is in neither.
+fn in_overflow(icon: &RECT, tray: Option<&RECT>, overflow: Option<&RECT>) -> Option<bool> {
+    let center = POINT {
+        x: icon.left + (icon.right - icon.left) / 2,
+        y: icon.top + (icon.bottom - icon.top) / 2,
+    };
+    let contains = |rect: &RECT| {
+        (rect.left..rect.right).contains(&center.x) && (rect.top..rect.bottom).contains(&center.y)
+    };
+
+    if tray.is_some_and(contains) {
+        Some(false)
+    } else if overflow.is_some_and(contains) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// Returns what requests the menu with the tray icon callback `event`, if anything.
+///
+/// Right clicks do so on the button down, left clicks on the button up, when enabled.
+fn menu_source(event: u32, menu_on_left_click: bool) -> Option<ContextMenuSource> {
+    match event {
+        WM_RBUTTONDOWN => Some(ContextMenuSource::RightClick),
+        WM_LBUTTONUP if menu_on_left_click => Some(ContextMenuSource::LeftClick),
+        _ => None,
+    }
+}
+
+/// Where and how a menu attached to a tray icon is shown.
+#[derive(Debug, PartialEq, Eq)]
+struct MenuAnchor {
+    x: i32,
+    y: i32,
+    flags: TRACK_POPUP_MENU_FLAGS,
+}
+
+/// Anchors a menu to the side of the `icon` facing away from the screen edge the taskbar
+/// is at, which is taken to be the edge of `monitor` closest to the icon.
+fn menu_anchor(icon: &RECT, monitor: &RECT) -> MenuAnchor {
+    let to_left = icon.left - monitor.left;
+    let to_right = monitor.right - icon.right;
+    let to_top = icon.top - monitor.top;
+    let to_bottom = monitor.bottom - icon.bottom;
+
+    if to_top.min(to_bottom) <= to_left.min(to_right) {
+        // a horizontal taskbar, the menu opens away from the closer side of the screen
+        let (x, horizontal) = if to_left <= to_right {
+            (icon.left, TPM_LEFTALIGN)
+        } else {
+            (icon.right, TPM_RIGHTALIGN)
+        };
+        let (y, vertical) = if to_bottom <= to_top {
+            (icon.top, TPM_BOTTOMALIGN)
+        } else {
+            (icon.bottom, TPM_TOPALIGN)
+        };
+        MenuAnchor {
+            x,
+            y,
+            flags: horizontal | vertical | TPM_VERTICAL,
+        }
+    } else {
+        let (x, horizontal) = if to_left <= to_right {
+            (icon.right, TPM_LEFTALIGN)
+        } else {
+            (icon.left, TPM_RIGHTALIGN)
+        };
+        let (y, vertical) = if to_top <= to_bottom {
+            (icon.top, TPM_TOPALIGN)
+        } else {
+            (icon.bottom, TPM_BOTTOMALIGN)
+        };
+        MenuAnchor {
+            x,
+            y,
+            flags: horizontal | vertical,
+        }
+    }
 }
 
 #[inline]
@@ -587,7 +2462,7 @@ fn get_tray_rect(id: u32, hwnd: HWND) -> Option<RECT> {
         hWnd: hwnd,
         cbSize: std::mem::size_of::<NOTIFYICONIDENTIFIER>() as _,
         uID: id,
-        ..unsafe { std::mem::zeroed() }
+        guidItem: util::icon_guid(id).unwrap_or(unsafe { std::mem::zeroed() }),
     };
 
     let mut rect = RECT {
@@ -603,6 +2478,34 @@ fn get_tray_rect(id: u32, hwnd: HWND) -> Option<RECT> {
     }
 }
 
+/// Converts the outcome of a shell call made in the subclass proc into the `LRESULT`
+/// returned to `SendMessageW`, `0` on success or the last OS error code otherwise.
+#[inline]
+fn os_result(success: bool) -> LRESULT {
+    if success {
+        0
+    } else {
+        std::io::Error::last_os_error()
+            .raw_os_error()
+            .filter(|&code| code != 0)
+            .unwrap_or(-1) as LRESULT
+    }
+}
+
+/// The counterpart of [`os_result`], converts the `LRESULT` returned by `SendMessageW`.
+#[inline]
+fn lresult_to_result(result: LRESULT) -> crate::Result<()> {
+    if result == 0 {
+        Ok(())
+    } else if result == GUID_REJECTED {
+        Err(crate::Error::GuidRejected)
+    } else {
+        Err(crate::Error::OsError(std::io::Error::from_raw_os_error(
+            result as i32,
+        )))
+    }
+}
+
 impl From<RECT> for Rect {
     fn from(rect: RECT) -> Self {
         Self {
@@ -614,3 +2517,248 @@ impl From<RECT> for Rect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_standard_tooltip_flag() {
+        assert_eq!(tooltip_flags(true), NIF_TIP | NIF_SHOWTIP);
+        // the text is still sent so it's kept for when the standard tooltip is turned back on
+        assert_eq!(tooltip_flags(false), NIF_TIP);
+    }
+
+    #[test]
+    fn encodes_tooltip_at_boundary() {
+        let decode = |sz_tip: [u16; 128]| {
+            let len = sz_tip.iter().position(|&c| c == 0).unwrap();
+            String::from_utf16(&sz_tip[..len]).unwrap()
+        };
+
+        // 126, 127 and 128 code units
+        let before = "🦀".repeat(63);
+        let exact = format!("a{before}");
+        let after = format!("aa{before}");
+        assert_eq!(decode(encode_tooltip(&before)), before);
+        assert_eq!(decode(encode_tooltip(&exact)), exact);
+        // the last emoji would end past the boundary, so it is dropped as a whole
+        assert_eq!(
+            decode(encode_tooltip(&after)),
+            format!("aa{}", "🦀".repeat(62))
+        );
+    }
+
+    #[test]
+    fn detects_overflow() {
+        let rect = |left, top, right, bottom| RECT {
+            left,
+            top,
+            right,
+            bottom,
+        };
+        let tray = rect(1700, 1040, 1920, 1080);
+        let overflow = rect(1650, 900, 1800, 1030);
+
+        let on_taskbar = rect(1750, 1048, 1774, 1072);
+        assert_eq!(
+            in_overflow(&on_taskbar, Some(&tray), Some(&overflow)),
+            Some(false)
+        );
+        assert_eq!(in_overflow(&on_taskbar, Some(&tray), None), Some(false));
+
+        let in_flyout = rect(1660, 910, 1684, 934);
+        assert_eq!(
+            in_overflow(&in_flyout, Some(&tray), Some(&overflow)),
+            Some(true)
+        );
+        assert_eq!(in_overflow(&in_flyout, None, Some(&overflow)), Some(true));
+
+        // an icon only partly on the taskbar counts where its center is
+        let at_edge = rect(1690, 1048, 1714, 1072);
+        assert_eq!(
+            in_overflow(&at_edge, Some(&tray), Some(&overflow)),
+            Some(false)
+        );
+
+        // unknown shell windows or icons elsewhere aren't classified
+        assert_eq!(in_overflow(&in_flyout, Some(&tray), None), None);
+        assert_eq!(in_overflow(&on_taskbar, None, None), None);
+        assert_eq!(
+            in_overflow(&rect(0, 0, 24, 24), Some(&tray), Some(&overflow)),
+            None
+        );
+    }
+
+    #[test]
+    fn skips_unchanged_values() {
+        let icon = Icon::from_rgba(vec![255; 16 * 16 * 4], 16, 16).unwrap();
+        let tray_icon = crate::TrayIconBuilder::new()
+            .with_icon(icon.clone())
+            .with_tooltip("tooltip")
+            .build()
+            .unwrap();
+        let calls = util::notify_icon_calls();
+
+        tray_icon.set_tooltip(Some("tooltip")).unwrap();
+        tray_icon.set_icon(Some(icon.clone())).unwrap();
+        tray_icon
+            .update(
+                crate::TrayIconUpdate::new()
+                    .icon(Some(icon))
+                    .tooltip(Some("tooltip")),
+            )
+            .unwrap();
+        assert_eq!(util::notify_icon_calls(), calls);
+
+        tray_icon.set_tooltip(Some("other")).unwrap();
+        assert_eq!(util::notify_icon_calls(), calls + 1);
+        assert_eq!(tray_icon.tooltip().as_deref(), Some("other"));
+
+        tray_icon.force_refresh().unwrap();
+        assert_eq!(util::notify_icon_calls(), calls + 2);
+    }
+
+    #[test]
+    fn keeps_attributes_set_while_hidden() {
+        let icon = |value| Icon::from_rgba(vec![value; 16 * 16 * 4], 16, 16).unwrap();
+        let tray_icon = crate::TrayIconBuilder::new()
+            .with_icon(icon(0))
+            .with_tooltip("tooltip")
+            .build()
+            .unwrap();
+
+        tray_icon.set_visible(false).unwrap();
+        let calls = util::notify_icon_calls();
+        tray_icon.set_icon(Some(icon(255))).unwrap();
+        tray_icon.set_tooltip(Some("hidden")).unwrap();
+        tray_icon.set_standard_tooltip(false).unwrap();
+        // nothing is sent to the shell until the icon is shown
+        assert_eq!(util::notify_icon_calls(), calls);
+
+        tray_icon.set_visible(true).unwrap();
+        assert!(tray_icon.visible());
+        assert_eq!(tray_icon.icon(), Some(icon(255)));
+        assert_eq!(tray_icon.tooltip().as_deref(), Some("hidden"));
+    }
+
+    #[test]
+    fn calls_message_hook() {
+        use std::{cell::RefCell, rc::Rc};
+
+        const WM_APP_PING: u32 = 0x8001;
+        let tray_icon = crate::TrayIconBuilder::new().build().unwrap();
+        let hwnd = tray_icon.window_handle() as HWND;
+        let messages = Rc::new(RefCell::new(Vec::new()));
+
+        let seen = messages.clone();
+        tray_icon.set_message_hook(Some(Box::new(move |msg, wparam, _| {
+            seen.borrow_mut().push(msg);
+            (msg == WM_APP_PING).then_some(wparam as isize + 1)
+        })));
+        unsafe {
+            assert_eq!(SendMessageW(hwnd, WM_APP_PING, 41, 0), 42);
+            // the tray icon callback is handled by the tray icon only
+            SendMessageW(hwnd, WM_USER_TRAYICON, 0, WM_MOUSEMOVE as _);
+        }
+        assert_eq!(*messages.borrow(), [WM_APP_PING]);
+
+        // a panicking hook is removed instead of unwinding into the system
+        tray_icon.set_message_hook(Some(Box::new(|_, _, _| panic!("hook"))));
+        unsafe {
+            assert_eq!(SendMessageW(hwnd, WM_APP_PING, 0, 0), 0);
+            assert_eq!(SendMessageW(hwnd, WM_APP_PING, 0, 0), 0);
+        }
+    }
+
+    #[test]
+    fn survives_message_hook_removing_icon() {
+        const WM_APP_REMOVE: u32 = 0x8002;
+        let tray_icon = crate::TrayIconBuilder::new().build().unwrap();
+        let hwnd = tray_icon.window_handle() as HWND;
+
+        let clone = tray_icon.clone();
+        tray_icon.set_message_hook(Some(Box::new(move |msg, _, _| {
+            (msg == WM_APP_REMOVE).then(|| {
+                clone.remove().unwrap();
+                1
+            })
+        })));
+        unsafe {
+            assert_eq!(SendMessageW(hwnd, WM_APP_REMOVE, 0, 0), 1);
+        }
+        assert_eq!(tray_icon.window_handle(), 0);
+        assert!(matches!(tray_icon.remove(), Err(crate::Error::Removed)));
+    }
+
+    #[test]
+    fn requests_menu_on_clicks() {
+        use ContextMenuSource::{LeftClick, RightClick};
+
+        assert_eq!(menu_source(WM_RBUTTONDOWN, false), Some(RightClick));
+        assert_eq!(menu_source(WM_RBUTTONDOWN, true), Some(RightClick));
+        assert_eq!(menu_source(WM_LBUTTONUP, true), Some(LeftClick));
+        assert_eq!(menu_source(WM_LBUTTONUP, false), None);
+        for event in [WM_LBUTTONDOWN, WM_RBUTTONUP, WM_MBUTTONUP, WM_LBUTTONDBLCLK] {
+            assert_eq!(menu_source(event, true), None);
+        }
+    }
+
+    #[test]
+    fn anchors_menu_to_icon() {
+        let rect = |left, top, right, bottom| RECT {
+            left,
+            top,
+            right,
+            bottom,
+        };
+        let monitor = rect(0, 0, 1920, 1080);
+        let anchor = |icon| menu_anchor(&icon, &monitor);
+
+        // a taskbar at the bottom, the menu opens above the icon towards the screen center
+        assert_eq!(
+            anchor(rect(1800, 1050, 1824, 1074)),
+            MenuAnchor {
+                x: 1824,
+                y: 1050,
+                flags: TPM_RIGHTALIGN | TPM_BOTTOMALIGN | TPM_VERTICAL
+            }
+        );
+        // at the top, below the icon
+        assert_eq!(
+            anchor(rect(100, 6, 124, 30)),
+            MenuAnchor {
+                x: 100,
+                y: 30,
+                flags: TPM_LEFTALIGN | TPM_TOPALIGN | TPM_VERTICAL
+            }
+        );
+        // at the right, left of the icon
+        assert_eq!(
+            anchor(rect(1890, 900, 1914, 924)),
+            MenuAnchor {
+                x: 1890,
+                y: 924,
+                flags: TPM_RIGHTALIGN | TPM_BOTTOMALIGN
+            }
+        );
+        // at the left, right of the icon
+        assert_eq!(
+            anchor(rect(6, 100, 30, 124)),
+            MenuAnchor {
+                x: 30,
+                y: 100,
+                flags: TPM_LEFTALIGN | TPM_TOPALIGN
+            }
+        );
+    }
+
+    #[test]
+    fn debounces_tooltip_provider() {
+        let now = Instant::now();
+        assert!(provider_due(None, now));
+        assert!(!provider_due(Some(now), now));
+        assert!(!provider_due(Some(now), now + Duration::from_millis(999)));
+        assert!(provider_due(Some(now), now + TOOLTIP_PROVIDER_INTERVAL));
+    }
+}
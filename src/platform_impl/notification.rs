@@ -0,0 +1,293 @@
+use windows_sys::Win32::{
+    Foundation::{HWND, S_OK},
+    UI::{
+        Shell::{
+            SHQueryUserNotificationState, NIF_INFO, NIF_REALTIME, NIIF_ERROR, NIIF_INFO,
+            NIIF_LARGE_ICON, NIIF_NONE, NIIF_NOSOUND, NIIF_RESPECT_QUIET_TIME, NIIF_USER,
+            NIIF_WARNING, NIM_MODIFY, NOTIFYICONDATAW, NOTIFY_ICON_DATA_FLAGS,
+            NOTIFY_ICON_INFOTIP_FLAGS, QUNS_APP, QUNS_BUSY, QUNS_NOT_PRESENT,
+            QUNS_PRESENTATION_MODE, QUNS_QUIET_TIME, QUNS_RUNNING_D3D_FULL_SCREEN,
+        },
+        WindowsAndMessaging::HICON,
+    },
+};
+
+use std::collections::VecDeque;
+
+use super::util;
+use crate::{
+    Notification, NotificationId, NotificationKind, NotificationOverflow, UserNotificationState,
+};
+
+/// The number of notifications queued by default.
+const DEFAULT_QUEUE_LEN: usize = 16;
+
+/// Notifications waiting for the shown balloon to close.
+pub struct NotificationQueue {
+    pub enabled: bool,
+    /// Whether a balloon is currently shown.
+    pub showing: bool,
+    notifications: VecDeque<(NotificationId, Notification)>,
+    max_len: usize,
+    overflow: NotificationOverflow,
+}
+
+impl Default for NotificationQueue {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            showing: false,
+            notifications: VecDeque::new(),
+            max_len: DEFAULT_QUEUE_LEN,
+            overflow: NotificationOverflow::DropOldest,
+        }
+    }
+}
+
+impl NotificationQueue {
+    /// Queues `notification`, returns `false` if the queue is full and rejects it.
+    pub fn push(&mut self, id: NotificationId, notification: Notification) -> bool {
+        if self.notifications.len() >= self.max_len {
+            match self.overflow {
+                NotificationOverflow::DropOldest => {
+                    self.notifications.pop_front();
+                }
+                NotificationOverflow::Reject => return false,
+            }
+        }
+        self.notifications.push_back((id, notification));
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<(NotificationId, Notification)> {
+        self.notifications.pop_front()
+    }
+
+    pub fn clear(&mut self) {
+        self.notifications.clear();
+    }
+
+    /// Queues at most `max_len` notifications, at least one, dropping the oldest
+    /// ones that don't fit anymore.
+    pub fn set_limit(&mut self, max_len: usize, overflow: NotificationOverflow) {
+        self.max_len = max_len.max(1);
+        self.overflow = overflow;
+        while self.notifications.len() > self.max_len {
+            self.notifications.pop_front();
+        }
+    }
+}
+
+/// Returns the `uFlags` for `notification`.
+fn flags(notification: &Notification) -> NOTIFY_ICON_DATA_FLAGS {
+    if notification.realtime {
+        NIF_INFO | NIF_REALTIME
+    } else {
+        NIF_INFO
+    }
+}
+
+/// Returns the `dwInfoFlags` for `notification`, `large_icon` is whether its user icon is large.
+fn info_flags(notification: &Notification, large_icon: bool) -> NOTIFY_ICON_INFOTIP_FLAGS {
+    // the icon is an enumeration in the low bits, options are flags above it
+    let mut flags = match notification.kind {
+        _ if notification.has_user_icon() => NIIF_USER,
+        NotificationKind::None => NIIF_NONE,
+        NotificationKind::Info => NIIF_INFO,
+        NotificationKind::Warning => NIIF_WARNING,
+        NotificationKind::Error => NIIF_ERROR,
+        NotificationKind::User => NIIF_USER,
+    };
+    if notification.silent {
+        flags |= NIIF_NOSOUND;
+    }
+    if large_icon && notification.has_user_icon() {
+        flags |= NIIF_LARGE_ICON;
+    }
+    if notification.respect_quiet_time {
+        flags |= NIIF_RESPECT_QUIET_TIME;
+    }
+    flags
+}
+
+/// Copies `string` and its nul terminator into `buffer`, which must be large enough.
+fn copy_wide(buffer: &mut [u16], string: &str) {
+    let wide = util::encode_wide(string);
+    buffer[..wide.len()].copy_from_slice(&wide);
+}
+
+/// Shows `notification` as a balloon of the tray icon `tray_id`, with `user_icon` for
+/// notifications with a user icon, which is large if `large_icon` is `true`.
+///
+/// The notification must have been validated already.
+pub unsafe fn show(
+    hwnd: HWND,
+    tray_id: u32,
+    notification: &Notification,
+    user_icon: Option<HICON>,
+    large_icon: bool,
+) -> bool {
+    let mut nid = NOTIFYICONDATAW {
+        uFlags: flags(notification),
+        hWnd: hwnd,
+        uID: tray_id,
+        dwInfoFlags: info_flags(notification, large_icon),
+        ..std::mem::zeroed()
+    };
+    if notification.has_user_icon() {
+        nid.hBalloonIcon = user_icon.unwrap_or(std::ptr::null_mut());
+    }
+    copy_wide(&mut nid.szInfoTitle, &notification.title);
+    copy_wide(&mut nid.szInfo, &notification.body);
+
+    util::notify_icon(NIM_MODIFY, &mut nid)
+}
+
+pub fn user_notification_state() -> crate::Result<UserNotificationState> {
+    let mut state = 0;
+    let result = unsafe { SHQueryUserNotificationState(&mut state) };
+    if result != S_OK {
+        return Err(crate::Error::OsError(std::io::Error::from_raw_os_error(
+            result,
+        )));
+    }
+
+    Ok(match state {
+        QUNS_NOT_PRESENT => UserNotificationState::NotPresent,
+        QUNS_BUSY => UserNotificationState::Busy,
+        QUNS_RUNNING_D3D_FULL_SCREEN => UserNotificationState::RunningD3DFullScreen,
+        QUNS_PRESENTATION_MODE => UserNotificationState::PresentationMode,
+        QUNS_QUIET_TIME => UserNotificationState::QuietTime,
+        QUNS_APP => UserNotificationState::App,
+        // QUNS_ACCEPTS_NOTIFICATIONS, unknown states shouldn't hold notifications back
+        _ => UserNotificationState::AcceptsNotifications,
+    })
+}
+
+/// Returns whether the shell holds `notification` back while the user is in `state`.
+pub fn suppresses(state: UserNotificationState, notification: &Notification) -> bool {
+    match state {
+        UserNotificationState::AcceptsNotifications => false,
+        UserNotificationState::QuietTime => notification.respect_quiet_time,
+        _ => true,
+    }
+}
+
+/// Removes the balloon of the tray icon `tray_id`, an empty text hides it.
+pub unsafe fn clear(hwnd: HWND, tray_id: u32) -> bool {
+    let mut nid = NOTIFYICONDATAW {
+        uFlags: NIF_INFO,
+        hWnd: hwnd,
+        uID: tray_id,
+        ..std::mem::zeroed()
+    };
+
+    util::notify_icon(NIM_MODIFY, &mut nid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows_sys::Win32::UI::Shell::NIIF_ICON_MASK;
+
+    #[test]
+    fn combines_info_flags() {
+        let kinds = [
+            (NotificationKind::None, NIIF_NONE),
+            (NotificationKind::Info, NIIF_INFO),
+            (NotificationKind::Warning, NIIF_WARNING),
+            (NotificationKind::Error, NIIF_ERROR),
+            (NotificationKind::User, NIIF_USER),
+        ];
+        for (kind, icon) in kinds {
+            let notification = Notification::new().kind(kind);
+            assert_eq!(info_flags(&notification, false), icon);
+
+            let flags = info_flags(&notification.silent(true), false);
+            assert_eq!(flags & NIIF_ICON_MASK, icon);
+            assert_eq!(flags & !NIIF_ICON_MASK, NIIF_NOSOUND);
+        }
+
+        // only user icons can be large
+        let notification = Notification::new().large_icon(true);
+        assert_eq!(info_flags(&notification, true), NIIF_NONE);
+        let notification = notification.kind(NotificationKind::User).silent(true);
+        assert_eq!(
+            info_flags(&notification, true),
+            NIIF_USER | NIIF_NOSOUND | NIIF_LARGE_ICON
+        );
+        assert_eq!(info_flags(&notification, false), NIIF_USER | NIIF_NOSOUND);
+
+        let notification = Notification::new()
+            .kind(NotificationKind::Warning)
+            .respect_quiet_time(true);
+        assert_eq!(
+            info_flags(&notification, false),
+            NIIF_WARNING | NIIF_RESPECT_QUIET_TIME
+        );
+
+        // a custom icon wins over the kind
+        let icon = crate::Icon::from_rgba(vec![255; 16 * 16 * 4], 16, 16).unwrap();
+        let notification = Notification::new().kind(NotificationKind::Info).icon(icon);
+        assert_eq!(info_flags(&notification, false), NIIF_USER);
+        assert_eq!(info_flags(&notification, true), NIIF_USER | NIIF_LARGE_ICON);
+    }
+
+    #[test]
+    fn sets_realtime_flag() {
+        let notification = Notification::new().silent(true);
+        assert_eq!(flags(&notification), NIF_INFO);
+        let notification = notification.realtime(true);
+        assert_eq!(flags(&notification), NIF_INFO | NIF_REALTIME);
+        // realtime is a notification flag, not a balloon flag
+        assert_eq!(info_flags(&notification, false), NIIF_NOSOUND);
+    }
+
+    #[test]
+    fn suppresses_in_busy_states() {
+        let notification = Notification::new();
+        assert!(!suppresses(
+            UserNotificationState::AcceptsNotifications,
+            &notification
+        ));
+        assert!(suppresses(UserNotificationState::Busy, &notification));
+        assert!(suppresses(
+            UserNotificationState::PresentationMode,
+            &notification
+        ));
+        // quiet time only affects notifications respecting it
+        assert!(!suppresses(UserNotificationState::QuietTime, &notification));
+        assert!(suppresses(
+            UserNotificationState::QuietTime,
+            &notification.respect_quiet_time(true)
+        ));
+    }
+
+    #[test]
+    fn handles_queue_overflow() {
+        let ids = |queue: &NotificationQueue| {
+            queue
+                .notifications
+                .iter()
+                .map(|(id, _)| id.0)
+                .collect::<Vec<_>>()
+        };
+
+        let mut queue = NotificationQueue::default();
+        queue.set_limit(2, NotificationOverflow::DropOldest);
+        for id in 1..=3 {
+            assert!(queue.push(NotificationId(id), Notification::new()));
+        }
+        assert_eq!(ids(&queue), [2, 3]);
+
+        queue.set_limit(2, NotificationOverflow::Reject);
+        assert!(!queue.push(NotificationId(4), Notification::new()));
+        assert_eq!(ids(&queue), [2, 3]);
+
+        // shrinking the queue drops the oldest notifications
+        queue.set_limit(0, NotificationOverflow::Reject);
+        assert_eq!(ids(&queue), [3]);
+        assert_eq!(queue.pop().unwrap().0, NotificationId(3));
+        assert!(queue.pop().is_none());
+    }
+}
@@ -0,0 +1,82 @@
+//! Commands sent to the tray window from other threads.
+//!
+//! Commands are queued rather than boxed into the posted message, so those still queued when
+//! the window is destroyed are dropped along with their reply senders instead of leaking.
+
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use crossbeam_channel::Sender;
+use windows_sys::Win32::{Foundation::HWND, UI::WindowsAndMessaging::PostMessageW};
+
+use super::WM_USER_PROXY_COMMAND;
+use crate::{Icon, Notification, NotificationId};
+
+pub enum ProxyCommand {
+    Update {
+        icon: Option<Option<Icon>>,
+        tooltip: Option<Option<String>>,
+        visible: Option<bool>,
+        reply: Sender<crate::Result<()>>,
+    },
+    ShowNotification {
+        id: NotificationId,
+        notification: Notification,
+        reply: Sender<crate::Result<NotificationId>>,
+    },
+}
+
+/// The commands waiting to be run by the tray window, `None` once it is destroyed.
+#[derive(Default)]
+pub struct ProxyQueue(Mutex<Option<VecDeque<ProxyCommand>>>);
+
+impl ProxyQueue {
+    pub fn new() -> Self {
+        Self(Mutex::new(Some(VecDeque::new())))
+    }
+
+    /// Takes the queued commands, to be run on the tray thread.
+    pub fn take(&self) -> VecDeque<ProxyCommand> {
+        let mut commands = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        commands.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Rejects further commands and drops the queued ones, once the tray window is destroyed.
+    pub fn close(&self) {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).take();
+    }
+}
+
+/// Queues commands for the tray window of a tray icon, from any thread.
+#[derive(Clone)]
+pub struct TrayProxy {
+    hwnd: isize,
+    queue: Arc<ProxyQueue>,
+}
+
+impl TrayProxy {
+    pub fn new(hwnd: HWND, queue: Arc<ProxyQueue>) -> Self {
+        Self {
+            hwnd: hwnd as isize,
+            queue,
+        }
+    }
+
+    /// Queues `command` and wakes up the tray window, returning it if the window is gone.
+    pub fn send(&self, command: ProxyCommand) -> Result<(), ProxyCommand> {
+        let mut commands = self.queue.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(commands) = commands.as_mut() else {
+            return Err(command);
+        };
+
+        // one wake up runs all queued commands
+        let wake = commands.is_empty();
+        commands.push_back(command);
+        if wake && unsafe { PostMessageW(self.hwnd as HWND, WM_USER_PROXY_COMMAND, 0, 0) } == 0 {
+            return Err(commands.pop_back().unwrap());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+use windows::{
+    core::{HRESULT, HSTRING},
+    Data::Xml::Dom::XmlDocument,
+    Foundation::TypedEventHandler,
+    Win32::System::WinRT::{RoInitialize, RO_INIT_SINGLETHREADED},
+    UI::Notifications::{
+        ToastDismissalReason, ToastDismissedEventArgs, ToastNotification, ToastNotificationManager,
+        ToastNotifier,
+    },
+};
+use windows_sys::{
+    w,
+    Win32::{
+        Foundation::{
+            CLASS_E_CLASSNOTAVAILABLE, ERROR_MOD_NOT_FOUND, ERROR_PROC_NOT_FOUND,
+            REGDB_E_CLASSNOTREG,
+        },
+        System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ},
+    },
+};
+
+use super::util;
+use crate::{NotificationId, Toast, TrayIconEvent, TrayIconId};
+
+/// Returns the AppUserModelID toasts of the tray icon `id` are shown with.
+fn app_user_model_id(id: &TrayIconId) -> String {
+    let sanitize = |s: &str| {
+        s.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect::<String>()
+    };
+    let exe = std::env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    // AppUserModelIDs are limited to 128 characters
+    let mut aumid = format!("{}.TrayIcon.{}", sanitize(&exe), sanitize(id.as_ref()));
+    aumid.truncate(128);
+    aumid
+}
+
+/// Registers `aumid` for the current user, which unpackaged apps need to show toasts.
+unsafe fn register_app_user_model_id(aumid: &str) {
+    let key = util::encode_wide(format!("Software\\Classes\\AppUserModelId\\{aumid}"));
+    let display_name = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_stem().map(|stem| stem.to_os_string()))
+        .unwrap_or_else(|| aumid.into());
+    let display_name = util::encode_wide(display_name);
+    RegSetKeyValueW(
+        HKEY_CURRENT_USER,
+        key.as_ptr(),
+        w!("DisplayName"),
+        REG_SZ,
+        display_name.as_ptr() as _,
+        (display_name.len() * std::mem::size_of::<u16>()) as u32,
+    );
+}
+
+/// Whether `err` means WinRT toasts aren't available, e.g. before Windows 10.
+pub fn is_unavailable(err: &windows::core::Error) -> bool {
+    let code = err.code();
+    code.0 == REGDB_E_CLASSNOTREG
+        || code.0 == CLASS_E_CLASSNOTAVAILABLE
+        || code == HRESULT::from_win32(ERROR_MOD_NOT_FOUND)
+        || code == HRESULT::from_win32(ERROR_PROC_NOT_FOUND)
+}
+
+/// Shows the toasts of a tray icon, WinRT and its AppUserModelID are set up once on creation.
+pub struct Toaster {
+    tray_id: TrayIconId,
+    notifier: ToastNotifier,
+    /// The last shown toast, kept alive so its events are delivered.
+    last_toast: Option<ToastNotification>,
+}
+
+impl Toaster {
+    pub fn new(tray_id: &TrayIconId) -> windows::core::Result<Self> {
+        let aumid = app_user_model_id(tray_id);
+        // fails harmlessly if the thread is already initialized
+        let _ = unsafe { RoInitialize(RO_INIT_SINGLETHREADED) };
+        let notifier = ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(&aumid))?;
+        unsafe { register_app_user_model_id(&aumid) };
+
+        Ok(Self {
+            tray_id: tray_id.clone(),
+            notifier,
+            last_toast: None,
+        })
+    }
+
+    /// Shows `toast`, its events are reported with `id`.
+    pub fn show(&mut self, id: NotificationId, toast: &Toast) -> windows::core::Result<()> {
+        let xml = XmlDocument::new()?;
+        xml.LoadXml(&HSTRING::from(toast.to_xml()))?;
+        let notification = ToastNotification::CreateToastNotification(&xml)?;
+
+        let tray = self.tray_id.clone();
+        notification.Activated(&TypedEventHandler::new(move |_, _| {
+            TrayIconEvent::send(TrayIconEvent::NotificationClicked {
+                id: tray.clone(),
+                notification: id,
+                timestamp: util::current_timestamp(),
+            });
+            Ok(())
+        }))?;
+
+        let tray = self.tray_id.clone();
+        notification.Dismissed(&TypedEventHandler::new(
+            move |_, args: &Option<ToastDismissedEventArgs>| {
+                let reason = args.as_ref().and_then(|args| args.Reason().ok());
+                let (id, notification) = (tray.clone(), id);
+                let timestamp = util::current_timestamp();
+                TrayIconEvent::send(if reason == Some(ToastDismissalReason::TimedOut) {
+                    TrayIconEvent::NotificationTimedOut {
+                        id,
+                        notification,
+                        timestamp,
+                    }
+                } else {
+                    TrayIconEvent::NotificationDismissed {
+                        id,
+                        notification,
+                        timestamp,
+                    }
+                });
+                Ok(())
+            },
+        ))?;
+
+        self.notifier.Show(&notification)?;
+        self.last_toast = Some(notification);
+        TrayIconEvent::send(TrayIconEvent::NotificationShown {
+            id: self.tray_id.clone(),
+            notification: id,
+            timestamp: util::current_timestamp(),
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizes_app_user_model_id() {
+        let aumid = app_user_model_id(&TrayIconId::new("my tray/icon.1"));
+        assert!(aumid.ends_with(".TrayIcon.my-tray-icon-1"));
+        assert_eq!(aumid.split('.').count(), 3);
+
+        assert_eq!(
+            app_user_model_id(&TrayIconId::new("x".repeat(200))).len(),
+            128
+        );
+    }
+}
@@ -1,16 +1,319 @@
-use std::ops::{Deref, DerefMut};
+#[cfg(test)]
+use std::cell::Cell;
+use std::{
+    collections::HashMap,
+    ops::{Deref, DerefMut},
+    sync::{LazyLock, RwLock},
+    time::SystemTime,
+};
 
-use windows_sys::Win32::{
-    Foundation::HWND,
-    UI::WindowsAndMessaging::{ACCEL, WINDOW_LONG_PTR_INDEX},
+use windows_sys::{
+    core::GUID,
+    w,
+    Win32::{
+        Foundation::{ERROR_SUCCESS, HWND, POINT, RECT, S_OK},
+        Graphics::Gdi::{
+            GetMonitorInfoW, MonitorFromPoint, MonitorFromRect, MonitorFromWindow, HMONITOR,
+            MONITORINFO, MONITORINFOEXW, MONITOR_DEFAULTTONEAREST, MONITOR_DEFAULTTOPRIMARY,
+        },
+        System::{
+            Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD},
+            SystemInformation::GetTickCount,
+        },
+        UI::{
+            HiDpi::{GetDpiForMonitor, GetSystemMetricsForDpi, MDT_EFFECTIVE_DPI},
+            Shell::{
+                Shell_NotifyIconW, NIF_GUID, NIM_ADD, NIM_DELETE, NOTIFYICONDATAW,
+                NOTIFY_ICON_MESSAGE,
+            },
+            WindowsAndMessaging::{
+                FindWindowExW, FindWindowW, GetMessageTime, GetSystemMetrics, GetWindowRect, ACCEL,
+                MONITORINFOF_PRIMARY, SM_CXICON, SM_CXSMICON, SM_CYSMICON, USER_DEFAULT_SCREEN_DPI,
+                WINDOW_LONG_PTR_INDEX,
+            },
+        },
+    },
 };
 
+use crate::{dpi::PhysicalPosition, Monitor, Rect, Timestamp};
+
 pub fn encode_wide<S: AsRef<std::ffi::OsStr>>(string: S) -> Vec<u16> {
     std::os::windows::prelude::OsStrExt::encode_wide(string.as_ref())
         .chain(std::iter::once(0))
         .collect()
 }
 
+/// Returns the timestamp of the message the calling thread is handling.
+pub fn message_timestamp() -> Timestamp {
+    unsafe { Timestamp::from_tick(GetMessageTime() as u32, GetTickCount(), SystemTime::now()) }
+}
+
+/// Returns the timestamp of an event happening now, outside of the message handling.
+pub fn current_timestamp() -> Timestamp {
+    let tick = unsafe { GetTickCount() };
+    Timestamp::from_tick(tick, tick, SystemTime::now())
+}
+
+/// Returns the system small icon size, which is the size used by the notification area.
+pub fn small_icon_size() -> (u32, u32) {
+    unsafe {
+        (
+            GetSystemMetrics(SM_CXSMICON) as u32,
+            GetSystemMetrics(SM_CYSMICON) as u32,
+        )
+    }
+}
+
+/// The GUIDs of tray icons identified by one instead of their window and id, by id.
+static ICON_GUIDS: LazyLock<RwLock<HashMap<u32, GUID>>> = LazyLock::new(Default::default);
+
+/// Identifies the tray icon with id `uid` by `guid` in all shell calls, or stops doing so.
+///
+/// The bytes are in the order of RFC 4122, as returned by `uuid::Uuid::as_bytes`.
+pub fn set_icon_guid(uid: u32, guid: Option<[u8; 16]>) {
+    let mut guids = ICON_GUIDS.write().unwrap_or_else(|e| e.into_inner());
+    match guid {
+        Some(bytes) => guids.insert(uid, GUID::from_u128(u128::from_be_bytes(bytes))),
+        None => guids.remove(&uid),
+    };
+}
+
+/// Returns the GUID identifying the tray icon with id `uid`, if any.
+pub fn icon_guid(uid: u32) -> Option<GUID> {
+    ICON_GUIDS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&uid)
+        .copied()
+}
+
+#[cfg(test)]
+thread_local! {
+    static NOTIFY_ICON_CALLS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Returns how often [`notify_icon`] was called on this thread.
+#[cfg(test)]
+pub fn notify_icon_calls() -> usize {
+    NOTIFY_ICON_CALLS.get()
+}
+
+/// Calls `Shell_NotifyIconW`, returning whether it succeeded.
+///
+/// Icons with a GUID are identified by it, see [`set_icon_guid`].
+pub unsafe fn notify_icon(message: NOTIFY_ICON_MESSAGE, nid: &mut NOTIFYICONDATAW) -> bool {
+    #[cfg(test)]
+    NOTIFY_ICON_CALLS.set(NOTIFY_ICON_CALLS.get() + 1);
+
+    if let Some(guid) = icon_guid(nid.uID) {
+        nid.uFlags |= NIF_GUID;
+        nid.guidItem = guid;
+    }
+
+    let success = Shell_NotifyIconW(message, nid) != 0;
+
+    #[cfg(feature = "tracing")]
+    {
+        use windows_sys::Win32::UI::Shell::{NIM_MODIFY, NIM_SETFOCUS, NIM_SETVERSION};
+
+        let operation = match message {
+            NIM_ADD => "add",
+            NIM_MODIFY => "modify",
+            NIM_DELETE => "delete",
+            NIM_SETVERSION => "set_version",
+            NIM_SETFOCUS => "set_focus",
+            _ => "other",
+        };
+        if success {
+            tracing::debug!(
+                operation,
+                flags = nid.uFlags,
+                uid = nid.uID,
+                success,
+                "Shell_NotifyIconW"
+            );
+        } else {
+            // the shell doesn't always set an error, e.g. while explorer restarts
+            let error = std::io::Error::last_os_error().raw_os_error().unwrap_or(0);
+            tracing::warn!(
+                operation,
+                flags = nid.uFlags,
+                uid = nid.uID,
+                success,
+                error,
+                "Shell_NotifyIconW"
+            );
+        }
+    }
+
+    success
+}
+
+/// Whether the shell adds the tray icon `nid` once its GUID is left out, which tells a
+/// rejected GUID apart from other failures. The icon is removed again right away.
+pub unsafe fn accepts_without_guid(nid: &NOTIFYICONDATAW) -> bool {
+    let nid = NOTIFYICONDATAW {
+        uFlags: nid.uFlags & !NIF_GUID,
+        guidItem: std::mem::zeroed(),
+        ..*nid
+    };
+    let added = Shell_NotifyIconW(NIM_ADD, &nid) != 0;
+    if added {
+        Shell_NotifyIconW(NIM_DELETE, &nid);
+    }
+    added
+}
+
+/// Returns the DPI of the monitor hosting the taskbar, falling back to the primary monitor.
+pub fn tray_dpi() -> u32 {
+    unsafe {
+        let taskbar = FindWindowW(w!("Shell_TrayWnd"), std::ptr::null());
+        let monitor = if taskbar.is_null() {
+            MonitorFromPoint(POINT { x: 0, y: 0 }, MONITOR_DEFAULTTOPRIMARY)
+        } else {
+            MonitorFromWindow(taskbar, MONITOR_DEFAULTTOPRIMARY)
+        };
+
+        monitor_dpi(monitor)
+    }
+}
+
+/// Returns the rects of the notification area on the taskbar and of the overflow flyout,
+/// `None` for windows that can't be found.
+pub fn notification_area_rects() -> (Option<RECT>, Option<RECT>) {
+    unsafe fn window_rect(hwnd: HWND) -> Option<RECT> {
+        let mut rect = std::mem::zeroed();
+        (!hwnd.is_null() && GetWindowRect(hwnd, &mut rect) != 0).then_some(rect)
+    }
+
+    unsafe {
+        let taskbar = FindWindowW(w!("Shell_TrayWnd"), std::ptr::null());
+        let tray = if taskbar.is_null() {
+            None
+        } else {
+            window_rect(FindWindowExW(
+                taskbar,
+                std::ptr::null_mut(),
+                w!("TrayNotifyWnd"),
+                std::ptr::null(),
+            ))
+        };
+        let overflow = window_rect(FindWindowW(
+            w!("NotifyIconOverflowWindow"),
+            std::ptr::null(),
+        ));
+        (tray, overflow)
+    }
+}
+
+/// Returns the scale factor of the monitor at `position`, which may differ between the
+/// events of one icon when the cursor is on a secondary monitor with another scaling.
+pub fn scale_factor_at(position: PhysicalPosition<f64>) -> f64 {
+    let point = POINT {
+        x: position.x as i32,
+        y: position.y as i32,
+    };
+    let dpi = unsafe { monitor_dpi(MonitorFromPoint(point, MONITOR_DEFAULTTONEAREST)) };
+    dpi as f64 / USER_DEFAULT_SCREEN_DPI as f64
+}
+
+unsafe fn monitor_dpi(monitor: HMONITOR) -> u32 {
+    let (mut dpi_x, mut dpi_y) = (0, 0);
+    if GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) == S_OK {
+        dpi_x
+    } else {
+        USER_DEFAULT_SCREEN_DPI
+    }
+}
+
+/// Returns the monitor a tray icon at `rect` is shown on.
+///
+/// Icons in the overflow flyout, or with an unknown position, are on the primary monitor.
+pub fn monitor_of(rect: &Rect) -> Monitor {
+    let center = POINT {
+        x: (rect.position.x + rect.size.width as f64 / 2.) as i32,
+        y: (rect.position.y + rect.size.height as f64 / 2.) as i32,
+    };
+
+    unsafe {
+        let monitor = MonitorFromPoint(center, MONITOR_DEFAULTTOPRIMARY);
+        let mut info = MONITORINFOEXW {
+            monitorInfo: MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFOEXW>() as u32,
+                ..std::mem::zeroed()
+            },
+            ..std::mem::zeroed()
+        };
+        if GetMonitorInfoW(monitor, &mut info.monitorInfo) == 0 {
+            return Monitor::default();
+        }
+
+        let name_len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(32);
+        Monitor {
+            name: String::from_utf16_lossy(&info.szDevice[..name_len]),
+            rect: info.monitorInfo.rcMonitor.into(),
+            work_area: info.monitorInfo.rcWork.into(),
+            primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+        }
+    }
+}
+
+/// Returns the bounds of the monitor nearest to `rect`.
+pub fn monitor_rect(rect: &RECT) -> Option<RECT> {
+    unsafe {
+        let monitor = MonitorFromRect(rect, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..std::mem::zeroed()
+        };
+        (GetMonitorInfoW(monitor, &mut info) != 0).then_some(info.rcMonitor)
+    }
+}
+
+/// Returns the size of a tray icon for the DPI of the monitor hosting the taskbar.
+pub fn tray_icon_size() -> u32 {
+    unsafe { GetSystemMetricsForDpi(SM_CXSMICON, tray_dpi()) as u32 }
+}
+
+/// Returns the size of a large icon, as shown in notifications, for the DPI of the monitor
+/// hosting the taskbar.
+pub fn large_icon_size() -> u32 {
+    unsafe { GetSystemMetricsForDpi(SM_CXICON, tray_dpi()) as u32 }
+}
+
+/// Returns whether the taskbar uses the light theme, defaults to `false` (dark)
+/// when the setting can't be read, e.g. on Windows versions without themes.
+pub fn system_uses_light_theme() -> bool {
+    let mut value: u32 = 0;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    let result = unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+            w!("SystemUsesLightTheme"),
+            RRF_RT_REG_DWORD,
+            std::ptr::null_mut(),
+            &mut value as *mut u32 as _,
+            &mut size,
+        )
+    };
+    result == ERROR_SUCCESS && value != 0
+}
+
+/// Compares a null-terminated wide string, which may be null, to `other`.
+pub unsafe fn pcwstr_eq(string: *const u16, other: &str) -> bool {
+    if string.is_null() {
+        return false;
+    }
+
+    let mut len = 0;
+    while *string.add(len) != 0 {
+        len += 1;
+    }
+    let string = std::slice::from_raw_parts(string, len);
+    string.iter().copied().eq(other.encode_utf16())
+}
+
 /// ACCEL wrapper to implement Debug
 #[derive(Clone)]
 #[repr(transparent)]
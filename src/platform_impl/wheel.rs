@@ -0,0 +1,116 @@
+//! The shell doesn't forward mouse wheel messages to tray icons, so they are read
+//! with a low-level mouse hook instead.
+
+use std::cell::RefCell;
+
+use windows_sys::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    UI::WindowsAndMessaging::{
+        CallNextHookEx, PostMessageW, SetWindowsHookExW, UnhookWindowsHookEx, HC_ACTION, HHOOK,
+        MSLLHOOKSTRUCT, WH_MOUSE_LL, WM_MOUSEWHEEL,
+    },
+};
+
+use super::{util, WM_USER_MOUSE_WHEEL};
+
+/// The hook of the current thread, along with the windows it posts wheel messages to.
+struct Hook {
+    hook: HHOOK,
+    windows: Vec<HWND>,
+}
+
+thread_local! {
+    // low-level hooks are called on the thread that installed them
+    static HOOK: RefCell<Option<Hook>> = const { RefCell::new(None) };
+}
+
+/// Posts `WM_USER_MOUSE_WHEEL` to `hwnd` for every wheel message, installing the hook
+/// for the current thread if needed.
+pub unsafe fn register(hwnd: HWND) -> bool {
+    HOOK.with_borrow_mut(|hook| {
+        if hook.is_none() {
+            let handle = SetWindowsHookExW(
+                WH_MOUSE_LL,
+                Some(mouse_hook_proc),
+                util::get_instance_handle(),
+                0,
+            );
+            if handle.is_null() {
+                return false;
+            }
+            *hook = Some(Hook {
+                hook: handle,
+                windows: Vec::new(),
+            });
+        }
+
+        if let Some(hook) = hook {
+            hook.windows.push(hwnd);
+        }
+        true
+    })
+}
+
+/// Stops posting wheel messages to `hwnd`, removing the hook once no window is left.
+pub unsafe fn unregister(hwnd: HWND) {
+    HOOK.with_borrow_mut(|hook| {
+        if let Some(h) = hook {
+            h.windows.retain(|&w| w != hwnd);
+            if h.windows.is_empty() {
+                UnhookWindowsHookEx(h.hook);
+                *hook = None;
+            }
+        }
+    })
+}
+
+unsafe extern "system" fn mouse_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 && wparam == WM_MOUSEWHEEL as usize {
+        let info = &*(lparam as *const MSLLHOOKSTRUCT);
+        let delta = (info.mouseData >> 16) as i16;
+        // hooks must return quickly, so checking whether the cursor is over the icon is
+        // left to the tray window
+        HOOK.with_borrow(|hook| {
+            if let Some(hook) = hook {
+                for &hwnd in &hook.windows {
+                    PostMessageW(
+                        hwnd,
+                        WM_USER_MOUSE_WHEEL,
+                        delta as WPARAM,
+                        pack_point(info.pt.x, info.pt.y),
+                    );
+                }
+            }
+        });
+    }
+
+    CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+}
+
+/// Packs screen coordinates into an `LPARAM`, like `MAKELPARAM`.
+#[inline]
+fn pack_point(x: i32, y: i32) -> LPARAM {
+    (((y as u16 as u32) << 16) | x as u16 as u32) as LPARAM
+}
+
+/// Unpacks screen coordinates packed with [`pack_point`], like `GET_X_LPARAM` and `GET_Y_LPARAM`.
+#[inline]
+pub fn unpack_point(lparam: LPARAM) -> (i32, i32) {
+    (
+        lparam as u16 as i16 as i32,
+        (lparam >> 16) as u16 as i16 as i32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_negative_coordinates() {
+        // monitors left of or above the primary one have negative coordinates
+        for (x, y) in [(0, 0), (1920, 1080), (-1280, 200), (300, -1024), (-1, -1)] {
+            assert_eq!(unpack_point(pack_point(x, y)), (x, y));
+        }
+    }
+}
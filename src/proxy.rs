@@ -0,0 +1,142 @@
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, Receiver, RecvTimeoutError};
+
+use crate::{
+    platform_impl::{ProxyCommand, TrayProxy},
+    Error, Icon, Notification, NotificationId, Result, TrayIconId, NOTIFICATION_COUNTER,
+};
+
+/// Controls a [`TrayIcon`](crate::TrayIcon) from other threads, see
+/// [`TrayIcon::proxy`](crate::TrayIcon::proxy).
+///
+/// Commands run on the thread of the tray icon, each returns a [`ProxyReply`] for their
+/// result. Once the tray icon is dropped or removed, they fail with [`Error::Removed`].
+#[derive(Clone)]
+pub struct TrayIconProxy {
+    id: TrayIconId,
+    inner: TrayProxy,
+}
+
+impl TrayIconProxy {
+    pub(crate) fn new(id: TrayIconId, inner: TrayProxy) -> Self {
+        Self { id, inner }
+    }
+
+    /// Returns the id of the tray icon.
+    pub fn id(&self) -> &TrayIconId {
+        &self.id
+    }
+
+    /// Sets the icon like [`TrayIcon::set_icon`](crate::TrayIcon::set_icon).
+    pub fn set_icon(&self, icon: Option<Icon>) -> ProxyReply {
+        self.update(Some(icon), None, None)
+    }
+
+    /// Sets the tooltip like [`TrayIcon::set_tooltip`](crate::TrayIcon::set_tooltip).
+    pub fn set_tooltip<S: AsRef<str>>(&self, tooltip: Option<S>) -> ProxyReply {
+        let tooltip = tooltip.map(|t| crate::tooltip::normalize(t.as_ref()).into_owned());
+        if let Some(Err(err)) = tooltip.as_deref().map(crate::tooltip::validate) {
+            return ProxyReply::ready(Err(err));
+        }
+        self.update(None, Some(tooltip), None)
+    }
+
+    /// Shows or hides the tray icon like [`TrayIcon::set_visible`](crate::TrayIcon::set_visible).
+    pub fn set_visible(&self, visible: bool) -> ProxyReply {
+        self.update(None, None, Some(visible))
+    }
+
+    /// Shows a notification like [`TrayIcon::show_notification_with`](crate::TrayIcon::show_notification_with),
+    /// replying with its id.
+    pub fn show_notification(&self, notification: Notification) -> ProxyReply<NotificationId> {
+        if let Err(err) = notification.validate() {
+            return ProxyReply::ready(Err(err));
+        }
+
+        let (reply, receiver) = bounded(1);
+        let command = ProxyCommand::ShowNotification {
+            id: NotificationId(NOTIFICATION_COUNTER.next()),
+            notification,
+            reply,
+        };
+        match self.inner.send(command) {
+            Ok(()) => ProxyReply { receiver },
+            Err(_) => ProxyReply::ready(Err(Error::Removed)),
+        }
+    }
+
+    fn update(
+        &self,
+        icon: Option<Option<Icon>>,
+        tooltip: Option<Option<String>>,
+        visible: Option<bool>,
+    ) -> ProxyReply {
+        let (reply, receiver) = bounded(1);
+        let command = ProxyCommand::Update {
+            icon,
+            tooltip,
+            visible,
+            reply,
+        };
+        match self.inner.send(command) {
+            Ok(()) => ProxyReply { receiver },
+            Err(_) => ProxyReply::ready(Err(Error::Removed)),
+        }
+    }
+}
+
+/// The result of a [`TrayIconProxy`] command, which may be dropped to ignore it.
+///
+/// Waiting on the thread of the tray icon never returns, since the command runs there.
+#[must_use = "the command runs either way, drop the reply to ignore its result"]
+pub struct ProxyReply<T = ()> {
+    receiver: Receiver<Result<T>>,
+}
+
+impl<T> ProxyReply<T> {
+    fn ready(result: Result<T>) -> Self {
+        let (sender, receiver) = bounded(1);
+        let _ = sender.send(result);
+        Self { receiver }
+    }
+
+    /// Waits for the command to run and returns its result.
+    pub fn wait(self) -> Result<T> {
+        self.receiver.recv().unwrap_or(Err(Error::Removed))
+    }
+
+    /// Waits up to `timeout` for the command to run, `None` if it didn't run yet.
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<Result<T>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(RecvTimeoutError::Timeout) => None,
+            Err(RecvTimeoutError::Disconnected) => Some(Err(Error::Removed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proxy_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync + Clone>() {}
+        assert_send_sync::<TrayIconProxy>();
+    }
+
+    #[test]
+    fn fails_once_tray_icon_is_gone() {
+        let tray_icon = crate::TrayIconBuilder::new().build().unwrap();
+        let proxy = tray_icon.proxy().unwrap();
+        drop(tray_icon);
+
+        assert!(matches!(
+            proxy.set_visible(false).wait(),
+            Err(Error::Removed)
+        ));
+        let reply = proxy.show_notification(Notification::new().title("title"));
+        assert!(matches!(reply.wait(), Err(Error::Removed)));
+    }
+}
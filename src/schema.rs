@@ -0,0 +1,33 @@
+//! JSON schemas of the `dpi` types, which don't implement `JsonSchema` themselves.
+
+#[derive(schemars::JsonSchema)]
+#[schemars(rename = "PhysicalPosition")]
+#[allow(dead_code)]
+pub(crate) struct PhysicalPosition {
+    x: f64,
+    y: f64,
+}
+
+#[derive(schemars::JsonSchema)]
+#[schemars(rename = "PhysicalSize")]
+#[allow(dead_code)]
+pub(crate) struct PhysicalSize {
+    width: u32,
+    height: u32,
+}
+
+#[derive(schemars::JsonSchema)]
+#[schemars(rename = "LogicalPosition")]
+#[allow(dead_code)]
+pub(crate) struct LogicalPosition {
+    x: f64,
+    y: f64,
+}
+
+#[derive(schemars::JsonSchema)]
+#[schemars(rename = "LogicalSize")]
+#[allow(dead_code)]
+pub(crate) struct LogicalSize {
+    width: f64,
+    height: f64,
+}
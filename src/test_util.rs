@@ -0,0 +1,90 @@
+//! Synthetic events for testing how an app handles tray icon events, without a shell.
+
+use crate::{
+    dpi::PhysicalPosition, Monitor, MouseButton, MouseButtonState, Rect, TrayIconEvent, TrayIconId,
+};
+
+impl TrayIconEvent {
+    /// Delivers `event` as if a tray icon sent it, through the same filter, handlers, stream
+    /// and channel as real events.
+    pub fn inject(event: TrayIconEvent) {
+        TrayIconEvent::send(event);
+    }
+
+    /// Creates a [`TrayIconEvent::Click`] at `position`, with an empty rect, a scale factor
+    /// of `1.0` and the current time.
+    pub fn click<I: Into<TrayIconId>, P: Into<PhysicalPosition<f64>>>(
+        id: I,
+        button: MouseButton,
+        button_state: MouseButtonState,
+        position: P,
+    ) -> Self {
+        TrayIconEvent::Click {
+            id: id.into(),
+            position: position.into(),
+            scale_factor: 1.0,
+            rect: Rect::default(),
+            button,
+            button_state,
+            monitor: Monitor::default(),
+            timestamp: crate::platform_impl::current_timestamp(),
+        }
+    }
+
+    /// Creates a [`TrayIconEvent::Enter`] at `position`, with an empty rect, a scale factor
+    /// of `1.0` and the current time.
+    pub fn enter<I: Into<TrayIconId>, P: Into<PhysicalPosition<f64>>>(id: I, position: P) -> Self {
+        TrayIconEvent::Enter {
+            id: id.into(),
+            position: position.into(),
+            scale_factor: 1.0,
+            rect: Rect::default(),
+            timestamp: crate::platform_impl::current_timestamp(),
+        }
+    }
+
+    /// Creates a [`TrayIconEvent::Leave`] at `position`, with an empty rect, a scale factor
+    /// of `1.0` and the current time.
+    pub fn leave<I: Into<TrayIconId>, P: Into<PhysicalPosition<f64>>>(id: I, position: P) -> Self {
+        TrayIconEvent::Leave {
+            id: id.into(),
+            position: position.into(),
+            scale_factor: 1.0,
+            rect: Rect::default(),
+            timestamp: crate::platform_impl::current_timestamp(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn injects_events() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        static HANDLED: AtomicBool = AtomicBool::new(false);
+
+        let event = TrayIconEvent::click(
+            "injected",
+            MouseButton::Left,
+            MouseButtonState::Up,
+            (1., 2.),
+        );
+        assert_eq!(event.position(), Some(PhysicalPosition::new(1., 2.)));
+        assert_eq!(event.button(), Some(MouseButton::Left));
+
+        TrayIconEvent::set_event_handler_for(
+            "injected",
+            Some(|event: TrayIconEvent| {
+                HANDLED.store(
+                    matches!(event, TrayIconEvent::Click { .. }),
+                    Ordering::SeqCst,
+                )
+            }),
+        );
+        TrayIconEvent::inject(event);
+        TrayIconEvent::set_event_handler_for("injected", None::<fn(TrayIconEvent)>);
+        assert!(HANDLED.load(Ordering::SeqCst));
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The latest event time handed out, in milliseconds since the Unix epoch.
+static LAST_EVENT_MILLIS: AtomicU64 = AtomicU64::new(0);
+
+/// When a [`TrayIconEvent`](crate::TrayIconEvent) happened.
+///
+/// With serde, the time is serialized as milliseconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "TimestampRepr", into = "TimestampRepr")
+)]
+pub struct Timestamp {
+    time: SystemTime,
+    tick: u32,
+}
+
+impl Timestamp {
+    /// Creates the timestamp of the message sent at `tick`, as returned by `GetMessageTime`,
+    /// when the tick count is `now_tick` at `now`.
+    ///
+    /// Timestamps never go back in time, even when messages are handled out of order
+    /// or the system clock is changed.
+    pub(crate) fn from_tick(tick: u32, now_tick: u32, now: SystemTime) -> Self {
+        // tick counts wrap around after 49.7 days, messages are never that old
+        let age = now_tick.wrapping_sub(tick) as i32;
+        let millis = unix_millis(now).saturating_sub(age.max(0) as u64);
+        let millis = LAST_EVENT_MILLIS
+            .fetch_max(millis, Ordering::Relaxed)
+            .max(millis);
+
+        Self {
+            time: UNIX_EPOCH + Duration::from_millis(millis),
+            tick,
+        }
+    }
+
+    /// The time of the event.
+    pub fn time(&self) -> SystemTime {
+        self.time
+    }
+
+    /// The tick count of the event as returned by `GetMessageTime`, in milliseconds since
+    /// the system was started. It wraps around after 49.7 days.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+}
+
+impl Default for Timestamp {
+    fn default() -> Self {
+        Self {
+            time: UNIX_EPOCH,
+            tick: 0,
+        }
+    }
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+struct TimestampRepr {
+    time: u64,
+    tick: u32,
+}
+
+#[cfg(feature = "serde")]
+impl From<TimestampRepr> for Timestamp {
+    fn from(repr: TimestampRepr) -> Self {
+        Self {
+            time: UNIX_EPOCH + Duration::from_millis(repr.time),
+            tick: repr.tick,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Timestamp> for TimestampRepr {
+    fn from(timestamp: Timestamp) -> Self {
+        Self {
+            time: unix_millis(timestamp.time),
+            tick: timestamp.tick,
+        }
+    }
+}
+
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Timestamp {
+    fn schema_name() -> String {
+        "Timestamp".into()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        TimestampRepr::json_schema(gen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_timestamps() {
+        let now = SystemTime::now();
+        let millis = |timestamp: Timestamp| unix_millis(timestamp.time());
+
+        let first = Timestamp::from_tick(1_000, 1_500, now);
+        assert_eq!(millis(first), unix_millis(now) - 500);
+        assert_eq!(first.tick(), 1_000);
+
+        // a message sent earlier but handled later doesn't get an earlier time
+        let second = Timestamp::from_tick(900, 1_500, now);
+        assert_eq!(millis(second), millis(first));
+        // neither does one handled after the clock was turned back
+        let third = Timestamp::from_tick(1_600, 1_600, now - Duration::from_secs(60));
+        assert_eq!(millis(third), millis(first));
+
+        // the tick count wrapped around since the message was sent
+        let later = now + Duration::from_secs(1);
+        let wrapped = Timestamp::from_tick(u32::MAX - 99, 100, later);
+        assert_eq!(millis(wrapped), unix_millis(later) - 200);
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use crate::{Notification, NotificationId};
+
+/// A toast notification shown in the Action Center with [`TrayIcon::show_toast`](crate::TrayIcon::show_toast).
+///
+/// ## Example
+///
+/// ```no_run
+/// # let tray_icon: tray_icon_win::TrayIcon = unimplemented!();
+/// use tray_icon_win::Toast;
+///
+/// let toast = Toast::new()
+///     .title("Build finished")
+///     .line("All 42 tests passed")
+///     .image("C:\\icons\\success.png");
+/// tray_icon.show_toast(toast).unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Toast {
+    title: String,
+    lines: Vec<String>,
+    image: Option<PathBuf>,
+}
+
+impl Toast {
+    /// Creates a new empty [`Toast`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the title.
+    pub fn title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Adds a line of body text, Windows shows at most two.
+    pub fn line<S: Into<String>>(mut self, line: S) -> Self {
+        self.lines.push(line.into());
+        self
+    }
+
+    /// Sets an image shown in place of the app logo, e.g. a PNG file. Relative paths are
+    /// resolved against the current directory.
+    pub fn image<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.image = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Returns the toast XML for this toast.
+    pub(crate) fn to_xml(&self) -> String {
+        let mut xml = String::from(r#"<toast><visual><binding template="ToastGeneric">"#);
+        for text in std::iter::once(&self.title).chain(&self.lines) {
+            xml.push_str("<text>");
+            xml.push_str(&escape(text));
+            xml.push_str("</text>");
+        }
+        if let Some(path) = &self.image {
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+            xml.push_str(r#"<image placement="appLogoOverride" src="file:///"#);
+            xml.push_str(&escape(&path.to_string_lossy().replace('\\', "/")));
+            xml.push_str(r#""/>"#);
+        }
+        xml.push_str("</binding></visual></toast>");
+        xml
+    }
+
+    /// Returns the notification balloon shown instead of this toast when toasts aren't available.
+    pub(crate) fn to_notification(&self) -> Notification {
+        Notification::new()
+            .title(self.title.clone())
+            .body(self.lines.join("\n"))
+            .kind(crate::NotificationKind::User)
+    }
+}
+
+/// How [`TrayIcon::show_toast`](crate::TrayIcon::show_toast) showed a toast.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ShownToast {
+    /// Shown as a toast notification.
+    Toast(NotificationId),
+    /// Shown as a notification balloon, because WinRT toasts aren't available.
+    Balloon(NotificationId),
+}
+
+impl ShownToast {
+    /// Returns the id events of the notification are reported with.
+    pub fn id(&self) -> NotificationId {
+        match self {
+            ShownToast::Toast(id) | ShownToast::Balloon(id) => *id,
+        }
+    }
+}
+
+/// Escapes `text` for use in XML text and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_toast_xml() {
+        let toast = Toast::new()
+            .title("Tom & Jerry")
+            .line("<b>1</b>")
+            .line("two")
+            .image("C:\\icons\\a\"b.png");
+        assert_eq!(
+            toast.to_xml(),
+            concat!(
+                r#"<toast><visual><binding template="ToastGeneric">"#,
+                "<text>Tom &amp; Jerry</text><text>&lt;b&gt;1&lt;/b&gt;</text><text>two</text>",
+                r#"<image placement="appLogoOverride" src="file:///C:/icons/a&quot;b.png"/>"#,
+                "</binding></visual></toast>"
+            )
+        );
+
+        let notification = toast.to_notification();
+        assert_eq!(notification.title, "Tom & Jerry");
+        assert_eq!(notification.body, "<b>1</b>\ntwo");
+    }
+}
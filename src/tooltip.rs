@@ -0,0 +1,175 @@
+use std::borrow::Cow;
+
+/// The maximum length of a tooltip, in UTF-16 code units.
+pub(crate) const MAX_TOOLTIP_LEN: usize = 127;
+
+/// Helpers for building tooltips, see [`TrayIcon::set_tooltip`](crate::TrayIcon::set_tooltip).
+///
+/// Tooltips may contain line breaks, `\r\n` and `\r` are turned into the `\n` the shell
+/// expects. Each line break counts as one UTF-16 code unit of the 127 available.
+pub struct Tooltip;
+
+impl Tooltip {
+    /// Joins `lines` into a multiline tooltip.
+    ///
+    /// Tooltips longer than 127 UTF-16 code units return [`Error::TooltipTooLong`](crate::Error::TooltipTooLong).
+    pub fn lines<I, S>(lines: I) -> crate::Result<String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut tooltip = String::new();
+        for (i, line) in lines.into_iter().enumerate() {
+            if i > 0 {
+                tooltip.push('\n');
+            }
+            tooltip.push_str(line.as_ref());
+        }
+
+        let tooltip = normalize(&tooltip).into_owned();
+        validate(&tooltip)?;
+        Ok(tooltip)
+    }
+}
+
+/// Turns `\r\n` and `\r` line breaks into `\n`.
+pub(crate) fn normalize(tooltip: &str) -> Cow<'_, str> {
+    if tooltip.contains('\r') {
+        Cow::Owned(tooltip.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(tooltip)
+    }
+}
+
+/// Checks that `tooltip` fits into `NOTIFYICONDATAW::szTip` along with its nul terminator.
+pub(crate) fn validate(tooltip: &str) -> crate::Result<()> {
+    let len = tooltip.encode_utf16().count();
+    if len > MAX_TOOLTIP_LEN {
+        return Err(crate::Error::TooltipTooLong {
+            len,
+            max: MAX_TOOLTIP_LEN,
+        });
+    }
+    Ok(())
+}
+
+/// Returns the longest prefix of `tooltip` that fits, cut on a char boundary.
+///
+/// Emoji joined into a sequence with zero width joiners, variation selectors or skin tone
+/// modifiers aren't split up either, the whole sequence is cut instead.
+pub(crate) fn truncate(tooltip: &str) -> &str {
+    let mut len = 0;
+    let Some((mut end, _)) = tooltip.char_indices().find(|(_, c)| {
+        len += c.len_utf16();
+        len > MAX_TOOLTIP_LEN
+    }) else {
+        return tooltip;
+    };
+
+    while let Some(last) = tooltip[..end].chars().next_back() {
+        let next = tooltip[end..].chars().next();
+        if last != ZWJ && !next.is_some_and(continues_sequence) {
+            break;
+        }
+        end -= last.len_utf8();
+    }
+    &tooltip[..end]
+}
+
+const ZWJ: char = '\u{200D}';
+
+/// Returns whether `c` attaches to the character before it.
+fn continues_sequence(c: char) -> bool {
+    matches!(c, ZWJ | '\u{FE0E}' | '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_length() {
+        assert!(validate(&"a".repeat(MAX_TOOLTIP_LEN)).is_ok());
+        assert!(matches!(
+            validate(&"a".repeat(MAX_TOOLTIP_LEN + 1)),
+            Err(crate::Error::TooltipTooLong { len: 128, max: 127 })
+        ));
+        // umlauts are a single UTF-16 code unit but two bytes
+        assert!(validate(&"ü".repeat(MAX_TOOLTIP_LEN)).is_ok());
+        // emoji are two UTF-16 code units
+        assert!(validate(&"🦀".repeat(63)).is_ok());
+        assert!(matches!(
+            validate(&"🦀".repeat(64)),
+            Err(crate::Error::TooltipTooLong { len: 128, .. })
+        ));
+    }
+
+    #[test]
+    fn builds_multiline_tooltips() {
+        assert_eq!(
+            Tooltip::lines(["Connected", "1.2 MB/s"]).unwrap(),
+            "Connected\n1.2 MB/s"
+        );
+        assert_eq!(
+            Tooltip::lines(["App", "Connected", "↓ 1.2 MB/s", "↑ 80 kB/s"]).unwrap(),
+            "App\nConnected\n↓ 1.2 MB/s\n↑ 80 kB/s"
+        );
+        // lines with their own line breaks are normalized too
+        assert_eq!(Tooltip::lines(["a\r\nb", "c\rd"]).unwrap(), "a\nb\nc\nd");
+    }
+
+    #[test]
+    fn counts_line_breaks() {
+        // three lines with two line breaks are exactly 127 code units
+        let line = "a".repeat(42);
+        let last = "a".repeat(41);
+        let tooltip = Tooltip::lines([&line, &line, &last]).unwrap();
+        assert_eq!(tooltip.encode_utf16().count(), MAX_TOOLTIP_LEN);
+
+        assert!(matches!(
+            Tooltip::lines([&line, &line, &line]),
+            Err(crate::Error::TooltipTooLong { len: 128, max: 127 })
+        ));
+        // with `\r\n` it would be 129 code units, but it is normalized to 127
+        assert_eq!(normalize(&format!("{line}\r\n{line}\r\n{last}")), tooltip);
+    }
+
+    #[test]
+    fn truncates_on_char_boundary() {
+        assert_eq!(truncate("short"), "short");
+
+        let umlauts = "ü".repeat(MAX_TOOLTIP_LEN + 10);
+        assert_eq!(truncate(&umlauts), "ü".repeat(MAX_TOOLTIP_LEN));
+
+        // the last emoji doesn't fit in the one remaining code unit
+        let emoji = format!("a{}", "🦀".repeat(64));
+        assert_eq!(truncate(&emoji), format!("a{}", "🦀".repeat(63)));
+        assert!(validate(truncate(&emoji)).is_ok());
+    }
+
+    #[test]
+    fn truncates_on_emoji_sequences() {
+        // a family emoji is 11 UTF-16 code units: 4 emoji joined by 3 zero width joiners
+        let family = "👨\u{200D}👩\u{200D}👧\u{200D}👦";
+        let fill = "a".repeat(MAX_TOOLTIP_LEN - 11);
+        let fits = format!("{fill}{family}");
+        assert_eq!(truncate(&fits), fits);
+
+        // cutting anywhere in the sequence drops all of it
+        for extra in 1..=10 {
+            let tooltip = format!("{}{fill}{family}", "a".repeat(extra));
+            assert_eq!(truncate(&tooltip), format!("{}{fill}", "a".repeat(extra)));
+        }
+
+        // so do emoji with a skin tone modifier or variation selector
+        let thumbs_up = "👍\u{1F3FD}";
+        let fill = "a".repeat(MAX_TOOLTIP_LEN - 4);
+        assert_eq!(
+            truncate(&format!("{fill}{thumbs_up}")),
+            format!("{fill}{thumbs_up}")
+        );
+        assert_eq!(truncate(&format!("a{fill}{thumbs_up}")), format!("a{fill}"));
+        let fill = "a".repeat(MAX_TOOLTIP_LEN - 2);
+        assert_eq!(truncate(&format!("a{fill}❤\u{FE0F}")), format!("a{fill}"));
+    }
+}
@@ -1,8 +1,17 @@
-use std::{convert::Infallible, str::FromStr};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    str::FromStr,
+    sync::{LazyLock, Mutex, PoisonError},
+};
+
+/// How many live tray icons have each id.
+static LIVE_IDS: LazyLock<Mutex<HashMap<TrayIconId, usize>>> = LazyLock::new(Default::default);
 
 /// An unique id that is associated with a tray icon.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct TrayIconId(pub String);
 
 impl TrayIconId {
@@ -68,9 +77,39 @@ impl PartialEq<&TrayIconId> for TrayIconId {
     }
 }
 
+/// Marks the id of a live tray icon as taken, until this is dropped.
+pub(crate) struct IdRegistration(TrayIconId);
+
+impl IdRegistration {
+    /// Takes `id`, failing with [`Error::DuplicateId`](crate::Error::DuplicateId) if a live
+    /// tray icon has it, unless `allow_duplicate`.
+    pub fn new(id: TrayIconId, allow_duplicate: bool) -> crate::Result<Self> {
+        let mut ids = LIVE_IDS.lock().unwrap_or_else(PoisonError::into_inner);
+        let count = ids.entry(id.clone()).or_default();
+        if *count > 0 && !allow_duplicate {
+            return Err(crate::Error::DuplicateId(id));
+        }
+        *count += 1;
+        Ok(Self(id))
+    }
+}
+
+impl Drop for IdRegistration {
+    fn drop(&mut self) {
+        let mut ids = LIVE_IDS.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(count) = ids.get_mut(&self.0) {
+            *count -= 1;
+            if *count == 0 {
+                ids.remove(&self.0);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::TrayIconId;
+    use super::IdRegistration;
+    use crate::{Error, TrayIconId};
 
     #[test]
     fn is_eq() {
@@ -82,4 +121,21 @@ mod test {
         assert_eq!(&TrayIconId::new("t"), &TrayIconId::new("t"));
         assert_eq!(TrayIconId::new("t").as_ref(), "t");
     }
+
+    #[test]
+    fn rejects_duplicate_ids() {
+        let id = TrayIconId::new("registered");
+        let first = IdRegistration::new(id.clone(), false).unwrap();
+        assert!(matches!(
+            IdRegistration::new(id.clone(), false),
+            Err(Error::DuplicateId(duplicate)) if duplicate == id
+        ));
+
+        // duplicates may be allowed, the id is taken until all are dropped
+        let second = IdRegistration::new(id.clone(), true).unwrap();
+        drop(first);
+        assert!(IdRegistration::new(id.clone(), false).is_err());
+        drop(second);
+        assert!(IdRegistration::new(id, false).is_ok());
+    }
 }
@@ -0,0 +1,44 @@
+use crate::{menu, Icon};
+
+/// Changes applied at once with [`TrayIcon::update`](crate::TrayIcon::update).
+///
+/// Only the attributes that are set are changed, e.g.
+/// `TrayIconUpdate::new().icon(Some(busy)).tooltip(Some("Syncing..."))`.
+#[derive(Default)]
+pub struct TrayIconUpdate {
+    pub(crate) icon: Option<Option<Icon>>,
+    pub(crate) tooltip: Option<Option<String>>,
+    pub(crate) visible: Option<bool>,
+    pub(crate) menu: Option<Option<Box<dyn menu::ContextMenu>>>,
+}
+
+impl TrayIconUpdate {
+    /// Creates an update that changes nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the icon, `None` removes it.
+    pub fn icon(mut self, icon: Option<Icon>) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Sets the tooltip, `None` removes it.
+    pub fn tooltip<S: AsRef<str>>(mut self, tooltip: Option<S>) -> Self {
+        self.tooltip = Some(tooltip.map(|t| t.as_ref().to_string()));
+        self
+    }
+
+    /// Shows or hides the tray icon.
+    pub fn visible(mut self, visible: bool) -> Self {
+        self.visible = Some(visible);
+        self
+    }
+
+    /// Sets the menu, `None` removes it.
+    pub fn menu(mut self, menu: Option<Box<dyn menu::ContextMenu>>) -> Self {
+        self.menu = Some(menu);
+        self
+    }
+}